@@ -0,0 +1,5 @@
+-- Server time of the last write to a guest (not the client-supplied StructuredValue.updated_at), used to filter
+-- listings and incremental exports; existing guests are stamped with the migration time
+ALTER TABLE guests ADD COLUMN updated_at TEXT NOT NULL DEFAULT '';
+UPDATE guests SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now');
+CREATE INDEX IF NOT EXISTS idx_guests_updated_at ON guests (updated_at);
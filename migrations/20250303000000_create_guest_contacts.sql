@@ -0,0 +1,20 @@
+-- Normalized projection of guests.mail / guests.phone for lookups (lowercased email, digits-only phone)
+CREATE TABLE IF NOT EXISTS guest_contacts (
+    guest_id TEXT NOT NULL REFERENCES guests (id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    normalized TEXT NOT NULL,
+    PRIMARY KEY (guest_id, kind, normalized)
+);
+CREATE INDEX IF NOT EXISTS idx_guest_contacts_lookup ON guest_contacts (kind, normalized);
+
+-- Backfill existing guests (phone: common separators stripped; rows are re-indexed on next update)
+INSERT OR IGNORE INTO guest_contacts (guest_id, kind, normalized)
+SELECT guests.id, 'mail', lower(trim(json_extract(json_each.value, '$.value')))
+FROM guests, json_each(guests.mail)
+WHERE trim(json_extract(json_each.value, '$.value')) <> '';
+
+INSERT OR IGNORE INTO guest_contacts (guest_id, kind, normalized)
+SELECT guests.id, 'phone', replace(replace(replace(replace(replace(replace(
+    json_extract(json_each.value, '$.value'), ' ', ''), '-', ''), '.', ''), '(', ''), ')', ''), '+', '')
+FROM guests, json_each(guests.phone)
+WHERE trim(json_extract(json_each.value, '$.value')) <> '';
@@ -0,0 +1,2 @@
+-- Optimistic concurrency: version incremented on every write (exposed as ETag)
+ALTER TABLE guests ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
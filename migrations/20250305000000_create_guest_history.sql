@@ -0,0 +1,13 @@
+-- Append-only change history of guests: one row per modified field (or list element)
+CREATE TABLE IF NOT EXISTS guest_history (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    guest_id TEXT NOT NULL,
+    operation TEXT NOT NULL,
+    field TEXT NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    source TEXT,
+    changed_at TEXT NOT NULL,
+    trace_id TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_guest_history_guest ON guest_history (guest_id, seq);
@@ -0,0 +1,3 @@
+-- Soft delete: tombstone date, hidden from reads until restored or purged
+ALTER TABLE guests ADD COLUMN deleted_at TEXT;
+CREATE INDEX IF NOT EXISTS idx_guests_deleted_at ON guests (deleted_at);
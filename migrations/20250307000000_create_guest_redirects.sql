@@ -0,0 +1,8 @@
+-- Redirects left by guest merges: merged (source) id -> surviving guest id
+CREATE TABLE IF NOT EXISTS guest_redirects (
+    source_id TEXT PRIMARY KEY NOT NULL,
+    target_id TEXT NOT NULL,
+    merged_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_guest_redirects_target ON guest_redirects (target_id);
@@ -0,0 +1,9 @@
+-- Result of the last bulk duplicate scan: ranked candidate pairs (rank = seq)
+CREATE TABLE IF NOT EXISTS guest_duplicates (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    guest_id TEXT NOT NULL,
+    candidate_id TEXT NOT NULL,
+    score REAL NOT NULL,
+    reasons TEXT NOT NULL DEFAULT '[]',
+    scanned_at TEXT NOT NULL
+);
@@ -0,0 +1,22 @@
+-- Replace anonymous opt_outs (StructuredValue<bool>) with typed consents per channel and purpose (JSON array).
+-- Legacy opt-outs were only used for email campaigns: the latest entry becomes an email/marketing consent.
+ALTER TABLE guests ADD COLUMN consents TEXT NOT NULL DEFAULT '[]';
+UPDATE guests SET consents = (
+    SELECT json_array(json_object(
+        'value', json_object(
+            'channel', 'email',
+            'purpose', 'marketing',
+            'status', CASE WHEN json_extract(value, '$.value') THEN 'opted_out' ELSE 'opted_in' END,
+            'legal_basis', 'consent',
+            'collected_at', NULL
+        ),
+        'from', json_extract(value, '$.from'),
+        'updated_at', json_extract(value, '$.updated_at'),
+        'preferred_at', NULL
+    ))
+    FROM json_each(guests.opt_outs)
+    ORDER BY json_extract(value, '$.updated_at') DESC
+    LIMIT 1
+)
+WHERE json_array_length(opt_outs) > 0;
+ALTER TABLE guests DROP COLUMN opt_outs;
@@ -0,0 +1,11 @@
+-- Opt-out events published for a guest (kept for GDPR subject access requests)
+CREATE TABLE IF NOT EXISTS guest_opt_out_events (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    guest_id TEXT NOT NULL,
+    channels TEXT NOT NULL DEFAULT '[]',
+    reason TEXT NOT NULL,
+    trace_id TEXT,
+    published INTEGER NOT NULL,
+    occurred_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_guest_opt_out_events_guest_id ON guest_opt_out_events (guest_id);
@@ -0,0 +1,10 @@
+-- Erasure certificates (GDPR right to be forgotten): the guest row is anonymized, not deleted
+CREATE TABLE IF NOT EXISTS guest_erasures (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    guest_id TEXT NOT NULL,
+    erased_at TEXT NOT NULL,
+    requester TEXT NOT NULL,
+    trace_id TEXT,
+    fields TEXT NOT NULL DEFAULT '[]'
+);
+CREATE INDEX IF NOT EXISTS idx_guest_erasures_guest_id ON guest_erasures (guest_id);
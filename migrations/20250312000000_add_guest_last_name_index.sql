@@ -0,0 +1,5 @@
+-- Duplicate-scan blocking key (last name initial, or its HMAC when PII is encrypted at rest)
+ALTER TABLE guests ADD COLUMN last_name_index TEXT;
+UPDATE guests SET last_name_index = lower(substr(trim(json_extract(last_name, '$.value')), 1, 1))
+WHERE trim(json_extract(last_name, '$.value')) <> '';
+CREATE INDEX IF NOT EXISTS idx_guests_last_name_index ON guests (last_name_index);
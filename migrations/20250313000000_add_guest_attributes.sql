@@ -0,0 +1,10 @@
+-- Custom guest attributes (JSON object name -> StructuredValue) and their definition registry
+ALTER TABLE guests ADD COLUMN attributes TEXT NOT NULL DEFAULT '{}';
+CREATE TABLE IF NOT EXISTS guest_attribute_definitions (
+    name TEXT PRIMARY KEY,
+    attribute_type TEXT NOT NULL,
+    enum_values TEXT NOT NULL DEFAULT '[]',
+    required INTEGER NOT NULL DEFAULT 0,
+    description TEXT,
+    updated_at TEXT NOT NULL
+);
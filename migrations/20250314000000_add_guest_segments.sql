@@ -0,0 +1,7 @@
+-- Saved guest segments: a name and a filter expression evaluated on demand
+CREATE TABLE IF NOT EXISTS guest_segments (
+    name TEXT PRIMARY KEY,
+    expression TEXT NOT NULL,
+    description TEXT,
+    updated_at TEXT NOT NULL
+);
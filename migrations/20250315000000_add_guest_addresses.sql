@@ -0,0 +1,2 @@
+-- Structured postal addresses (JSON list of StructuredValue<Address>, sealed like mail/phone when a PII key is set)
+ALTER TABLE guests ADD COLUMN addresses TEXT NOT NULL DEFAULT '[]';
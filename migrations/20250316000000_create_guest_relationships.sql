@@ -0,0 +1,10 @@
+-- Relationships between guests (spouse, child, colleague, booker_for, household_member)
+CREATE TABLE IF NOT EXISTS guest_relationships (
+    guest_id TEXT NOT NULL,
+    related_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (guest_id, related_id, kind)
+);
+
+CREATE INDEX IF NOT EXISTS idx_guest_relationships_related ON guest_relationships (related_id);
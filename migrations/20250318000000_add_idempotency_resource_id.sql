@@ -0,0 +1,6 @@
+-- Resource created by the recorded response (ex. guest id) and date its response was scrubbed
+-- (guest erased or deleted): the key then refuses replays instead of returning personal data
+ALTER TABLE idempotency_keys ADD COLUMN resource_id TEXT;
+ALTER TABLE idempotency_keys ADD COLUMN erased_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_resource_id ON idempotency_keys (resource_id);
@@ -0,0 +1,3 @@
+-- Erasure date of a guest (GDPR), set only by the erasure: an erased guest is no longer inferred from its names
+ALTER TABLE guests ADD COLUMN erased_at TEXT;
+UPDATE guests SET erased_at = (SELECT MIN(erased_at) FROM guest_erasures WHERE guest_erasures.guest_id = guests.id);
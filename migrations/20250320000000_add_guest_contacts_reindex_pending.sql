@@ -0,0 +1,4 @@
+-- Guests whose contacts predate their canonical form (E.164 phones, punycode mail domains): re-indexed once at
+-- startup, then cleared, so values that cannot be canonicalized (or erased ones) are not rewritten on every start
+ALTER TABLE guests ADD COLUMN contacts_reindex_pending INTEGER NOT NULL DEFAULT 0;
+UPDATE guests SET contacts_reindex_pending = 1;
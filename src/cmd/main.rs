@@ -1,8 +1,14 @@
 //! Point d'entrée : wiring domaine → store → server (style DDD, équivalent cmd/server en Go).
 
+use std::time::Duration;
+
+use hello_world_api::domain::{PhoneRegion, SurvivorshipRules};
 use hello_world_api::environment;
-use hello_world_api::server::{router, spawn_guests_stream_tasks, AppState};
-use hello_world_api::store::Store;
+use hello_world_api::server::{
+    router, spawn_guest_duplicate_scan_task, spawn_guest_purge_task, spawn_guests_stream_tasks,
+    spawn_idempotency_purge_task, AppState, Settings,
+};
+use hello_world_api::store::{PiiCipher, Store};
 use tokio::signal;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
@@ -25,14 +31,80 @@ async fn main() {
         .await
         .expect("migrations SQLite");
 
+    let cipher = PiiCipher::parse(
+        &env_vars.pii_keys,
+        &env_vars.pii_current_key_id,
+        &env_vars.pii_index_key,
+    )
+    .unwrap_or_else(|e| panic!("ECH_PII_KEYS / ECH_PII_KEY_ID / ECH_PII_INDEX_KEY: {e}"));
+    match &cipher {
+        Some(cipher) => tracing::info!(
+            key_id = cipher.current_key_id(),
+            "guests: PII encrypted at rest"
+        ),
+        None => tracing::warn!("guests: PII stored in clear (ECH_PII_KEYS not set)"),
+    }
+    let store = Store::new(pool, cipher);
+
+    // `reencrypt-guests` : réécrit les données personnelles avec la clé courante (rotation), puis quitte.
+    if std::env::args().nth(1).as_deref() == Some("reencrypt-guests") {
+        let count = store
+            .guests
+            .reencrypt_all()
+            .await
+            .expect("réécriture des données personnelles");
+        tracing::info!(count, "guests: re-encryption done");
+        return;
+    }
+
+    let default_phone_region: Option<PhoneRegion> = Some(env_vars.default_phone_region.trim())
+        .filter(|r| !r.is_empty())
+        .map(|r| {
+            r.to_uppercase()
+                .parse()
+                .expect("ECH_DEFAULT_PHONE_REGION: code pays ISO 3166-1 alpha-2 (ex: FR)")
+        });
+
+    // Coordonnées antérieures à la canonicalisation (ex. index `guest_contacts` en chiffres saisis) : forme E.164.
+    store
+        .guests
+        .reindex_contacts(default_phone_region)
+        .await
+        .expect("réindexation des coordonnées");
+
     let nats = async_nats::connect(&env_vars.nats_url)
         .await
         .expect("connexion NATS (démarre le container avec: docker compose up -d)");
 
     spawn_guests_stream_tasks(nats.clone());
 
-    let store = Store::new(pool);
-    let state = AppState::new(store, nats);
+    spawn_guest_purge_task(
+        store.clone(),
+        chrono::Duration::days(env_vars.guest_retention_days),
+        Duration::from_secs(env_vars.guest_purge_interval_secs),
+    );
+    spawn_guest_duplicate_scan_task(
+        store.clone(),
+        Duration::from_secs(env_vars.guest_duplicate_scan_interval_secs),
+    );
+    let idempotency_ttl = chrono::Duration::hours(env_vars.idempotency_ttl_hours);
+    spawn_idempotency_purge_task(
+        store.clone(),
+        idempotency_ttl,
+        Duration::from_secs(env_vars.guest_purge_interval_secs),
+    );
+
+    let settings = Settings {
+        guest_restore_grace: chrono::Duration::days(env_vars.guest_restore_grace_days),
+        default_phone_region,
+        survivorship: SurvivorshipRules {
+            source_priority: env_vars.survivorship_source_priority,
+            verified_sources: env_vars.survivorship_verified_sources,
+            prefer_recent: env_vars.survivorship_prefer_recent,
+        },
+        idempotency_ttl,
+    };
+    let state = AppState::new(store, nats, settings);
 
     let app = router(state);
 
@@ -0,0 +1,345 @@
+//! Adresses postales d'un guest : composants structurés (lignes, code postal, ville, région, pays ISO 3166-1)
+//! et contrôle du code postal selon le pays.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::ValidationError;
+
+/// Nombre maximal de lignes d'adresse (numéro, voie, complément…).
+const MAX_ADDRESS_LINES: usize = 4;
+/// Longueur maximale d'un composant (ligne, ville, région).
+const MAX_COMPONENT_LEN: usize = 200;
+/// Longueur maximale d'un code postal d'un pays sans format connu.
+const MAX_POSTAL_CODE_LEN: usize = 12;
+
+/// Codes pays ISO 3166-1 alpha-2 (triés).
+const ISO_COUNTRIES: &str = "AD AE AF AG AI AL AM AO AQ AR AS AT AU AW AX AZ BA BB BD BE BF BG BH BI BJ BL BM BN BO BQ \
+    BR BS BT BV BW BY BZ CA CC CD CF CG CH CI CK CL CM CN CO CR CU CV CW CX CY CZ DE DJ DK DM DO DZ EC EE EG EH ER ES ET \
+    FI FJ FK FM FO FR GA GB GD GE GF GG GH GI GL GM GN GP GQ GR GS GT GU GW GY HK HM HN HR HT HU ID IE IL IM IN IO IQ IR \
+    IS IT JE JM JO JP KE KG KH KI KM KN KP KR KW KY KZ LA LB LC LI LK LR LS LT LU LV LY MA MC MD ME MF MG MH MK ML MM MN \
+    MO MP MQ MR MS MT MU MV MW MX MY MZ NA NC NE NF NG NI NL NO NP NR NU NZ OM PA PE PF PG PH PK PL PM PN PR PS PT PW PY \
+    QA RE RO RS RU RW SA SB SC SD SE SG SH SI SJ SK SL SM SN SO SR SS ST SV SX SY SZ TC TD TF TG TH TJ TK TL TM TN TO TR \
+    TT TV TW TZ UA UG UM US UY UZ VA VC VE VG VI VN VU WF WS YE YT ZA ZM ZW";
+
+/// Formats de code postal par pays (obligatoire pour ces pays) : `9` = chiffre, `A` = lettre,
+/// espace = espace facultatif, autre caractère = littéral.
+const POSTAL_CODE_FORMATS: [(&str, &[&str]); 30] = [
+    ("AT", &["9999"]),
+    ("AU", &["9999"]),
+    ("BE", &["9999"]),
+    ("BR", &["99999-999"]),
+    ("CA", &["A9A 9A9"]),
+    ("CH", &["9999"]),
+    ("CN", &["999999"]),
+    ("CZ", &["999 99"]),
+    ("DE", &["99999"]),
+    ("DK", &["9999"]),
+    ("ES", &["99999"]),
+    ("FI", &["99999"]),
+    ("FR", &["99999"]),
+    (
+        "GB",
+        &[
+            "A9 9AA", "A99 9AA", "A9A 9AA", "AA9 9AA", "AA99 9AA", "AA9A 9AA",
+        ],
+    ),
+    ("GR", &["999 99"]),
+    ("HU", &["9999"]),
+    ("IN", &["999999"]),
+    ("IT", &["99999"]),
+    ("JP", &["999-9999"]),
+    ("LU", &["9999"]),
+    ("MC", &["99999"]),
+    ("MX", &["99999"]),
+    ("NL", &["9999 AA"]),
+    ("NO", &["9999"]),
+    ("NZ", &["9999"]),
+    ("PL", &["99-999"]),
+    ("PT", &["9999-999"]),
+    ("RU", &["999999"]),
+    ("SE", &["999 99"]),
+    ("US", &["99999", "99999-9999"]),
+];
+
+/// Adresse postale.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Address {
+    /// Lignes d'adresse (au moins une), dans l'ordre d'impression.
+    pub lines: Vec<String>,
+    #[serde(default)]
+    pub postal_code: Option<String>,
+    pub city: String,
+    /// Région, état ou province.
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Code pays ISO 3166-1 alpha-2 (ex. `FR`).
+    pub country: String,
+}
+
+/// Forme de comparaison d'un composant : sans casse, espaces réduits.
+fn fold(s: &str) -> String {
+    s.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn fold_option(s: &Option<String>) -> String {
+    s.as_deref().map(fold).unwrap_or_default()
+}
+
+impl Address {
+    /// Deux adresses désignent le même lieu (composants comparés sans casse ni espaces superflus).
+    pub fn same_as(&self, other: &Address) -> bool {
+        self.lines.len() == other.lines.len()
+            && self
+                .lines
+                .iter()
+                .zip(&other.lines)
+                .all(|(a, b)| fold(a) == fold(b))
+            && fold_option(&self.postal_code).replace(' ', "")
+                == fold_option(&other.postal_code).replace(' ', "")
+            && fold(&self.city) == fold(&other.city)
+            && fold_option(&self.region) == fold_option(&other.region)
+            && fold(&self.country) == fold(&other.country)
+    }
+
+    /// Adresse sur une ligne (ex. `1 rue de la Paix, 75002 Paris, FR`) : libellé des éléments de liste
+    /// (historique, survie) et adresse renvoyée par la joignabilité postale.
+    pub fn label(&self) -> String {
+        let locality = [
+            self.postal_code.as_deref(),
+            Some(self.city.as_str()),
+            self.region.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+        self.lines
+            .iter()
+            .map(|l| l.trim())
+            .chain([locality.as_str(), self.country.trim()])
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Forme canonique avant écriture : composants sans espaces superflus, pays et code postal en majuscules,
+    /// composants facultatifs vides retirés.
+    pub fn canonicalize(&mut self) {
+        let clean = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ");
+        self.lines = self.lines.iter().map(|l| clean(l)).collect();
+        self.city = clean(&self.city);
+        self.country = self.country.trim().to_uppercase();
+        self.postal_code = self
+            .postal_code
+            .as_deref()
+            .map(|p| clean(p).to_uppercase())
+            .filter(|p| !p.is_empty());
+        self.region = self.region.as_deref().map(clean).filter(|r| !r.is_empty());
+    }
+
+    /// Valide l'adresse (`field` préfixe les messages, ex. `addresses[0]`).
+    pub fn validate(&self, field: &str) -> Result<(), ValidationError> {
+        if self.lines.iter().all(|l| l.trim().is_empty()) {
+            return Err(ValidationError(format!(
+                "{}.lines: au moins une ligne",
+                field
+            )));
+        }
+        if self.lines.len() > MAX_ADDRESS_LINES {
+            return Err(ValidationError(format!(
+                "{}.lines: {} lignes au plus",
+                field, MAX_ADDRESS_LINES
+            )));
+        }
+        if self.city.trim().is_empty() {
+            return Err(ValidationError(format!(
+                "{}.city: ne peut pas être vide",
+                field
+            )));
+        }
+        let too_long = self
+            .lines
+            .iter()
+            .chain([&self.city])
+            .chain(self.region.iter())
+            .any(|s| s.chars().count() > MAX_COMPONENT_LEN);
+        if too_long {
+            return Err(ValidationError(format!(
+                "{}: {} caractères au plus par composant",
+                field, MAX_COMPONENT_LEN
+            )));
+        }
+        let country = self.country.trim().to_uppercase();
+        if country.len() != 2 || !ISO_COUNTRIES.split_whitespace().any(|c| c == country) {
+            return Err(ValidationError(format!(
+                "{}.country: code pays ISO 3166-1 alpha-2 invalide '{}'",
+                field, self.country
+            )));
+        }
+        validate_postal_code(
+            self.postal_code
+                .as_deref()
+                .map(str::trim)
+                .filter(|p| !p.is_empty()),
+            &country,
+            field,
+        )
+    }
+}
+
+/// Le code postal respecte-t-il le format (`9` chiffre, `A` lettre, espace facultatif) ?
+fn matches_format(code: &[char], format: &[char]) -> bool {
+    match (format.first(), code.first()) {
+        (None, _) => code.is_empty(),
+        (Some(' '), Some(' ')) => matches_format(&code[1..], &format[1..]),
+        (Some(' '), _) => matches_format(code, &format[1..]),
+        (Some(_), None) => false,
+        (Some(f), Some(c)) => {
+            let ok = match f {
+                '9' => c.is_ascii_digit(),
+                'A' => c.is_ascii_alphabetic(),
+                literal => c == literal,
+            };
+            ok && matches_format(&code[1..], &format[1..])
+        }
+    }
+}
+
+/// Code postal : obligatoire et au format du pays si celui-ci est connu, sinon libre (alphanumérique, court).
+fn validate_postal_code(
+    code: Option<&str>,
+    country: &str,
+    field: &str,
+) -> Result<(), ValidationError> {
+    let formats = POSTAL_CODE_FORMATS
+        .iter()
+        .find(|(c, _)| *c == country)
+        .map(|(_, formats)| *formats);
+    match (code, formats) {
+        (None, Some(_)) => Err(ValidationError(format!(
+            "{}.postal_code: obligatoire pour {}",
+            field, country
+        ))),
+        (None, None) => Ok(()),
+        (Some(code), Some(formats)) => {
+            let chars: Vec<char> = code.to_uppercase().chars().collect();
+            if formats
+                .iter()
+                .any(|f| matches_format(&chars, &f.chars().collect::<Vec<_>>()))
+            {
+                Ok(())
+            } else {
+                Err(ValidationError(format!(
+                    "{}.postal_code: '{}' invalide pour {} (format {})",
+                    field,
+                    code,
+                    country,
+                    formats.join(" ou ")
+                )))
+            }
+        }
+        (Some(code), None) => {
+            let valid = code.len() <= MAX_POSTAL_CODE_LEN
+                && code
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == '-');
+            if valid {
+                Ok(())
+            } else {
+                Err(ValidationError(format!(
+                    "{}.postal_code: '{}' invalide (lettres, chiffres, espace ou tiret, {} caractères au plus)",
+                    field, code, MAX_POSTAL_CODE_LEN
+                )))
+            }
+        }
+    }
+}
+
+/// Valide une liste d'adresses (adresse, preferred_at) : au plus une préférée, chaque adresse valide,
+/// sans doublon (`addresses[i]` dans les messages).
+pub fn validate_addresses<'a>(
+    addresses: impl IntoIterator<Item = (&'a Address, Option<DateTime<Utc>>)>,
+) -> Result<(), ValidationError> {
+    let addresses: Vec<(&Address, Option<DateTime<Utc>>)> = addresses.into_iter().collect();
+    if addresses
+        .iter()
+        .filter(|(_, preferred)| preferred.is_some())
+        .count()
+        > 1
+    {
+        return Err(ValidationError(
+            "addresses: au plus une adresse peut avoir preferred_at".into(),
+        ));
+    }
+    for (i, (address, _)) in addresses.iter().enumerate() {
+        let field = format!("addresses[{}]", i);
+        address.validate(&field)?;
+        if addresses[..i]
+            .iter()
+            .any(|(other, _)| other.same_as(address))
+        {
+            return Err(ValidationError(format!("{}: adresse en double", field)));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(line: &str, postal_code: &str, city: &str) -> Address {
+        Address {
+            lines: vec![line.to_string()],
+            postal_code: Some(postal_code.to_string()),
+            city: city.to_string(),
+            region: None,
+            country: "FR".to_string(),
+        }
+    }
+
+    #[test]
+    fn at_most_one_address_is_preferred() {
+        let home = address("1 rue de la Paix", "75002", "Paris");
+        let work = address("5 avenue Foch", "69006", "Lyon");
+        let now = Utc::now();
+        assert!(validate_addresses([(&home, Some(now)), (&work, None)]).is_ok());
+        assert!(validate_addresses([(&home, None), (&work, None)]).is_ok());
+        let err = validate_addresses([(&home, Some(now)), (&work, Some(now))]).unwrap_err();
+        assert_eq!(
+            err.0,
+            "addresses: au plus une adresse peut avoir preferred_at"
+        );
+    }
+
+    #[test]
+    fn same_place_written_differently_is_a_duplicate() {
+        let home = address("1 rue de la Paix", "75002", "Paris");
+        let again = address("1  Rue de la PAIX ", "75002", " paris");
+        assert!(home.same_as(&again));
+        let err = validate_addresses([(&home, None), (&again, None)]).unwrap_err();
+        assert_eq!(err.0, "addresses[1]: adresse en double");
+    }
+
+    #[test]
+    fn postal_code_follows_the_country_format() {
+        let err = validate_addresses([(&address("1 rue de la Paix", "7500", "Paris"), None)])
+            .unwrap_err();
+        assert!(
+            err.0
+                .starts_with("addresses[0].postal_code: '7500' invalide pour FR"),
+            "{}",
+            err.0
+        );
+        let mut no_code = address("1 rue de la Paix", "", "Paris");
+        no_code.postal_code = None;
+        let err = validate_addresses([(&no_code, None)]).unwrap_err();
+        assert_eq!(err.0, "addresses[0].postal_code: obligatoire pour FR");
+    }
+}
@@ -0,0 +1,172 @@
+//! Attributs personnalisés des guests (ex. niveau de fidélité, régime alimentaire, société) : valeurs libres
+//! en JSON sur le guest, décrites et validées par un registre de définitions (nom, type, valeurs permises, obligatoire).
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_json::Value;
+
+use crate::domain::{StructuredValue, ValidationError};
+
+/// Longueur maximale du nom d'un attribut (ou d'un segment).
+const MAX_NAME_LEN: usize = 64;
+
+/// Attributs personnalisés d'un guest, par nom (ordre stable pour l'historique et les réponses).
+pub type GuestAttributes = BTreeMap<String, StructuredValue<Value>>;
+
+/// Valide un nom d'attribut ou de segment : minuscules, chiffres et `_`, commençant par une lettre.
+pub(super) fn validate_name(name: &str) -> Result<(), ValidationError> {
+    let valid = name.len() <= MAX_NAME_LEN
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid {
+        return Err(ValidationError(format!(
+            "name: '{}' invalide (minuscules, chiffres et _, commençant par une lettre, {} caractères au plus)",
+            name, MAX_NAME_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// Type de la valeur d'un attribut.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    /// Date au format `AAAA-MM-JJ`.
+    Date,
+    /// Chaîne parmi `enum_values`.
+    Enum,
+}
+
+impl AttributeType {
+    /// Nom stable du type (valeur persistée et exposée par l'API).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttributeType::String => "string",
+            AttributeType::Number => "number",
+            AttributeType::Integer => "integer",
+            AttributeType::Boolean => "boolean",
+            AttributeType::Date => "date",
+            AttributeType::Enum => "enum",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "string" => Some(AttributeType::String),
+            "number" => Some(AttributeType::Number),
+            "integer" => Some(AttributeType::Integer),
+            "boolean" => Some(AttributeType::Boolean),
+            "date" => Some(AttributeType::Date),
+            "enum" => Some(AttributeType::Enum),
+            _ => None,
+        }
+    }
+}
+
+/// Définition d'un attribut personnalisé dans le registre.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeDefinition {
+    /// Nom de l'attribut (clé dans `Guest.attributes`) : minuscules, chiffres et `_`, commençant par une lettre.
+    pub name: String,
+    pub attribute_type: AttributeType,
+    /// Valeurs permises d'un attribut `enum` (vide pour les autres types).
+    pub enum_values: Vec<String>,
+    /// Tout guest doit porter une valeur pour cet attribut.
+    pub required: bool,
+    pub description: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl AttributeDefinition {
+    /// Valide la définition : nom, valeurs permises présentes (et distinctes) si et seulement si le type est `enum`.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate_name(&self.name)?;
+        match self.attribute_type {
+            AttributeType::Enum if self.enum_values.is_empty() => Err(ValidationError(
+                "enum_values: obligatoire pour un attribut de type enum".into(),
+            )),
+            AttributeType::Enum => {
+                for (i, value) in self.enum_values.iter().enumerate() {
+                    if value.trim().is_empty() {
+                        return Err(ValidationError(format!(
+                            "enum_values[{}]: ne peut pas être vide",
+                            i
+                        )));
+                    }
+                    if self.enum_values[..i].contains(value) {
+                        return Err(ValidationError(format!(
+                            "enum_values[{}]: '{}' en double",
+                            i, value
+                        )));
+                    }
+                }
+                Ok(())
+            }
+            _ if !self.enum_values.is_empty() => Err(ValidationError(
+                "enum_values: réservé aux attributs de type enum".into(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// La valeur est-elle conforme au type de l'attribut ?
+    fn accepts(&self, value: &Value) -> bool {
+        match (self.attribute_type, value) {
+            (AttributeType::String, Value::String(_)) => true,
+            (AttributeType::Number, Value::Number(_)) => true,
+            (AttributeType::Integer, Value::Number(n)) => n.is_i64() || n.is_u64(),
+            (AttributeType::Boolean, Value::Bool(_)) => true,
+            (AttributeType::Date, Value::String(s)) => {
+                NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok()
+            }
+            (AttributeType::Enum, Value::String(s)) => self.enum_values.contains(s),
+            _ => false,
+        }
+    }
+
+    /// Type attendu, pour les messages d'erreur.
+    fn expected(&self) -> String {
+        match self.attribute_type {
+            AttributeType::Date => "une date AAAA-MM-JJ".into(),
+            AttributeType::Enum => format!("une valeur parmi {}", self.enum_values.join(", ")),
+            other => format!("une valeur de type {}", other.as_str()),
+        }
+    }
+}
+
+/// Valide les attributs d'un guest contre le registre : attribut défini, valeur conforme au type,
+/// attributs obligatoires présents (`attributes.<nom>` dans les messages).
+pub fn validate_attributes(
+    attributes: &GuestAttributes,
+    definitions: &[AttributeDefinition],
+) -> Result<(), ValidationError> {
+    for (name, value) in attributes {
+        let definition = definitions
+            .iter()
+            .find(|d| &d.name == name)
+            .ok_or_else(|| ValidationError(format!("attributes.{}: attribut non défini", name)))?;
+        if !definition.accepts(&value.value) {
+            return Err(ValidationError(format!(
+                "attributes.{}: {} attendue",
+                name,
+                definition.expected()
+            )));
+        }
+    }
+    if let Some(missing) = definitions
+        .iter()
+        .find(|d| d.required && !attributes.contains_key(&d.name))
+    {
+        return Err(ValidationError(format!(
+            "attributes.{}: attribut obligatoire",
+            missing.name
+        )));
+    }
+    Ok(())
+}
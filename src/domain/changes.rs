@@ -0,0 +1,353 @@
+//! Modifications d'un guest et fusion "last writer wins" basée sur `StructuredValue.updated_at`
+//! (synchronisation et fusion de doublons).
+
+use crate::domain::{Address, Consent, ContactKind, Guest, GuestAttributes, StructuredValue};
+
+/// Champs modifiés d'un guest (None = champ non fourni).
+#[derive(Debug, Clone, Default)]
+pub struct GuestChanges {
+    pub first_name: Option<StructuredValue<String>>,
+    pub last_name: Option<StructuredValue<String>>,
+    pub mail: Option<Vec<StructuredValue<String>>>,
+    pub phone: Option<Vec<StructuredValue<String>>>,
+    pub addresses: Option<Vec<StructuredValue<Address>>>,
+    pub consents: Option<Vec<StructuredValue<Consent>>>,
+    pub attributes: Option<GuestAttributes>,
+}
+
+/// Résultat d'une synchronisation : champs acceptés et champs rejetés car plus anciens que la valeur stockée.
+/// Les éléments de liste sont nommés `liste[valeur]` (ex. `mail[a@b.com]`, `consents[email/marketing]`),
+/// les attributs personnalisés `attributes.<nom>`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub accepted: Vec<String>,
+    pub rejected: Vec<String>,
+}
+
+impl SyncReport {
+    fn record(&mut self, field: String, accepted: bool) {
+        if accepted {
+            self.accepted.push(field);
+        } else {
+            self.rejected.push(field);
+        }
+    }
+}
+
+/// Remplace `stored` par `incoming` seulement si `incoming` est strictement plus récent.
+fn sync_value<T: Clone>(
+    stored: &mut StructuredValue<T>,
+    incoming: &StructuredValue<T>,
+    field: &str,
+    report: &mut SyncReport,
+) {
+    let newer = incoming.updated_at > stored.updated_at;
+    if newer {
+        *stored = incoming.clone();
+    }
+    report.record(field.to_string(), newer);
+}
+
+/// Fusionne une liste élément par élément : un élément entrant remplace l'élément stocké de même valeur
+/// (téléphones et emails comparés sur leur forme canonique)
+/// s'il est plus récent, et est ajouté si la valeur est absente. Les éléments stockés non cités sont conservés.
+fn sync_list<T: Clone>(
+    stored: &mut Vec<StructuredValue<T>>,
+    incoming: &[StructuredValue<T>],
+    field: &str,
+    same_value: impl Fn(&StructuredValue<T>, &StructuredValue<T>) -> bool,
+    label: impl Fn(&T) -> String,
+    report: &mut SyncReport,
+) {
+    for item in incoming {
+        let name = format!("{}[{}]", field, label(&item.value));
+        match stored.iter_mut().find(|s| same_value(s, item)) {
+            Some(existing) => sync_value(existing, item, &name, report),
+            None => {
+                stored.push(item.clone());
+                report.record(name, true);
+            }
+        }
+    }
+}
+
+/// Garde au plus un élément préféré : l'élément préféré le plus récemment désigné l'emporte.
+pub(super) fn keep_latest_preferred<T>(list: &mut [StructuredValue<T>]) {
+    let latest = list
+        .iter()
+        .enumerate()
+        .filter_map(|(i, v)| v.preferred_at.map(|at| (at, i)))
+        .max()
+        .map(|(_, i)| i);
+    for (i, item) in list.iter_mut().enumerate() {
+        if Some(i) != latest {
+            item.preferred_at = None;
+        }
+    }
+}
+
+/// Fusionne les attributs nom par nom : même règle que les listes (les attributs non cités sont conservés).
+fn sync_attributes(
+    stored: &mut GuestAttributes,
+    incoming: &GuestAttributes,
+    report: &mut SyncReport,
+) {
+    for (name, value) in incoming {
+        let field = format!("attributes.{}", name);
+        match stored.get_mut(name) {
+            Some(existing) => sync_value(existing, value, &field, report),
+            None => {
+                stored.insert(name.clone(), value.clone());
+                report.record(field, true);
+            }
+        }
+    }
+}
+
+fn sync_contacts(
+    stored: &mut Vec<StructuredValue<String>>,
+    incoming: &[StructuredValue<String>],
+    kind: ContactKind,
+    report: &mut SyncReport,
+) {
+    sync_list(
+        stored,
+        incoming,
+        kind.as_str(),
+        |a, b| kind.same_contact(a, b),
+        |v| v.clone(),
+        report,
+    );
+    keep_latest_preferred(stored);
+}
+
+/// Applique les modifications en "last writer wins" : chaque champ (et chaque élément de liste, apparié par valeur)
+/// n'est remplacé que si son `updated_at` est plus récent que la valeur stockée.
+pub fn sync_last_writer_wins(mut guest: Guest, changes: &GuestChanges) -> (Guest, SyncReport) {
+    let mut report = SyncReport::default();
+    if let Some(first_name) = &changes.first_name {
+        sync_value(&mut guest.first_name, first_name, "first_name", &mut report);
+    }
+    if let Some(last_name) = &changes.last_name {
+        sync_value(&mut guest.last_name, last_name, "last_name", &mut report);
+    }
+    if let Some(mail) = &changes.mail {
+        sync_contacts(&mut guest.mail, mail, ContactKind::Mail, &mut report);
+    }
+    if let Some(phone) = &changes.phone {
+        sync_contacts(&mut guest.phone, phone, ContactKind::Phone, &mut report);
+    }
+    if let Some(addresses) = &changes.addresses {
+        sync_list(
+            &mut guest.addresses,
+            addresses,
+            "addresses",
+            |a, b| a.value.same_as(&b.value),
+            Address::label,
+            &mut report,
+        );
+        keep_latest_preferred(&mut guest.addresses);
+    }
+    if let Some(consents) = &changes.consents {
+        sync_list(
+            &mut guest.consents,
+            consents,
+            "consents",
+            |a, b| a.value.same_scope(&b.value),
+            Consent::scope,
+            &mut report,
+        );
+    }
+    if let Some(attributes) = &changes.attributes {
+        sync_attributes(&mut guest.attributes, attributes, &mut report);
+    }
+    (guest, report)
+}
+
+/// Fusionne `source` dans `target` (doublons d'une même personne) : listes unies et dédoublonnées par valeur
+/// (l'élément le plus récent garde son `updated_at` et son `from`), attributs unis par nom, un seul préféré (le plus récemment désigné),
+/// noms pris selon la mise à jour la plus récente. L'id et la version de `target` sont conservés.
+pub fn merge_guests(target: Guest, source: &Guest) -> Guest {
+    let changes = GuestChanges {
+        first_name: Some(source.first_name.clone()),
+        last_name: Some(source.last_name.clone()),
+        mail: Some(source.mail.clone()),
+        phone: Some(source.phone.clone()),
+        addresses: Some(source.addresses.clone()),
+        consents: Some(source.consents.clone()),
+        attributes: Some(source.attributes.clone()),
+    };
+    sync_last_writer_wins(target, &changes).0
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, Utc};
+
+    use super::*;
+
+    fn value(v: &str, from: &str, at: DateTime<Utc>) -> StructuredValue<String> {
+        StructuredValue {
+            from: Some(from.to_string()),
+            ..StructuredValue::with_updated_at(v.to_string(), at)
+        }
+    }
+
+    fn guest(first_name: StructuredValue<String>) -> Guest {
+        Guest {
+            first_name,
+            ..Guest::new(uuid::Uuid::new_v4(), String::new(), "Lopez".into())
+        }
+    }
+
+    #[test]
+    fn merge_keeps_target_identity_and_the_most_recent_values() {
+        let now = Utc::now();
+        let mut target = guest(value("Ana", "pms", now - Duration::days(1)));
+        target.version = 7;
+        target.mail = vec![value("ana@hotel.com", "pms", now - Duration::days(1))];
+        let mut source = guest(value("Anna", "web", now));
+        source.mail = vec![
+            value("ANA@hotel.com", "web", now),
+            value("ana@web.com", "web", now - Duration::days(2)),
+        ];
+
+        let merged = merge_guests(target.clone(), &source);
+        assert_eq!((merged.id, merged.version), (target.id, 7));
+        assert_eq!(merged.first_name, source.first_name);
+        // Même adresse (casse près) : un seul élément, celui du plus récent ; les autres sont unis.
+        let mails: Vec<(&str, Option<&str>)> = merged
+            .mail
+            .iter()
+            .map(|m| (m.value.as_str(), m.from.as_deref()))
+            .collect();
+        assert_eq!(
+            mails,
+            [("ANA@hotel.com", Some("web")), ("ana@web.com", Some("web"))]
+        );
+    }
+
+    #[test]
+    fn merge_keeps_target_values_newer_than_the_source() {
+        let now = Utc::now();
+        let target = guest(value("Ana", "reception", now));
+        let mut source = guest(value("Anna", "web", now - Duration::hours(1)));
+        source.attributes.insert(
+            "tier".into(),
+            StructuredValue::with_updated_at(serde_json::json!("gold"), now),
+        );
+
+        let merged = merge_guests(target.clone(), &source);
+        assert_eq!(merged.first_name, target.first_name);
+        assert_eq!(
+            merged.attributes.get("tier").unwrap().value,
+            serde_json::json!("gold")
+        );
+    }
+
+    #[test]
+    fn merge_keeps_a_single_preferred_contact() {
+        let now = Utc::now();
+        let mut target = guest(value("Ana", "pms", now));
+        target.phone = vec![StructuredValue {
+            preferred_at: Some(now - Duration::days(3)),
+            ..value("+33612345678", "pms", now - Duration::days(3))
+        }];
+        let mut source = guest(value("Ana", "pms", now));
+        source.phone = vec![StructuredValue {
+            preferred_at: Some(now),
+            ..value("+33699999999", "web", now)
+        }];
+
+        let merged = merge_guests(target, &source);
+        let preferred: Vec<&str> = merged
+            .phone
+            .iter()
+            .filter(|p| p.preferred_at.is_some())
+            .map(|p| p.value.as_str())
+            .collect();
+        assert_eq!(merged.phone.len(), 2);
+        assert_eq!(preferred, ["+33699999999"]);
+    }
+
+    #[test]
+    fn sync_accepts_only_strictly_newer_values() {
+        let now = Utc::now();
+        let stored = guest(value("Ana", "pms", now));
+        let sync = |first_name| {
+            let changes = GuestChanges {
+                first_name: Some(first_name),
+                ..Default::default()
+            };
+            sync_last_writer_wins(stored.clone(), &changes)
+        };
+
+        let (kept, report) = sync(value("Anna", "web", now));
+        assert_eq!(kept.first_name.value, "Ana");
+        assert_eq!(report.rejected, ["first_name"]);
+        let (kept, report) = sync(value("Anna", "web", now - Duration::seconds(1)));
+        assert_eq!(kept.first_name.value, "Ana");
+        assert_eq!(report.rejected, ["first_name"]);
+        let (synced, report) = sync(value("Anna", "web", now + Duration::seconds(1)));
+        assert_eq!(
+            (
+                synced.first_name.value.as_str(),
+                synced.first_name.from.as_deref()
+            ),
+            ("Anna", Some("web"))
+        );
+        assert_eq!(
+            (report.accepted, report.rejected),
+            (vec!["first_name".to_string()], Vec::<String>::new())
+        );
+    }
+
+    #[test]
+    fn sync_matches_list_elements_by_value_and_never_removes_them() {
+        let now = Utc::now();
+        let mut stored = guest(value("Ana", "pms", now));
+        stored.mail = vec![
+            value("ana@hotel.com", "pms", now),
+            value("ana@old.com", "pms", now),
+        ];
+        let changes = GuestChanges {
+            mail: Some(vec![
+                value("Ana@Hotel.com", "web", now - Duration::hours(1)),
+                value("ana@web.com", "web", now),
+            ]),
+            ..Default::default()
+        };
+
+        let (synced, report) = sync_last_writer_wins(stored, &changes);
+        let mails: Vec<&str> = synced.mail.iter().map(|m| m.value.as_str()).collect();
+        assert_eq!(mails, ["ana@hotel.com", "ana@old.com", "ana@web.com"]);
+        assert_eq!(report.rejected, ["mail[Ana@Hotel.com]"]);
+        assert_eq!(report.accepted, ["mail[ana@web.com]"]);
+    }
+
+    #[test]
+    fn sync_keeps_attributes_not_mentioned() {
+        let now = Utc::now();
+        let mut stored = guest(value("Ana", "pms", now));
+        stored.attributes.insert(
+            "tier".into(),
+            StructuredValue::with_updated_at(serde_json::json!("gold"), now),
+        );
+        let mut attributes = GuestAttributes::new();
+        attributes.insert(
+            "room".into(),
+            StructuredValue::with_updated_at(serde_json::json!(12), now),
+        );
+        let changes = GuestChanges {
+            attributes: Some(attributes),
+            ..Default::default()
+        };
+
+        let (synced, report) = sync_last_writer_wins(stored, &changes);
+        assert_eq!(
+            synced.attributes.keys().collect::<Vec<_>>(),
+            ["room", "tier"]
+        );
+        assert_eq!(report.accepted, ["attributes.room"]);
+    }
+}
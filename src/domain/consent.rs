@@ -0,0 +1,288 @@
+//! Consentements d'un guest : un statut par canal de contact et par finalité, avec base légale (RGPD art. 6).
+//! Provenance et date d'enregistrement sont portées par `StructuredValue` (`from`, `updated_at`).
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{Guest, StructuredValue, ValidationError};
+
+/// Canal de contact soumis à consentement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentChannel {
+    Email,
+    Sms,
+    Phone,
+    Postal,
+}
+
+impl ConsentChannel {
+    /// Tous les canaux (ex. opt-out global à la suppression d'un guest).
+    pub const ALL: [ConsentChannel; 4] = [
+        ConsentChannel::Email,
+        ConsentChannel::Sms,
+        ConsentChannel::Phone,
+        ConsentChannel::Postal,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConsentChannel::Email => "email",
+            ConsentChannel::Sms => "sms",
+            ConsentChannel::Phone => "phone",
+            ConsentChannel::Postal => "postal",
+        }
+    }
+}
+
+/// Finalité de la communication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentPurpose {
+    Marketing,
+    Transactional,
+}
+
+impl ConsentPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConsentPurpose::Marketing => "marketing",
+            ConsentPurpose::Transactional => "transactional",
+        }
+    }
+}
+
+/// Statut du consentement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentStatus {
+    OptedIn,
+    OptedOut,
+}
+
+impl ConsentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConsentStatus::OptedIn => "opted_in",
+            ConsentStatus::OptedOut => "opted_out",
+        }
+    }
+}
+
+/// Base légale du traitement (RGPD art. 6.1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LegalBasis {
+    Consent,
+    Contract,
+    LegalObligation,
+    LegitimateInterest,
+}
+
+impl LegalBasis {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LegalBasis::Consent => "consent",
+            LegalBasis::Contract => "contract",
+            LegalBasis::LegalObligation => "legal_obligation",
+            LegalBasis::LegitimateInterest => "legitimate_interest",
+        }
+    }
+}
+
+/// Consentement pour un canal et une finalité.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Consent {
+    pub channel: ConsentChannel,
+    pub purpose: ConsentPurpose,
+    pub status: ConsentStatus,
+    pub legal_basis: LegalBasis,
+    /// Date à laquelle le guest a exprimé son choix (peut précéder l'enregistrement `updated_at`).
+    #[serde(default)]
+    pub collected_at: Option<DateTime<Utc>>,
+}
+
+impl Consent {
+    /// Deux consentements portent sur le même canal et la même finalité.
+    pub fn same_scope(&self, other: &Consent) -> bool {
+        self.channel == other.channel && self.purpose == other.purpose
+    }
+
+    /// Libellé `canal/finalité` (ex. `email/marketing`).
+    pub fn scope(&self) -> String {
+        format!("{}/{}", self.channel.as_str(), self.purpose.as_str())
+    }
+}
+
+/// `incoming` peut-il remplacer le consentement stocké de même portée ? Un opt-out n'est levé que par
+/// un `opted_in` explicite plus récent (`updated_at`).
+pub fn may_replace_consent(
+    stored: &StructuredValue<Consent>,
+    incoming: &StructuredValue<Consent>,
+) -> bool {
+    stored.value.status != ConsentStatus::OptedOut
+        || incoming.value.status == ConsentStatus::OptedOut
+        || incoming.updated_at > stored.updated_at
+}
+
+/// Rétablit dans `consents` les opt-outs de `stored` retirés de la liste ou remplacés sans `opted_in` plus récent
+/// (un opt-out n'est jamais supprimé) ; retourne les portées rétablies (`email/marketing`).
+pub fn keep_opt_outs(
+    stored: &[StructuredValue<Consent>],
+    consents: &mut Vec<StructuredValue<Consent>>,
+) -> Vec<String> {
+    let mut kept = Vec::new();
+    for opt_out in stored
+        .iter()
+        .filter(|c| c.value.status == ConsentStatus::OptedOut)
+    {
+        match consents
+            .iter_mut()
+            .find(|c| c.value.same_scope(&opt_out.value))
+        {
+            Some(current) if may_replace_consent(opt_out, current) => continue,
+            Some(current) => *current = opt_out.clone(),
+            None => consents.push(opt_out.clone()),
+        }
+        kept.push(opt_out.value.scope());
+    }
+    kept
+}
+
+/// Valide une liste de consentements : au plus un par canal et finalité (`consents[i]` dans les messages).
+pub fn validate_consents<'a>(
+    consents: impl IntoIterator<Item = &'a Consent>,
+) -> Result<(), ValidationError> {
+    let consents: Vec<&Consent> = consents.into_iter().collect();
+    for (i, consent) in consents.iter().enumerate() {
+        if let Some(first) = consents[..i].iter().position(|c| c.same_scope(consent)) {
+            return Err(ValidationError(format!(
+                "consents[{}]: {} déjà présent en consents[{}]",
+                i,
+                consent.scope(),
+                first
+            )));
+        }
+    }
+    Ok(())
+}
+
+impl Guest {
+    /// Consentement du guest pour ce canal et cette finalité.
+    pub fn consent(
+        &self,
+        channel: ConsentChannel,
+        purpose: ConsentPurpose,
+    ) -> Option<&StructuredValue<Consent>> {
+        self.consents
+            .iter()
+            .find(|c| c.value.channel == channel && c.value.purpose == purpose)
+    }
+
+    /// Canaux passés en opt-out (pour au moins une finalité) entre `before` et `self`, triés.
+    pub fn new_opt_outs(&self, before: Option<&Guest>) -> Vec<ConsentChannel> {
+        let was_opted_out = |consent: &Consent| {
+            before
+                .and_then(|g| g.consent(consent.channel, consent.purpose))
+                .is_some_and(|c| c.value.status == ConsentStatus::OptedOut)
+        };
+        let mut channels: Vec<ConsentChannel> = self
+            .consents
+            .iter()
+            .map(|c| &c.value)
+            .filter(|c| c.status == ConsentStatus::OptedOut && !was_opted_out(c))
+            .map(|c| c.channel)
+            .collect();
+        channels.sort();
+        channels.dedup();
+        channels
+    }
+}
+
+/// Événement opt-out : canaux sur lesquels le guest ne doit plus être contacté
+/// (publié sur NATS et conservé pour les demandes d'accès).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OptOutEvent {
+    pub guest_id: uuid::Uuid,
+    pub channels: Vec<ConsentChannel>,
+    /// `deleted` (guest supprimé, tous les canaux) ou `consent` (consentement passé en opt-out).
+    pub reason: String,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Événement opt-out conservé, avec la trace de la requête et le résultat de la publication.
+#[derive(Debug, Clone)]
+pub struct OptOutEventRecord {
+    pub seq: i64,
+    pub event: OptOutEvent,
+    pub trace_id: Option<String>,
+    /// `false` si la publication NATS a échoué.
+    pub published: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn consent(status: ConsentStatus, at: DateTime<Utc>) -> StructuredValue<Consent> {
+        let consent = Consent {
+            channel: ConsentChannel::Email,
+            purpose: ConsentPurpose::Marketing,
+            status,
+            legal_basis: LegalBasis::Consent,
+            collected_at: None,
+        };
+        StructuredValue::with_updated_at(consent, at)
+    }
+
+    #[test]
+    fn opt_out_is_lifted_only_by_a_newer_opt_in() {
+        let now = Utc::now();
+        let opt_out = consent(ConsentStatus::OptedOut, now);
+        assert!(may_replace_consent(
+            &opt_out,
+            &consent(ConsentStatus::OptedIn, now + Duration::seconds(1))
+        ));
+        assert!(!may_replace_consent(
+            &opt_out,
+            &consent(ConsentStatus::OptedIn, now)
+        ));
+        assert!(!may_replace_consent(
+            &opt_out,
+            &consent(ConsentStatus::OptedIn, now - Duration::days(1))
+        ));
+        assert!(may_replace_consent(
+            &opt_out,
+            &consent(ConsentStatus::OptedOut, now - Duration::days(1))
+        ));
+        let opt_in = consent(ConsentStatus::OptedIn, now);
+        assert!(may_replace_consent(
+            &opt_in,
+            &consent(ConsentStatus::OptedIn, now - Duration::days(1))
+        ));
+    }
+
+    #[test]
+    fn omitted_or_stale_opt_outs_are_restored() {
+        let now = Utc::now();
+        let stored = vec![consent(ConsentStatus::OptedOut, now)];
+
+        let mut omitted = Vec::new();
+        assert_eq!(
+            keep_opt_outs(&stored, &mut omitted),
+            vec!["email/marketing".to_string()]
+        );
+        assert_eq!(omitted, stored);
+
+        let mut stale = vec![consent(ConsentStatus::OptedIn, now - Duration::hours(1))];
+        assert_eq!(keep_opt_outs(&stored, &mut stale).len(), 1);
+        assert_eq!(stale, stored);
+
+        let newer = vec![consent(ConsentStatus::OptedIn, now + Duration::hours(1))];
+        let mut lifted = newer.clone();
+        assert!(keep_opt_outs(&stored, &mut lifted).is_empty());
+        assert_eq!(lifted, newer);
+    }
+}
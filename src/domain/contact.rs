@@ -0,0 +1,117 @@
+//! Coordonnées de contact (mail, phone) : normalisation partagée pour l'indexation, la recherche
+//! et l'appariement des valeurs (mises à jour, synchronisation, fusion, historique).
+
+use crate::domain::email::canonicalize_mail_list;
+use crate::domain::phone::canonicalize_phone_list;
+use crate::domain::{
+    parse_email, parse_phone, Guest, GuestChanges, PhoneRegion, StructuredValue, ERASED_VALUE,
+};
+
+/// Type de coordonnée indexée pour la recherche de guests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContactKind {
+    Mail,
+    Phone,
+}
+
+impl ContactKind {
+    /// Nom stable du type (valeur persistée en base).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContactKind::Mail => "mail",
+            ContactKind::Phone => "phone",
+        }
+    }
+
+    /// Normalise une valeur de ce type.
+    pub fn normalize(&self, value: &str) -> String {
+        match self {
+            ContactKind::Mail => normalize_email(value),
+            ContactKind::Phone => normalize_phone(value),
+        }
+    }
+
+    /// Clé d'appariement d'une coordonnée : forme canonique (`normalized`, ex. E.164) si connue, normalisée.
+    pub fn key(&self, value: &StructuredValue<String>) -> String {
+        self.normalize(canonical(value))
+    }
+
+    /// Deux coordonnées canonicalisées désignent la même adresse ou le même numéro
+    /// (ex. `06 12 34 56 78` saisi en France et `+33612345678`).
+    pub fn same_contact(&self, a: &StructuredValue<String>, b: &StructuredValue<String>) -> bool {
+        self.key(a) == self.key(b)
+    }
+
+    /// Clé d'appariement d'une saisie non canonicalisée (ex. valeur visée par un PATCH), analysée comme à l'écriture.
+    pub fn input_key(&self, value: &str, default_region: Option<PhoneRegion>) -> String {
+        let canonical = match self {
+            ContactKind::Mail => parse_email(value).ok(),
+            ContactKind::Phone => parse_phone(value, default_region).ok().map(|p| p.e164),
+        };
+        self.normalize(canonical.as_deref().unwrap_or(value))
+    }
+}
+
+/// Email normalisé pour la recherche : sans espaces autour, en minuscules.
+pub fn normalize_email(value: &str) -> String {
+    value.trim().to_lowercase()
+}
+
+/// Téléphone normalisé pour la recherche : uniquement les chiffres.
+pub fn normalize_phone(value: &str) -> String {
+    value.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+/// Valeur indexée d'une coordonnée : forme canonique si connue, sinon la saisie.
+fn canonical(value: &StructuredValue<String>) -> &str {
+    value.normalized.as_deref().unwrap_or(&value.value)
+}
+
+impl Guest {
+    /// Renseigne la forme canonique des emails et téléphones avant écriture (voir `canonicalize_mails`
+    /// et `canonicalize_phones`) et nettoie les adresses postales.
+    pub fn canonicalize_contacts(&mut self, default_region: Option<PhoneRegion>) {
+        self.canonicalize_mails();
+        self.canonicalize_phones(default_region);
+        for address in &mut self.addresses {
+            address.value.canonicalize();
+        }
+    }
+
+    /// Coordonnées normalisées du guest (mails puis téléphones), sans doublon, valeur vide ni valeur effacée.
+    /// La forme canonique (`normalized`, ex. E.164) est utilisée quand elle est connue.
+    pub fn normalized_contacts(&self) -> Vec<(ContactKind, String)> {
+        let not_erased = |v: &&StructuredValue<String>| v.value != ERASED_VALUE;
+        let mails = self
+            .mail
+            .iter()
+            .filter(not_erased)
+            .map(|m| (ContactKind::Mail, m));
+        let phones = self
+            .phone
+            .iter()
+            .filter(not_erased)
+            .map(|p| (ContactKind::Phone, p));
+        let mut contacts: Vec<(ContactKind, String)> = Vec::new();
+        for (kind, value) in mails.chain(phones) {
+            let normalized = kind.key(value);
+            if !normalized.is_empty() && !contacts.contains(&(kind, normalized.clone())) {
+                contacts.push((kind, normalized));
+            }
+        }
+        contacts
+    }
+}
+
+impl GuestChanges {
+    /// Renseigne la forme canonique des emails et téléphones entrants (voir `Guest::canonicalize_contacts`)
+    /// pour qu'ils soient appariés aux coordonnées stockées sur leur forme canonique.
+    pub fn canonicalize_contacts(&mut self, default_region: Option<PhoneRegion>) {
+        if let Some(mail) = &mut self.mail {
+            canonicalize_mail_list(mail);
+        }
+        if let Some(phone) = &mut self.phone {
+            canonicalize_phone_list(phone, default_region);
+        }
+    }
+}
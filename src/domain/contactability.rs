@@ -0,0 +1,283 @@
+//! Joignabilité d'un guest sur un canal : adresse à utiliser (préférée, à défaut la première utilisable)
+//! ou raison pour laquelle le contact n'est pas permis.
+
+use crate::domain::{
+    phone_line_type, ConsentChannel, ConsentPurpose, ConsentStatus, Guest, PhoneLineType,
+    StructuredValue,
+};
+
+/// Raison pour laquelle un guest ne peut pas être contacté.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContactabilityReason {
+    /// Guest supprimé (en attente de purge).
+    GuestDeleted,
+    /// Données personnelles effacées (RGPD).
+    GuestErased,
+    /// Consentement en opt-out pour ce canal et cette finalité.
+    OptedOut,
+    /// Aucune coordonnée pour ce canal.
+    NoAddress,
+    /// SMS : aucun téléphone mobile (uniquement des lignes fixes).
+    NoMobilePhone,
+}
+
+impl ContactabilityReason {
+    /// Code stable de la raison (valeur exposée par l'API).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContactabilityReason::GuestDeleted => "guest_deleted",
+            ContactabilityReason::GuestErased => "guest_erased",
+            ContactabilityReason::OptedOut => "opted_out",
+            ContactabilityReason::NoAddress => "no_address",
+            ContactabilityReason::NoMobilePhone => "no_mobile_phone",
+        }
+    }
+}
+
+/// Résultat : l'adresse à utiliser, ou la raison du refus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Contactability {
+    Contactable { address: String },
+    NotContactable { reason: ContactabilityReason },
+}
+
+/// Adresse d'une coordonnée : forme canonique si connue (E.164, domaine punycode), sinon la saisie.
+fn address(value: &StructuredValue<String>) -> String {
+    value
+        .normalized
+        .clone()
+        .unwrap_or_else(|| value.value.trim().to_string())
+}
+
+/// Préférée si elle convient, sinon la première qui convient.
+fn pick<T>(
+    list: &[StructuredValue<T>],
+    usable: impl Fn(&StructuredValue<T>) -> bool,
+) -> Option<&StructuredValue<T>> {
+    list.iter()
+        .find(|v| v.preferred_at.is_some() && usable(v))
+        .or_else(|| list.iter().find(|v| usable(v)))
+}
+
+/// Joignabilité de `guest` sur `channel` pour `purpose` (None = guest supprimé).
+/// Sans consentement enregistré pour le canal et la finalité, le contact est permis (seul un opt-out l'interdit).
+/// En SMS, un mobile est préféré à un numéro de type inconnu ; les lignes fixes sont exclues.
+/// En postal, l'adresse est renvoyée sur une ligne (`1 rue de la Paix, 75002 Paris, FR`).
+pub fn contactability(
+    guest: Option<&Guest>,
+    channel: ConsentChannel,
+    purpose: ConsentPurpose,
+) -> Contactability {
+    let not_contactable = |reason| Contactability::NotContactable { reason };
+    let Some(guest) = guest else {
+        return not_contactable(ContactabilityReason::GuestDeleted);
+    };
+    if guest.is_erased() {
+        return not_contactable(ContactabilityReason::GuestErased);
+    }
+    if guest
+        .consent(channel, purpose)
+        .is_some_and(|c| c.value.status == ConsentStatus::OptedOut)
+    {
+        return not_contactable(ContactabilityReason::OptedOut);
+    }
+    let chosen = match channel {
+        ConsentChannel::Email => pick(&guest.mail, |_| true).map(address),
+        ConsentChannel::Phone => pick(&guest.phone, |_| true).map(address),
+        ConsentChannel::Sms => {
+            if guest.phone.is_empty() {
+                None
+            } else {
+                let mobile = pick(&guest.phone, |p| {
+                    phone_line_type(p) == Some(PhoneLineType::Mobile)
+                })
+                .or_else(|| {
+                    pick(&guest.phone, |p| {
+                        phone_line_type(p) != Some(PhoneLineType::Landline)
+                    })
+                });
+                match mobile {
+                    Some(phone) => Some(address(phone)),
+                    None => return not_contactable(ContactabilityReason::NoMobilePhone),
+                }
+            }
+        }
+        ConsentChannel::Postal => pick(&guest.addresses, |_| true).map(|a| a.value.label()),
+    };
+    match chosen {
+        Some(address) => Contactability::Contactable { address },
+        None => not_contactable(ContactabilityReason::NoAddress),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::domain::{Address, Consent, LegalBasis};
+
+    fn guest() -> Guest {
+        Guest::new(uuid::Uuid::new_v4(), "Ana".into(), "Lopez".into())
+    }
+
+    /// Téléphone canonicalisé (forme E.164 connue) : son type de ligne en est déduit.
+    fn phone(number: &str) -> StructuredValue<String> {
+        StructuredValue {
+            normalized: Some(number.to_string()),
+            ..StructuredValue::new(number.to_string())
+        }
+    }
+
+    fn consent(
+        channel: ConsentChannel,
+        purpose: ConsentPurpose,
+        status: ConsentStatus,
+    ) -> StructuredValue<Consent> {
+        StructuredValue::new(Consent {
+            channel,
+            purpose,
+            status,
+            legal_basis: LegalBasis::Consent,
+            collected_at: None,
+        })
+    }
+
+    fn reachable_at(result: Contactability) -> String {
+        match result {
+            Contactability::Contactable { address } => address,
+            other => panic!("{other:?}"),
+        }
+    }
+
+    fn refused(result: Contactability) -> ContactabilityReason {
+        match result {
+            Contactability::NotContactable { reason } => reason,
+            other => panic!("{other:?}"),
+        }
+    }
+
+    #[test]
+    fn contact_is_allowed_without_a_recorded_consent() {
+        let mut ana = guest();
+        ana.mail
+            .push(StructuredValue::new("ana@example.com".into()));
+        for purpose in [ConsentPurpose::Marketing, ConsentPurpose::Transactional] {
+            let result = contactability(Some(&ana), ConsentChannel::Email, purpose);
+            assert_eq!(reachable_at(result), "ana@example.com");
+        }
+    }
+
+    #[test]
+    fn opt_out_applies_to_its_channel_and_purpose_only() {
+        let mut ana = guest();
+        ana.mail
+            .push(StructuredValue::new("ana@example.com".into()));
+        ana.phone.push(phone("+33612345678"));
+        ana.consents.push(consent(
+            ConsentChannel::Email,
+            ConsentPurpose::Marketing,
+            ConsentStatus::OptedOut,
+        ));
+        ana.consents.push(consent(
+            ConsentChannel::Sms,
+            ConsentPurpose::Marketing,
+            ConsentStatus::OptedIn,
+        ));
+
+        let email_marketing =
+            contactability(Some(&ana), ConsentChannel::Email, ConsentPurpose::Marketing);
+        assert_eq!(refused(email_marketing), ContactabilityReason::OptedOut);
+        let email_transactional = contactability(
+            Some(&ana),
+            ConsentChannel::Email,
+            ConsentPurpose::Transactional,
+        );
+        assert_eq!(reachable_at(email_transactional), "ana@example.com");
+        let sms_marketing =
+            contactability(Some(&ana), ConsentChannel::Sms, ConsentPurpose::Marketing);
+        assert_eq!(reachable_at(sms_marketing), "+33612345678");
+    }
+
+    #[test]
+    fn preferred_address_is_used_first() {
+        let mut ana = guest();
+        ana.mail
+            .push(StructuredValue::new("ana@example.com".into()));
+        ana.mail.push(StructuredValue {
+            preferred_at: Some(Utc::now()),
+            ..StructuredValue::new("ana@work.com".into())
+        });
+        ana.addresses.push(StructuredValue::new(Address {
+            lines: vec!["1 rue de la Paix".into()],
+            postal_code: Some("75002".into()),
+            city: "Paris".into(),
+            region: None,
+            country: "FR".into(),
+        }));
+
+        let email = contactability(Some(&ana), ConsentChannel::Email, ConsentPurpose::Marketing);
+        assert_eq!(reachable_at(email), "ana@work.com");
+        let postal = contactability(
+            Some(&ana),
+            ConsentChannel::Postal,
+            ConsentPurpose::Marketing,
+        );
+        assert_eq!(reachable_at(postal), "1 rue de la Paix, 75002 Paris, FR");
+        let phone = contactability(
+            Some(&ana),
+            ConsentChannel::Phone,
+            ConsentPurpose::Transactional,
+        );
+        assert_eq!(refused(phone), ContactabilityReason::NoAddress);
+    }
+
+    #[test]
+    fn sms_prefers_a_mobile_and_excludes_landlines() {
+        let mut ana = guest();
+        ana.phone.push(phone("+33140000000"));
+        let sms = contactability(
+            Some(&ana),
+            ConsentChannel::Sms,
+            ConsentPurpose::Transactional,
+        );
+        assert_eq!(refused(sms), ContactabilityReason::NoMobilePhone);
+        let call = contactability(
+            Some(&ana),
+            ConsentChannel::Phone,
+            ConsentPurpose::Transactional,
+        );
+        assert_eq!(reachable_at(call), "+33140000000");
+
+        ana.phone.push(phone("+12025550123"));
+        let sms = contactability(
+            Some(&ana),
+            ConsentChannel::Sms,
+            ConsentPurpose::Transactional,
+        );
+        assert_eq!(reachable_at(sms), "+12025550123");
+        ana.phone.push(phone("+33612345678"));
+        let sms = contactability(
+            Some(&ana),
+            ConsentChannel::Sms,
+            ConsentPurpose::Transactional,
+        );
+        assert_eq!(reachable_at(sms), "+33612345678");
+    }
+
+    #[test]
+    fn deleted_or_erased_guests_are_not_contactable() {
+        let deleted = contactability(None, ConsentChannel::Email, ConsentPurpose::Transactional);
+        assert_eq!(refused(deleted), ContactabilityReason::GuestDeleted);
+        let mut ana = guest();
+        ana.mail
+            .push(StructuredValue::new("ana@example.com".into()));
+        ana.erased_at = Some(Utc::now());
+        let erased = contactability(
+            Some(&ana),
+            ConsentChannel::Email,
+            ConsentPurpose::Transactional,
+        );
+        assert_eq!(refused(erased), ContactabilityReason::GuestErased);
+    }
+}
@@ -0,0 +1,165 @@
+//! Détection de doublons : score d'un guest candidat selon les coordonnées partagées (après normalisation)
+//! et la similarité des noms.
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{ContactKind, Guest};
+
+/// Poids d'un mail identique après normalisation.
+const MAIL_WEIGHT: f64 = 0.5;
+/// Poids d'un téléphone identique après normalisation.
+const PHONE_WEIGHT: f64 = 0.4;
+/// Poids du nom de famille (multiplié par la similarité).
+const LAST_NAME_WEIGHT: f64 = 0.25;
+/// Poids du prénom (multiplié par la similarité).
+const FIRST_NAME_WEIGHT: f64 = 0.15;
+/// Similarité minimale pour qu'un nom compte comme raison.
+pub const NAME_SIMILARITY_THRESHOLD: f64 = 0.8;
+/// Score minimal d'un candidat (ex. nom + prénom proches, ou une coordonnée partagée).
+pub const MIN_DUPLICATE_SCORE: f64 = 0.35;
+
+/// Raison ayant contribué au score : champ (`mail`, `phone`, `first_name`, `last_name`),
+/// valeur du candidat (normalisée pour mail/phone) et similarité (1.0 pour une égalité).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateReason {
+    pub field: String,
+    pub value: String,
+    pub similarity: f64,
+}
+
+/// Guest candidat au doublon, avec son score (0..=1) et les raisons.
+#[derive(Debug, Clone)]
+pub struct DuplicateCandidate {
+    pub guest: Guest,
+    pub score: f64,
+    pub reasons: Vec<DuplicateReason>,
+}
+
+/// Paire de doublons potentiels trouvée par le scan global (`guest_id` < `candidate_id`).
+#[derive(Debug, Clone)]
+pub struct DuplicatePair {
+    pub guest_id: uuid::Uuid,
+    pub candidate_id: uuid::Uuid,
+    pub score: f64,
+    pub reasons: Vec<DuplicateReason>,
+}
+
+/// Pagination des paires du dernier scan (score décroissant).
+#[derive(Debug, Clone, Default)]
+pub struct DuplicatePairQuery {
+    /// Reprend la liste après ce rang (curseur).
+    pub after: Option<i64>,
+    pub limit: u32,
+}
+
+/// Une page de paires ; `next` vaut le rang à passer dans `after` pour la page suivante.
+#[derive(Debug, Clone)]
+pub struct DuplicatePairPage {
+    pub pairs: Vec<DuplicatePair>,
+    pub next: Option<i64>,
+}
+
+/// Nom comparé : minuscules, espaces superflus retirés.
+fn normalize_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Distance d'édition (Levenshtein) entre deux chaînes, par caractère.
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Similarité de deux noms entre 0 et 1 (1 - distance d'édition / longueur du plus long nom normalisé).
+pub fn name_similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = normalize_name(a).chars().collect();
+    let b: Vec<char> = normalize_name(b).chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 0.0;
+    }
+    1.0 - levenshtein(&a, &b) as f64 / longest as f64
+}
+
+/// Score `other` comme doublon potentiel de `guest` ; None si le score est sous `MIN_DUPLICATE_SCORE`
+/// ou si `other` est le même guest (les guests effacés ne sont jamais des doublons).
+pub fn score_duplicate(guest: &Guest, other: &Guest) -> Option<DuplicateCandidate> {
+    if guest.id == other.id || guest.is_erased() || other.is_erased() {
+        return None;
+    }
+    let mut score = 0.0;
+    let mut reasons = Vec::new();
+
+    let contacts = guest.normalized_contacts();
+    for (kind, normalized) in other.normalized_contacts() {
+        if contacts.contains(&(kind, normalized.clone())) {
+            score += match kind {
+                ContactKind::Mail => MAIL_WEIGHT,
+                ContactKind::Phone => PHONE_WEIGHT,
+            };
+            reasons.push(DuplicateReason {
+                field: kind.as_str().to_string(),
+                value: normalized,
+                similarity: 1.0,
+            });
+        }
+    }
+
+    for (field, weight, ours, theirs) in [
+        (
+            "last_name",
+            LAST_NAME_WEIGHT,
+            &guest.last_name.value,
+            &other.last_name.value,
+        ),
+        (
+            "first_name",
+            FIRST_NAME_WEIGHT,
+            &guest.first_name.value,
+            &other.first_name.value,
+        ),
+    ] {
+        let similarity = name_similarity(ours, theirs);
+        if similarity >= NAME_SIMILARITY_THRESHOLD {
+            score += weight * similarity;
+            reasons.push(DuplicateReason {
+                field: field.to_string(),
+                value: theirs.clone(),
+                similarity,
+            });
+        }
+    }
+
+    let score = score.min(1.0);
+    (score >= MIN_DUPLICATE_SCORE).then(|| DuplicateCandidate {
+        guest: other.clone(),
+        score,
+        reasons,
+    })
+}
+
+/// Classe les doublons potentiels de `guest` parmi `others` : score décroissant, puis uuid.
+pub fn rank_duplicates(guest: &Guest, others: &[Guest]) -> Vec<DuplicateCandidate> {
+    let mut candidates: Vec<DuplicateCandidate> = others
+        .iter()
+        .filter_map(|other| score_duplicate(guest, other))
+        .collect();
+    candidates.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.guest.id.cmp(&b.guest.id))
+    });
+    candidates
+}
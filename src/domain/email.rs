@@ -0,0 +1,267 @@
+//! Adresses email : analyse selon RFC 5321 / 5322 (partie locale dot-atom ou quoted-string,
+//! domaine IDN ou littéral d'adresse IP) et forme canonique (domaine en minuscules / punycode).
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::domain::{normalize_email, Guest, StructuredValue, ValidationError};
+
+/// Longueur maximale d'une adresse (RFC 5321 §4.5.3.1.3, chemin de 256 moins les chevrons).
+const MAX_EMAIL_LENGTH: usize = 254;
+/// Longueur maximale de la partie locale (RFC 5321 §4.5.3.1.1).
+const MAX_LOCAL_PART_LENGTH: usize = 64;
+/// Longueur maximale du domaine (RFC 5321 §4.5.3.1.2).
+const MAX_DOMAIN_LENGTH: usize = 253;
+/// Longueur maximale d'un label du domaine (RFC 1035).
+const MAX_LABEL_LENGTH: usize = 63;
+
+/// Caractère `atext` (RFC 5322 §3.2.3), étendu aux caractères non ASCII (RFC 6531).
+fn is_atext(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(c) || !c.is_ascii()
+}
+
+/// Partie locale dot-atom : atomes non vides séparés par un point.
+fn is_dot_atom(local: &str) -> bool {
+    local
+        .split('.')
+        .all(|atom| !atom.is_empty() && atom.chars().all(is_atext))
+}
+
+/// Partie locale quoted-string : `"..."` avec qtext ou paires `\x` (RFC 5322 §3.2.4).
+fn is_quoted_string(local: &str) -> bool {
+    let Some(inner) = local.strip_prefix('"').and_then(|l| l.strip_suffix('"')) else {
+        return false;
+    };
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(escaped)
+                    if escaped == ' ' || escaped.is_ascii_graphic() || !escaped.is_ascii() => {}
+                _ => return false,
+            },
+            '"' => return false,
+            c if c == ' ' || c.is_ascii_graphic() || !c.is_ascii() => {}
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Littéral d'adresse (`[192.0.2.1]` ou `[IPv6:2001:db8::1]`), rendu tel quel en minuscules.
+fn address_literal(domain: &str) -> Option<String> {
+    let inner = domain.strip_prefix('[')?.strip_suffix(']')?;
+    let valid = match inner.get(..5) {
+        Some(tag) if tag.eq_ignore_ascii_case("IPv6:") => inner[5..].parse::<Ipv6Addr>().is_ok(),
+        _ => inner.parse::<Ipv4Addr>().is_ok(),
+    };
+    valid.then(|| domain.to_lowercase())
+}
+
+/// Domaine en ASCII (IDN converti en punycode, minuscules) ; chaque label respecte la règle LDH.
+fn ascii_domain(domain: &str) -> Result<String, String> {
+    let ascii =
+        idna::domain_to_ascii(domain).map_err(|_| format!("domaine IDN invalide '{}'", domain))?;
+    if ascii.is_empty() || ascii.len() > MAX_DOMAIN_LENGTH {
+        return Err(format!("domaine de 1 à {} caractères", MAX_DOMAIN_LENGTH));
+    }
+    let labels: Vec<&str> = ascii.split('.').collect();
+    if labels.len() < 2 {
+        return Err("domaine sans point (ex. example.com)".into());
+    }
+    for label in labels {
+        let ldh = label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        if label.is_empty()
+            || label.len() > MAX_LABEL_LENGTH
+            || !ldh
+            || label.starts_with('-')
+            || label.ends_with('-')
+        {
+            return Err(format!("label de domaine invalide '{}'", label));
+        }
+    }
+    Ok(ascii)
+}
+
+/// Analyse une adresse email et retourne sa forme canonique : partie locale inchangée,
+/// domaine en minuscules (punycode pour un domaine IDN, ex. `jean@bücher.de` → `jean@xn--bcher-kva.de`).
+pub fn parse_email(raw: &str) -> Result<String, String> {
+    let email = raw.trim();
+    if email.is_empty() {
+        return Err("adresse vide".into());
+    }
+    // La partie locale peut contenir `@` entre guillemets : le domaine suit le dernier `@`.
+    let (local, domain) = email.rsplit_once('@').ok_or("'@' manquant")?;
+    if local.is_empty() || local.len() > MAX_LOCAL_PART_LENGTH {
+        return Err(format!(
+            "partie locale de 1 à {} octets",
+            MAX_LOCAL_PART_LENGTH
+        ));
+    }
+    if !is_dot_atom(local) && !is_quoted_string(local) {
+        return Err(format!("partie locale invalide '{}'", local));
+    }
+    let domain = match address_literal(domain) {
+        Some(literal) => literal,
+        None if domain.starts_with('[') => {
+            return Err(format!("littéral d'adresse invalide '{}'", domain))
+        }
+        None => ascii_domain(domain)?,
+    };
+    let canonical = format!("{}@{}", local, domain);
+    if canonical.len() > MAX_EMAIL_LENGTH {
+        return Err(format!("adresse de plus de {} octets", MAX_EMAIL_LENGTH));
+    }
+    Ok(canonical)
+}
+
+/// Valide une liste d'emails (`mail[i]` dans les messages d'erreur) : chaque adresse ajoutée ou modifiée par
+/// rapport à `stored` doit être analysable et ne doublonner aucune autre adresse une fois normalisée.
+/// Les adresses déjà stockées (données antérieures invalides ou en double, valeurs effacées) ne sont pas
+/// recontrôlées entre elles.
+pub fn validate_emails<'a>(
+    emails: impl IntoIterator<Item = &'a str>,
+    stored: &[StructuredValue<String>],
+) -> Result<(), ValidationError> {
+    // Clé normalisée de chaque adresse vue et `true` si elle est nouvelle.
+    let mut seen: Vec<(String, bool)> = Vec::new();
+    for (i, raw) in emails.into_iter().enumerate() {
+        let is_new = !stored.iter().any(|s| s.value == raw);
+        let canonical = match parse_email(raw) {
+            Ok(canonical) => canonical,
+            Err(e) if is_new => {
+                return Err(ValidationError(format!(
+                    "mail[{}]: value doit être une adresse email valide ({})",
+                    i, e
+                )))
+            }
+            Err(_) => raw.to_string(),
+        };
+        let key = normalize_email(&canonical);
+        if let Some(first) = seen
+            .iter()
+            .position(|(k, first_is_new)| *k == key && (is_new || *first_is_new))
+        {
+            return Err(ValidationError(format!(
+                "mail[{}]: adresse déjà présente en mail[{}]",
+                i, first
+            )));
+        }
+        seen.push((key, is_new));
+    }
+    Ok(())
+}
+
+/// Renseigne la forme canonique (`normalized`) de chaque email analysable de la liste.
+pub(super) fn canonicalize_mail_list(mails: &mut [StructuredValue<String>]) {
+    for mail in mails {
+        if let Ok(canonical) = parse_email(&mail.value) {
+            mail.normalized = Some(canonical);
+        }
+    }
+}
+
+impl Guest {
+    /// Renseigne la forme canonique (`normalized`) de chaque email analysable ; `value` garde la saisie.
+    /// Les adresses non analysables (données antérieures) sont laissées telles quelles.
+    pub fn canonicalize_mails(&mut self) {
+        canonicalize_mail_list(&mut self.mail);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ERASED_VALUE;
+
+    fn stored(values: &[&str]) -> Vec<StructuredValue<String>> {
+        values
+            .iter()
+            .map(|v| StructuredValue::new(v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_email_returns_the_canonical_form() {
+        assert_eq!(
+            parse_email("  Jean.Dupont@Example.COM ").unwrap(),
+            "Jean.Dupont@example.com"
+        );
+        assert_eq!(
+            parse_email("jean@bücher.de").unwrap(),
+            "jean@xn--bcher-kva.de"
+        );
+        assert_eq!(
+            parse_email("\"jean @dupont\"@example.com").unwrap(),
+            "\"jean @dupont\"@example.com"
+        );
+        assert_eq!(parse_email("jean@[192.0.2.1]").unwrap(), "jean@[192.0.2.1]");
+        assert_eq!(
+            parse_email("jean@[IPv6:2001:DB8::1]").unwrap(),
+            "jean@[ipv6:2001:db8::1]"
+        );
+    }
+
+    #[test]
+    fn parse_email_rejects_malformed_addresses() {
+        for raw in [
+            "",
+            "jean.example.com",
+            "@example.com",
+            "jean..dupont@example.com",
+            "jean@localhost",
+            "jean@-example.com",
+            "jean@[300.0.0.1]",
+            "jean dupont@example.com",
+            ERASED_VALUE,
+        ] {
+            assert!(parse_email(raw).is_err(), "{raw}");
+        }
+        let long_local = format!("{}@example.com", "a".repeat(MAX_LOCAL_PART_LENGTH + 1));
+        assert!(parse_email(&long_local).is_err());
+    }
+
+    #[test]
+    fn duplicates_are_detected_after_normalization() {
+        assert!(validate_emails(["ana@example.com", "bob@example.com"], &[]).is_ok());
+        let err = validate_emails(
+            ["ana@example.com", "bob@example.com", " ANA@Example.com"],
+            &[],
+        )
+        .unwrap_err();
+        assert_eq!(err.0, "mail[2]: adresse déjà présente en mail[0]");
+        let err = validate_emails(["ana@example.com", "ana"], &[]).unwrap_err();
+        assert!(
+            err.0
+                .starts_with("mail[1]: value doit être une adresse email valide"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn stored_addresses_are_not_checked_again() {
+        let legacy = stored(&[
+            "ana@",
+            "bob@example.com",
+            "BOB@example.com",
+            ERASED_VALUE,
+            ERASED_VALUE,
+        ]);
+        let values: Vec<&str> = legacy.iter().map(|m| m.value.as_str()).collect();
+        assert!(validate_emails(values.clone(), &legacy).is_ok());
+
+        let mut added = values.clone();
+        added.push("ana@example.com");
+        assert!(validate_emails(added, &legacy).is_ok());
+
+        let mut duplicate = values.clone();
+        duplicate.push("bob@EXAMPLE.com");
+        assert_eq!(
+            validate_emails(duplicate, &legacy).unwrap_err().0,
+            "mail[5]: adresse déjà présente en mail[1]"
+        );
+
+        let mut invalid = values;
+        invalid.push("carla@");
+        assert!(validate_emails(invalid, &legacy).is_err());
+    }
+}
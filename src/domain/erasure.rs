@@ -0,0 +1,77 @@
+//! Effacement RGPD (art. 17) : anonymisation irréversible des données personnelles d'un guest
+//! (noms, emails, téléphones, adresses postales, attributs personnalisés). L'id, les consentements et les métadonnées non personnelles
+//! (provenance, dates, version) sont conservés pour les statistiques et l'intégrité référentielle.
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::{Address, Guest, StructuredValue};
+
+/// Valeur de remplacement d'une donnée effacée.
+pub const ERASED_VALUE: &str = "[erased]";
+
+/// Champs contenant des données personnelles : anonymisés par l'effacement (également dans l'historique)
+/// et chiffrés au repos lorsqu'une clé est configurée.
+pub const PII_FIELDS: [&str; 6] = [
+    "first_name",
+    "last_name",
+    "mail",
+    "phone",
+    "addresses",
+    "attributes",
+];
+
+/// Champ d'historique personnel : un champ de `PII_FIELDS` ou un attribut (`attributes.<nom>`).
+pub fn is_pii_field(field: &str) -> bool {
+    let root = field.split_once('.').map_or(field, |(root, _)| root);
+    PII_FIELDS.contains(&root)
+}
+
+/// Certificat d'effacement conservé comme preuve de la demande.
+#[derive(Debug, Clone)]
+pub struct ErasureCertificate {
+    pub guest_id: uuid::Uuid,
+    pub erased_at: DateTime<Utc>,
+    /// Auteur de la demande (ex. DPO, service juridique).
+    pub requester: String,
+    pub trace_id: Option<String>,
+    /// Champs anonymisés.
+    pub fields: Vec<String>,
+}
+
+/// Remplace la valeur et sa forme canonique ; la provenance est conservée.
+fn tombstone(value: &mut StructuredValue<String>, now: DateTime<Utc>) {
+    value.value = ERASED_VALUE.to_string();
+    value.normalized = None;
+    value.updated_at = now;
+}
+
+/// Efface les composants de l'adresse ; seul le pays est conservé.
+fn tombstone_address(address: &mut StructuredValue<Address>, now: DateTime<Utc>) {
+    address.value.lines = vec![ERASED_VALUE.to_string()];
+    address.value.postal_code = None;
+    address.value.city = ERASED_VALUE.to_string();
+    address.value.region = None;
+    address.updated_at = now;
+}
+
+impl Guest {
+    /// Anonymise noms, emails, téléphones et adresses (une valeur effacée par élément de liste) et retire
+    /// les attributs personnalisés (leurs valeurs n'ont pas de forme anonymisée générique).
+    pub fn erase(&mut self, now: DateTime<Utc>) {
+        tombstone(&mut self.first_name, now);
+        tombstone(&mut self.last_name, now);
+        for value in self.mail.iter_mut().chain(self.phone.iter_mut()) {
+            tombstone(value, now);
+        }
+        for address in &mut self.addresses {
+            tombstone_address(address, now);
+        }
+        self.attributes.clear();
+        self.erased_at = Some(now);
+    }
+
+    /// Le guest a été effacé (date d'effacement persistée, et non des noms valant `[erased]`).
+    pub fn is_erased(&self) -> bool {
+        self.erased_at.is_some()
+    }
+}
@@ -3,7 +3,9 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-/// Valeur structurée générique : value + provenance + dates (optionnel preferred pour listes mail/phone).
+use crate::domain::{Address, Consent, GuestAttributes};
+
+/// Valeur structurée générique : value + provenance + dates (optionnel preferred pour listes mail/phone/addresses).
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StructuredValue<T> {
     pub value: T,
@@ -12,6 +14,9 @@ pub struct StructuredValue<T> {
     pub updated_at: DateTime<Utc>,
     #[serde(default)]
     pub preferred_at: Option<DateTime<Utc>>,
+    /// Forme canonique de la valeur quand elle est connue (ex. E.164 pour un téléphone, domaine punycode pour un email) ; `value` garde la saisie.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normalized: Option<String>,
 }
 
 impl<T> StructuredValue<T> {
@@ -21,6 +26,7 @@ impl<T> StructuredValue<T> {
             from: None,
             updated_at: Utc::now(),
             preferred_at: None,
+            normalized: None,
         }
     }
 
@@ -30,6 +36,7 @@ impl<T> StructuredValue<T> {
             from: Some(from),
             updated_at: Utc::now(),
             preferred_at: None,
+            normalized: None,
         }
     }
 
@@ -39,6 +46,7 @@ impl<T> StructuredValue<T> {
             from: None,
             updated_at,
             preferred_at: None,
+            normalized: None,
         }
     }
 }
@@ -51,7 +59,20 @@ pub struct Guest {
     pub last_name: StructuredValue<String>,
     pub mail: Vec<StructuredValue<String>>,
     pub phone: Vec<StructuredValue<String>>,
-    pub opt_outs: Vec<StructuredValue<bool>>,
+    /// Adresses postales (au plus une préférée).
+    #[serde(default)]
+    pub addresses: Vec<StructuredValue<Address>>,
+    /// Consentements par canal et finalité (au plus un par couple).
+    pub consents: Vec<StructuredValue<Consent>>,
+    /// Attributs personnalisés, par nom (validés contre le registre des définitions).
+    #[serde(default)]
+    pub attributes: GuestAttributes,
+    /// Version du guest, incrémentée à chaque écriture (concurrence optimiste).
+    #[serde(default)]
+    pub version: i64,
+    /// Date de l'effacement RGPD, posée uniquement par `Guest::erase` (None si le guest n'a pas été effacé).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub erased_at: Option<DateTime<Utc>>,
 }
 
 impl Guest {
@@ -62,7 +83,40 @@ impl Guest {
             last_name: StructuredValue::new(last_name),
             mail: Vec::new(),
             phone: Vec::new(),
-            opt_outs: Vec::new(),
+            addresses: Vec::new(),
+            consents: Vec::new(),
+            attributes: GuestAttributes::new(),
+            version: 0,
+            erased_at: None,
         }
     }
+
+    /// Date de dernière modification du guest : le plus récent `updated_at` de ses champs structurés.
+    pub fn last_updated_at(&self) -> DateTime<Utc> {
+        [self.first_name.updated_at, self.last_name.updated_at]
+            .into_iter()
+            .chain(self.mail.iter().map(|v| v.updated_at))
+            .chain(self.phone.iter().map(|v| v.updated_at))
+            .chain(self.addresses.iter().map(|v| v.updated_at))
+            .chain(self.consents.iter().map(|v| v.updated_at))
+            .chain(self.attributes.values().map(|v| v.updated_at))
+            .max()
+            .unwrap_or(self.first_name.updated_at)
+    }
+
+    /// Email préféré du guest, à défaut le premier de la liste.
+    pub fn preferred_mail(&self) -> Option<&StructuredValue<String>> {
+        preferred_or_first(&self.mail)
+    }
+
+    /// Téléphone préféré du guest, à défaut le premier de la liste.
+    pub fn preferred_phone(&self) -> Option<&StructuredValue<String>> {
+        preferred_or_first(&self.phone)
+    }
+}
+
+fn preferred_or_first<T>(list: &[StructuredValue<T>]) -> Option<&StructuredValue<T>> {
+    list.iter()
+        .find(|v| v.preferred_at.is_some())
+        .or_else(|| list.first())
 }
@@ -0,0 +1,310 @@
+//! Historique des modifications d'un guest : une entrée par champ (ou élément de liste) modifié.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::domain::{Consent, ContactKind, Guest, GuestAttributes, StructuredValue};
+
+/// Contexte d'une écriture, reporté dans l'historique (ex. trace_id de la requête HTTP).
+#[derive(Debug, Clone, Default)]
+pub struct ChangeContext {
+    pub trace_id: Option<String>,
+}
+
+impl ChangeContext {
+    pub fn with_trace_id(trace_id: String) -> Self {
+        Self {
+            trace_id: Some(trace_id).filter(|t| !t.is_empty()),
+        }
+    }
+}
+
+/// Opération ayant produit une entrée d'historique.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeOperation {
+    Create,
+    Update,
+    Delete,
+    Restore,
+    Merge,
+    Erase,
+}
+
+impl ChangeOperation {
+    /// Nom stable de l'opération (valeur persistée en base).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeOperation::Create => "create",
+            ChangeOperation::Update => "update",
+            ChangeOperation::Delete => "delete",
+            ChangeOperation::Restore => "restore",
+            ChangeOperation::Merge => "merge",
+            ChangeOperation::Erase => "erase",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "create" => Some(ChangeOperation::Create),
+            "update" => Some(ChangeOperation::Update),
+            "delete" => Some(ChangeOperation::Delete),
+            "restore" => Some(ChangeOperation::Restore),
+            "merge" => Some(ChangeOperation::Merge),
+            "erase" => Some(ChangeOperation::Erase),
+            _ => None,
+        }
+    }
+}
+
+/// Modification d'un champ : valeur avant / après (StructuredValue en JSON, None = absente).
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old_value: Option<Value>,
+    pub new_value: Option<Value>,
+    /// Provenance de la nouvelle valeur.
+    pub from: Option<String>,
+}
+
+/// Entrée d'historique persistée.
+#[derive(Debug, Clone)]
+pub struct GuestHistoryEntry {
+    /// Numéro de séquence (croissant dans l'ordre d'écriture).
+    pub seq: i64,
+    pub guest_id: uuid::Uuid,
+    pub operation: ChangeOperation,
+    pub change: FieldChange,
+    pub changed_at: DateTime<Utc>,
+    pub trace_id: Option<String>,
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+fn diff_value<T: Serialize + PartialEq>(
+    field: &str,
+    old: Option<&StructuredValue<T>>,
+    new: Option<&StructuredValue<T>>,
+    changes: &mut Vec<FieldChange>,
+) {
+    if old == new {
+        return;
+    }
+    changes.push(FieldChange {
+        field: field.to_string(),
+        old_value: old.map(to_value),
+        new_value: new.map(to_value),
+        from: new.and_then(|n| n.from.clone()),
+    });
+}
+
+/// Compare deux listes élément par élément (appariés par valeur) : ajout, retrait ou modification.
+fn diff_list<T: Serialize + PartialEq>(
+    field: &str,
+    old: &[StructuredValue<T>],
+    new: &[StructuredValue<T>],
+    same_value: impl Fn(&StructuredValue<T>, &StructuredValue<T>) -> bool,
+    changes: &mut Vec<FieldChange>,
+) {
+    for item in new {
+        let previous = old.iter().find(|o| same_value(o, item));
+        diff_value(field, previous, Some(item), changes);
+    }
+    for item in old {
+        if !new.iter().any(|n| same_value(n, item)) {
+            diff_value(field, Some(item), None, changes);
+        }
+    }
+}
+
+/// Champs modifiés entre deux états d'un guest (None = guest inexistant avant création / après suppression) ;
+/// un attribut personnalisé est reporté sous le champ `attributes.<nom>`.
+pub fn diff_guests(old: Option<&Guest>, new: Option<&Guest>) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    diff_value(
+        "first_name",
+        old.map(|g| &g.first_name),
+        new.map(|g| &g.first_name),
+        &mut changes,
+    );
+    diff_value(
+        "last_name",
+        old.map(|g| &g.last_name),
+        new.map(|g| &g.last_name),
+        &mut changes,
+    );
+    let empty_strings: Vec<StructuredValue<String>> = Vec::new();
+    let empty_consents: Vec<StructuredValue<Consent>> = Vec::new();
+    for (kind, old_list, new_list) in [
+        (
+            ContactKind::Mail,
+            old.map_or(&empty_strings, |g| &g.mail),
+            new.map_or(&empty_strings, |g| &g.mail),
+        ),
+        (
+            ContactKind::Phone,
+            old.map_or(&empty_strings, |g| &g.phone),
+            new.map_or(&empty_strings, |g| &g.phone),
+        ),
+    ] {
+        diff_list(
+            kind.as_str(),
+            old_list,
+            new_list,
+            |a, b| kind.same_contact(a, b),
+            &mut changes,
+        );
+    }
+    diff_list(
+        "addresses",
+        old.map_or(&[][..], |g| &g.addresses),
+        new.map_or(&[][..], |g| &g.addresses),
+        |a, b| a.value.same_as(&b.value),
+        &mut changes,
+    );
+    diff_list(
+        "consents",
+        old.map_or(&empty_consents, |g| &g.consents),
+        new.map_or(&empty_consents, |g| &g.consents),
+        |a, b| a.value.same_scope(&b.value),
+        &mut changes,
+    );
+    let empty_attributes = GuestAttributes::new();
+    let old_attributes = old.map_or(&empty_attributes, |g| &g.attributes);
+    let new_attributes = new.map_or(&empty_attributes, |g| &g.attributes);
+    let mut names: Vec<&String> = old_attributes.keys().chain(new_attributes.keys()).collect();
+    names.sort();
+    names.dedup();
+    for name in names {
+        diff_value(
+            &format!("attributes.{}", name),
+            old_attributes.get(name),
+            new_attributes.get(name),
+            &mut changes,
+        );
+    }
+    changes
+}
+
+/// Pagination de l'historique d'un guest (du plus récent au plus ancien).
+#[derive(Debug, Clone, Default)]
+pub struct HistoryQuery {
+    /// Reprend l'historique avant cette séquence (curseur).
+    pub before: Option<i64>,
+    pub limit: u32,
+}
+
+/// Une page d'historique ; `next` vaut la séquence à passer dans `before` pour la page suivante.
+#[derive(Debug, Clone)]
+pub struct HistoryPage {
+    pub entries: Vec<GuestHistoryEntry>,
+    pub next: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn guest() -> Guest {
+        Guest::new(uuid::Uuid::new_v4(), "Ana".into(), "Lopez".into())
+    }
+
+    fn fields(changes: &[FieldChange]) -> Vec<&str> {
+        changes.iter().map(|c| c.field.as_str()).collect()
+    }
+
+    #[test]
+    fn creation_and_deletion_list_every_present_field() {
+        let mut ana = guest();
+        ana.mail
+            .push(StructuredValue::new("ana@example.com".into()));
+        ana.attributes
+            .insert("tier".into(), StructuredValue::new(json!("gold")));
+
+        let created = diff_guests(None, Some(&ana));
+        assert_eq!(
+            fields(&created),
+            ["first_name", "last_name", "mail", "attributes.tier"]
+        );
+        assert!(created
+            .iter()
+            .all(|c| c.old_value.is_none() && c.new_value.is_some()));
+        assert_eq!(
+            created[2].new_value.as_ref().unwrap()["value"],
+            "ana@example.com"
+        );
+
+        let deleted = diff_guests(Some(&ana), None);
+        assert_eq!(fields(&deleted), fields(&created));
+        assert!(deleted
+            .iter()
+            .all(|c| c.old_value.is_some() && c.new_value.is_none() && c.from.is_none()));
+        assert!(diff_guests(Some(&ana), Some(&ana)).is_empty());
+    }
+
+    #[test]
+    fn list_elements_are_matched_by_value() {
+        let mut before = guest();
+        before
+            .mail
+            .push(StructuredValue::new("ana@example.com".into()));
+        before
+            .phone
+            .push(StructuredValue::new("+33612345678".into()));
+        let mut after = before.clone();
+        after.mail[0] = StructuredValue::with_from("ANA@example.com".into(), "web".into());
+        after.phone.clear();
+        after
+            .phone
+            .push(StructuredValue::new("+33699999999".into()));
+
+        let changes = diff_guests(Some(&before), Some(&after));
+        assert_eq!(fields(&changes), ["mail", "phone", "phone"]);
+        // Même adresse (casse près) : une modification, pas un retrait suivi d'un ajout.
+        assert_eq!(
+            changes[0].old_value.as_ref().unwrap()["value"],
+            "ana@example.com"
+        );
+        assert_eq!(
+            changes[0].new_value.as_ref().unwrap()["value"],
+            "ANA@example.com"
+        );
+        assert_eq!(changes[0].from.as_deref(), Some("web"));
+        assert_eq!(
+            (
+                changes[1].old_value.is_none(),
+                changes[2].new_value.is_none()
+            ),
+            (true, true)
+        );
+        assert_eq!(
+            changes[2].old_value.as_ref().unwrap()["value"],
+            "+33612345678"
+        );
+    }
+
+    #[test]
+    fn attributes_are_reported_by_name() {
+        let mut before = guest();
+        before
+            .attributes
+            .insert("tier".into(), StructuredValue::new(json!("gold")));
+        before
+            .attributes
+            .insert("room".into(), StructuredValue::new(json!(12)));
+        let mut after = before.clone();
+        after.attributes.remove("room");
+        after
+            .attributes
+            .insert("tier".into(), StructuredValue::new(json!("platinum")));
+
+        let changes = diff_guests(Some(&before), Some(&after));
+        assert_eq!(fields(&changes), ["attributes.room", "attributes.tier"]);
+        assert!(changes[0].new_value.is_none());
+        assert_eq!(changes[1].new_value.as_ref().unwrap()["value"], "platinum");
+    }
+}
@@ -0,0 +1,48 @@
+//! Requêtes idempotentes (en-tête `Idempotency-Key`) : une clé rejouée renvoie la réponse d'origine
+//! tant qu'elle n'a pas expiré ; réutilisée avec un autre corps, elle est refusée.
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::ValidationError;
+
+/// Longueur maximale d'une clé d'idempotence.
+const MAX_KEY_LEN: usize = 255;
+
+/// Réponse enregistrée pour être rejouée à l'identique (corps JSON).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// Clé enregistrée : empreinte du corps de la requête d'origine et sa réponse (None tant que la requête
+/// d'origine est en cours, ou une fois effacée).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdempotencyRecord {
+    pub fingerprint: String,
+    pub response: Option<StoredResponse>,
+    pub created_at: DateTime<Utc>,
+    /// Date d'effacement de la réponse (ressource créée effacée ou supprimée) : la clé ne se rejoue plus.
+    pub erased_at: Option<DateTime<Utc>>,
+}
+
+/// Résultat de la réservation d'une clé.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdempotencyReservation {
+    /// Clé nouvelle (ou expirée) : la requête doit être traitée puis sa réponse enregistrée.
+    Reserved,
+    /// Clé déjà utilisée et non expirée.
+    Existing(IdempotencyRecord),
+}
+
+/// Valide une clé d'idempotence : non vide, 255 caractères ASCII visibles au plus.
+pub fn validate_idempotency_key(key: &str) -> Result<(), ValidationError> {
+    if key.is_empty() || key.len() > MAX_KEY_LEN || !key.bytes().all(|b| b.is_ascii_graphic()) {
+        return Err(ValidationError(format!(
+            "Idempotency-Key: 1 à {} caractères ASCII visibles",
+            MAX_KEY_LEN
+        )));
+    }
+    Ok(())
+}
@@ -1,12 +1,58 @@
 //! Domaine : entités, règles de validation et interfaces (traits).
 //! Équivalent du root Go : types du domaine + validators + interfaces.
 
+mod address;
+mod attribute;
+mod changes;
+mod consent;
+mod contact;
+mod contactability;
+mod duplicates;
+mod email;
+mod erasure;
 mod guest;
+mod history;
+mod idempotency;
 mod item;
+mod phone;
+mod relationship;
 mod repository;
+mod segment;
+mod survivorship;
 mod validation;
 
+pub use address::{validate_addresses, Address};
+pub use attribute::{validate_attributes, AttributeDefinition, AttributeType, GuestAttributes};
+pub use changes::{merge_guests, sync_last_writer_wins, GuestChanges, SyncReport};
+pub use consent::{
+    keep_opt_outs, may_replace_consent, validate_consents, Consent, ConsentChannel, ConsentPurpose,
+    ConsentStatus, LegalBasis, OptOutEvent, OptOutEventRecord,
+};
+pub use contact::{normalize_email, normalize_phone, ContactKind};
+pub use contactability::{contactability, Contactability, ContactabilityReason};
+pub use duplicates::{
+    name_similarity, rank_duplicates, score_duplicate, DuplicateCandidate, DuplicatePair,
+    DuplicatePairPage, DuplicatePairQuery, DuplicateReason,
+};
+pub use email::{parse_email, validate_emails};
+pub use erasure::{is_pii_field, ErasureCertificate, ERASED_VALUE, PII_FIELDS};
 pub use guest::{Guest, StructuredValue};
+pub use history::{
+    diff_guests, ChangeContext, ChangeOperation, FieldChange, GuestHistoryEntry, HistoryPage,
+    HistoryQuery,
+};
+pub use idempotency::{
+    validate_idempotency_key, IdempotencyRecord, IdempotencyReservation, StoredResponse,
+};
 pub use item::Item;
-pub use repository::{GuestRepository, ItemRepository, RepositoryError};
-pub use validation::{validate_item_name, ValidationError};
\ No newline at end of file
+pub use phone::{parse_phone, phone_line_type, ParsedPhone, PhoneLineType, PhoneRegion};
+pub use relationship::{Relationship, RelationshipKind};
+pub use repository::{
+    AttributeRepository, GuestListQuery, GuestPage, GuestRepository, IdempotencyRepository,
+    ItemRepository, RelationshipRepository, RepositoryError, SegmentRepository,
+};
+pub use segment::{parse_segment, Segment, SegmentExpr};
+pub use survivorship::{
+    apply_survivorship, SurvivorshipDecision, SurvivorshipRule, SurvivorshipRules,
+};
+pub use validation::{validate_item_name, ValidationError};
@@ -0,0 +1,105 @@
+//! Numéros de téléphone : analyse avec une région par défaut, forme canonique E.164 et type de ligne.
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{Guest, StructuredValue};
+
+/// Région (code pays ISO 3166-1 alpha-2) utilisée pour les numéros saisis sans indicatif international.
+pub type PhoneRegion = phonenumber::country::Id;
+
+/// Type de ligne d'un numéro.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhoneLineType {
+    Mobile,
+    Landline,
+    /// Indéterminable depuis le numéro (ex. fixe ou mobile aux États-Unis, VoIP, numéro spécial).
+    Unknown,
+}
+
+impl PhoneLineType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PhoneLineType::Mobile => "mobile",
+            PhoneLineType::Landline => "landline",
+            PhoneLineType::Unknown => "unknown",
+        }
+    }
+}
+
+/// Numéro analysé : forme E.164 (ex. `+33612345678`) et type de ligne.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedPhone {
+    pub e164: String,
+    pub line_type: PhoneLineType,
+}
+
+/// Analyse un numéro ; `default_region` s'applique aux numéros sans indicatif (ex. `06 12 34 56 78`).
+/// Erreur si le numéro ne peut pas être lu ou n'est pas un numéro attribuable.
+pub fn parse_phone(raw: &str, default_region: Option<PhoneRegion>) -> Result<ParsedPhone, String> {
+    let number = phonenumber::parse(default_region, raw.trim()).map_err(|e| e.to_string())?;
+    if !number.is_valid() {
+        return Err("numéro inexistant pour sa région".into());
+    }
+    let line_type = match number.number_type(&phonenumber::metadata::DATABASE) {
+        phonenumber::Type::Mobile => PhoneLineType::Mobile,
+        phonenumber::Type::FixedLine => PhoneLineType::Landline,
+        _ => PhoneLineType::Unknown,
+    };
+    Ok(ParsedPhone {
+        e164: number.format().mode(phonenumber::Mode::E164).to_string(),
+        line_type,
+    })
+}
+
+/// Renseigne la forme E.164 (`normalized`) de chaque téléphone analysable de la liste.
+pub(super) fn canonicalize_phone_list(
+    phones: &mut [StructuredValue<String>],
+    default_region: Option<PhoneRegion>,
+) {
+    for phone in phones {
+        if let Ok(parsed) = parse_phone(&phone.value, default_region) {
+            phone.normalized = Some(parsed.e164);
+        }
+    }
+}
+
+/// Type de ligne d'un téléphone, déduit de sa forme E.164 ; None si le numéro n'a pas de forme canonique.
+pub fn phone_line_type(phone: &StructuredValue<String>) -> Option<PhoneLineType> {
+    let e164 = phone.normalized.as_deref()?;
+    parse_phone(e164, None).ok().map(|parsed| parsed.line_type)
+}
+
+impl Guest {
+    /// Renseigne la forme E.164 (`normalized`) de chaque téléphone analysable ;
+    /// `value` garde la saisie d'origine. Les numéros non analysables (données antérieures) sont laissés tels quels.
+    pub fn canonicalize_phones(&mut self, default_region: Option<PhoneRegion>) {
+        canonicalize_phone_list(&mut self.phone, default_region);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_type_is_derived_from_the_e164_form() {
+        let mut guest = Guest::new(uuid::Uuid::new_v4(), "Ana".into(), "Lopez".into());
+        for number in ["06 12 34 56 78", "01 40 00 00 00", "not a phone"] {
+            guest.phone.push(StructuredValue::new(number.to_string()));
+        }
+        guest.canonicalize_phones(Some(phonenumber::country::Id::FR));
+        let line_types: Vec<Option<PhoneLineType>> =
+            guest.phone.iter().map(phone_line_type).collect();
+        assert_eq!(
+            line_types,
+            [
+                Some(PhoneLineType::Mobile),
+                Some(PhoneLineType::Landline),
+                None
+            ]
+        );
+        assert_eq!(guest.phone[0].normalized.as_deref(), Some("+33612345678"));
+        assert_eq!(guest.phone[0].value, "06 12 34 56 78");
+    }
+}
@@ -0,0 +1,80 @@
+//! Relations entre guests (familles, groupes d'entreprise) : conjoint, enfant, collègue, réservation pour
+//! un tiers, membre du foyer. Les relations `child` et `booker_for` sont orientées, les autres symétriques.
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::ValidationError;
+
+/// Nature de la relation entre `guest_id` et `related_id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RelationshipKind {
+    /// Conjoint (symétrique).
+    Spouse,
+    /// `related_id` est l'enfant de `guest_id`.
+    Child,
+    /// Collègue (symétrique).
+    Colleague,
+    /// `guest_id` réserve pour `related_id` (assistant, agence…).
+    BookerFor,
+    /// Membre du même foyer (symétrique).
+    HouseholdMember,
+}
+
+impl RelationshipKind {
+    /// Nom stable de la relation (valeur persistée et exposée par l'API).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RelationshipKind::Spouse => "spouse",
+            RelationshipKind::Child => "child",
+            RelationshipKind::Colleague => "colleague",
+            RelationshipKind::BookerFor => "booker_for",
+            RelationshipKind::HouseholdMember => "household_member",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "spouse" => Some(RelationshipKind::Spouse),
+            "child" => Some(RelationshipKind::Child),
+            "colleague" => Some(RelationshipKind::Colleague),
+            "booker_for" => Some(RelationshipKind::BookerFor),
+            "household_member" => Some(RelationshipKind::HouseholdMember),
+            _ => None,
+        }
+    }
+
+    /// La relation vaut dans les deux sens (A conjoint de B ⇔ B conjoint de A).
+    pub fn is_symmetric(&self) -> bool {
+        !matches!(self, RelationshipKind::Child | RelationshipKind::BookerFor)
+    }
+}
+
+/// Relation entre deux guests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relationship {
+    pub guest_id: uuid::Uuid,
+    pub related_id: uuid::Uuid,
+    pub kind: RelationshipKind,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Relationship {
+    /// Un guest ne peut pas être en relation avec lui-même.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.guest_id == self.related_id {
+            return Err(ValidationError(
+                "related_id: un guest ne peut pas être en relation avec lui-même".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Relation vue depuis `id` : une relation symétrique est renvoyée avec `id` en `guest_id` ;
+    /// une relation orientée garde son sens.
+    pub fn seen_from(mut self, id: &uuid::Uuid) -> Self {
+        if self.kind.is_symmetric() && self.related_id == *id {
+            std::mem::swap(&mut self.guest_id, &mut self.related_id);
+        }
+        self
+    }
+}
@@ -2,13 +2,28 @@
 //! Les implémentations (store) vivent dans `pkg/store` / `store`.
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::stream::BoxStream;
 
-use crate::domain::{Guest, Item};
+use crate::domain::{
+    AttributeDefinition, ChangeContext, ContactKind, DuplicatePair, DuplicatePairPage,
+    DuplicatePairQuery, ErasureCertificate, Guest, HistoryPage, HistoryQuery,
+    IdempotencyReservation, Item, OptOutEvent, OptOutEventRecord, PhoneRegion, Relationship,
+    RelationshipKind, Segment, StoredResponse,
+};
 
 /// Erreur retournée par le repository.
 #[derive(Debug)]
 pub enum RepositoryError {
     NotFound(String),
+    /// La version attendue ne correspond plus à la version stockée (écriture concurrente).
+    VersionConflict {
+        id: String,
+        expected: i64,
+        actual: i64,
+    },
+    /// L'écriture contredit l'état stocké (ex. uuid d'un guest fusionné réutilisé).
+    Conflict(String),
     Other(String),
 }
 
@@ -16,7 +31,16 @@ impl std::fmt::Display for RepositoryError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             RepositoryError::NotFound(id) => write!(f, "not found: {}", id),
-            RepositoryError::Other(msg) => write!(f, "{}", msg),
+            RepositoryError::VersionConflict {
+                id,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "version conflict on {}: expected {}, found {}",
+                id, expected, actual
+            ),
+            RepositoryError::Conflict(msg) | RepositoryError::Other(msg) => write!(f, "{}", msg),
         }
     }
 }
@@ -33,18 +57,280 @@ pub trait ItemRepository: Send + Sync {
     async fn get_by_id(&self, id: &str) -> Result<Option<Item>, RepositoryError>;
 }
 
+/// Interface du registre des attributs personnalisés des guests.
+#[async_trait]
+pub trait AttributeRepository: Send + Sync {
+    /// Définitions triées par nom.
+    async fn list(&self) -> Result<Vec<AttributeDefinition>, RepositoryError>;
+
+    /// Récupère une définition par nom.
+    async fn get(&self, name: &str) -> Result<Option<AttributeDefinition>, RepositoryError>;
+
+    /// Crée ou remplace la définition portant ce nom ; retourne `true` si elle a été créée.
+    /// Les valeurs déjà stockées ne sont pas revalidées (elles le seront à la prochaine écriture du guest).
+    async fn save(&self, definition: &AttributeDefinition) -> Result<bool, RepositoryError>;
+
+    /// Supprime une définition ; retourne `false` si elle n'existe pas.
+    async fn delete(&self, name: &str) -> Result<bool, RepositoryError>;
+
+    /// Nombre de guests (actifs ou supprimés non purgés) portant une valeur pour cet attribut.
+    async fn usage(&self, name: &str) -> Result<u64, RepositoryError>;
+}
+
+/// Interface du stockage des segments de guests (expressions nommées).
+#[async_trait]
+pub trait SegmentRepository: Send + Sync {
+    /// Segments triés par nom.
+    async fn list(&self) -> Result<Vec<Segment>, RepositoryError>;
+
+    /// Récupère un segment par nom.
+    async fn get(&self, name: &str) -> Result<Option<Segment>, RepositoryError>;
+
+    /// Crée ou remplace le segment portant ce nom ; retourne `true` s'il a été créé.
+    async fn save(&self, segment: &Segment) -> Result<bool, RepositoryError>;
+
+    /// Supprime un segment ; retourne `false` s'il n'existe pas.
+    async fn delete(&self, name: &str) -> Result<bool, RepositoryError>;
+}
+
+/// Interface du stockage des relations entre guests.
+#[async_trait]
+pub trait RelationshipRepository: Send + Sync {
+    /// Relations du guest dans les deux sens (vues depuis `guest_id`, voir `Relationship::seen_from`),
+    /// par date de création.
+    async fn list(&self, guest_id: &uuid::Uuid) -> Result<Vec<Relationship>, RepositoryError>;
+
+    /// Enregistre une relation ; retourne `false` si elle existe déjà (dans un sens ou l'autre pour une
+    /// relation symétrique). `RepositoryError::NotFound` si l'un des deux guests n'existe pas.
+    async fn add(&self, relationship: &Relationship) -> Result<bool, RepositoryError>;
+
+    /// Supprime les relations entre deux guests, dans les deux sens (seulement celles de ce type si `kind`
+    /// est fourni) ; retourne le nombre de relations supprimées.
+    async fn remove(
+        &self,
+        guest_id: &uuid::Uuid,
+        related_id: &uuid::Uuid,
+        kind: Option<RelationshipKind>,
+    ) -> Result<u64, RepositoryError>;
+}
+
+/// Interface du stockage des clés d'idempotence (par portée, ex. `POST /guests`).
+#[async_trait]
+pub trait IdempotencyRepository: Send + Sync {
+    /// Réserve la clé pour une requête d'empreinte `fingerprint` ; une clé créée avant `expired_before`
+    /// est remplacée. Retourne l'enregistrement existant si la clé est déjà utilisée.
+    async fn reserve(
+        &self,
+        scope: &str,
+        key: &str,
+        fingerprint: &str,
+        now: DateTime<Utc>,
+        expired_before: DateTime<Utc>,
+    ) -> Result<IdempotencyReservation, RepositoryError>;
+
+    /// Enregistre la réponse de la requête ayant réservé la clé et l'id de la ressource qu'elle a créée
+    /// (la réponse est effacée avec la ressource, voir `GuestRepository::erase`).
+    async fn complete(
+        &self,
+        scope: &str,
+        key: &str,
+        resource_id: Option<&str>,
+        response: &StoredResponse,
+    ) -> Result<(), RepositoryError>;
+
+    /// Libère une clé réservée (la requête d'origine n'a pas abouti et pourra être retentée).
+    async fn release(&self, scope: &str, key: &str) -> Result<(), RepositoryError>;
+
+    /// Supprime les clés créées avant `expired_before` ; retourne le nombre de clés supprimées.
+    async fn purge_expired(&self, expired_before: DateTime<Utc>) -> Result<u64, RepositoryError>;
+}
+
+/// Filtres et pagination pour lister les guests (tous les filtres sont combinés en ET).
+#[derive(Debug, Clone, Default)]
+pub struct GuestListQuery {
+    /// Au moins un champ structuré (nom, mail, phone, consentement, attribut) a cette provenance `from`.
+    pub from: Option<String>,
+    /// Dernière écriture du guest (heure serveur) >= cette date.
+    pub updated_after: Option<DateTime<Utc>>,
+    /// Dernière écriture du guest (heure serveur) < cette date.
+    pub updated_before: Option<DateTime<Utc>>,
+    /// `Some(true)` : au moins un mail ; `Some(false)` : aucun mail.
+    pub has_mail: Option<bool>,
+    /// `Some(true)` : au moins un téléphone ; `Some(false)` : aucun téléphone.
+    pub has_phone: Option<bool>,
+    /// Reprend la liste après ce guest (pagination par curseur, tri par uuid).
+    pub after: Option<uuid::Uuid>,
+    /// Nombre maximum de guests retournés.
+    pub limit: u32,
+}
+
+/// Une page de guests ; `next` vaut l'uuid à passer dans `after` pour la page suivante.
+#[derive(Debug, Clone)]
+pub struct GuestPage {
+    pub guests: Vec<Guest>,
+    pub next: Option<uuid::Uuid>,
+}
+
 /// Interface du store des guests.
+/// Chaque écriture (create / update / delete) ajoute ses modifications à l'historique du guest,
+/// dans la même transaction, avec le contexte `ChangeContext`.
 #[async_trait]
 pub trait GuestRepository: Send + Sync {
-    /// Crée un guest et le persiste.
-    async fn create(&self, guest: Guest) -> Result<Guest, RepositoryError>;
+    /// Crée un guest et le persiste (version 1).
+    async fn create(&self, guest: Guest, ctx: &ChangeContext) -> Result<Guest, RepositoryError>;
+
+    /// Crée plusieurs guests dans une seule transaction (tout ou rien), ex. import en masse.
+    async fn create_many(
+        &self,
+        guests: Vec<Guest>,
+        ctx: &ChangeContext,
+    ) -> Result<Vec<Guest>, RepositoryError>;
 
     /// Récupère un guest par uuid.
     async fn get_by_id(&self, id: &uuid::Uuid) -> Result<Option<Guest>, RepositoryError>;
 
-    /// Met à jour un guest.
-    async fn update(&self, guest: Guest) -> Result<Guest, RepositoryError>;
+    /// Récupère un guest par uuid, y compris supprimé et pas encore purgé (ex. demande d'accès RGPD).
+    async fn get_including_deleted(
+        &self,
+        id: &uuid::Uuid,
+    ) -> Result<Option<Guest>, RepositoryError>;
+
+    /// Liste les guests selon les filtres, page par page.
+    async fn list(&self, query: GuestListQuery) -> Result<GuestPage, RepositoryError>;
+
+    /// Parcourt tous les guests correspondant aux filtres, triés par uuid, en lisant la table par pages
+    /// de `query.limit` guests (mémoire constante quel que soit le nombre de guests).
+    fn export(&self, query: GuestListQuery) -> BoxStream<'static, Result<Guest, RepositoryError>>;
+
+    /// Recherche les guests ayant cette coordonnée (comparée après normalisation).
+    async fn find_by_contact(
+        &self,
+        kind: ContactKind,
+        value: &str,
+    ) -> Result<Vec<Guest>, RepositoryError>;
+
+    /// Met à jour un guest si sa version stockée vaut `guest.version` ; retourne le guest avec la nouvelle version.
+    /// `RepositoryError::VersionConflict` si le guest a été modifié entre-temps.
+    async fn update(&self, guest: Guest, ctx: &ChangeContext) -> Result<Guest, RepositoryError>;
+
+    /// Crée le guest sous son uuid (fourni par le client) si aucun guest ne le porte, sinon le met à jour comme
+    /// `update` (version stockée attendue : `guest.version`), dans une seule transaction ; retourne le guest et
+    /// `true` s'il a été créé. `RepositoryError::VersionConflict` si le guest a été créé entre-temps
+    /// (`guest.version` vaut 0) ou si l'uuid est celui d'un guest supprimé ; `RepositoryError::Conflict`
+    /// si c'est celui d'un guest fusionné (redirection conservée).
+    async fn upsert(
+        &self,
+        guest: Guest,
+        ctx: &ChangeContext,
+    ) -> Result<(Guest, bool), RepositoryError>;
+
+    /// Supprime un guest par uuid (suppression logique : le guest devient invisible mais restaurable),
+    /// supprime ses relations avec les autres guests et efface la réponse d'idempotence de sa création.
+    /// Retourne l'uuid si supprimé.
+    /// Si `expected_version` est fourni, `RepositoryError::VersionConflict` quand la version stockée diffère.
+    async fn delete(
+        &self,
+        id: &uuid::Uuid,
+        expected_version: Option<i64>,
+        ctx: &ChangeContext,
+    ) -> Result<Option<uuid::Uuid>, RepositoryError>;
+
+    /// Restaure un guest supprimé après `deleted_since` ; None s'il n'existe pas de tel guest supprimé.
+    async fn restore(
+        &self,
+        id: &uuid::Uuid,
+        deleted_since: DateTime<Utc>,
+        ctx: &ChangeContext,
+    ) -> Result<Option<Guest>, RepositoryError>;
+
+    /// Effacement RGPD : anonymise noms, emails et téléphones du guest (actif ou supprimé) et de son historique
+    /// (y compris celui des guests fusionnés dans lui), retire ses paires de doublons, efface la réponse
+    /// d'idempotence de sa création et conserve un certificat. Idempotent : un guest déjà effacé n'est pas
+    /// réécrit et son certificat d'origine est retourné. Retourne None si le guest n'existe pas.
+    async fn erase(
+        &self,
+        id: &uuid::Uuid,
+        requester: &str,
+        ctx: &ChangeContext,
+    ) -> Result<Option<ErasureCertificate>, RepositoryError>;
+
+    /// Certificats d'effacement du guest, du plus ancien au plus récent.
+    async fn erasures(&self, id: &uuid::Uuid) -> Result<Vec<ErasureCertificate>, RepositoryError>;
+
+    /// Supprime définitivement les guests supprimés avant `deleted_before`, avec leurs paires de doublons.
+    /// Retourne le nombre purgé.
+    async fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<u64, RepositoryError>;
+
+    /// Réécrit les données personnelles stockées (guests actifs ou supprimés, historique, raisons des doublons)
+    /// avec la clé de chiffrement courante et recalcule les index aveugles (rotation de clé).
+    /// Retourne le nombre de guests réécrits.
+    async fn reencrypt_all(&self) -> Result<u64, RepositoryError>;
+
+    /// Renseigne la forme canonique des emails et téléphones des guests antérieurs à la canonicalisation
+    /// et réécrit leur projection `guest_contacts` (ex. E.164 plutôt que les chiffres saisis). Chaque guest
+    /// n'est traité qu'une fois. Version inchangée. Retourne le nombre de guests réindexés.
+    async fn reindex_contacts(
+        &self,
+        default_region: Option<PhoneRegion>,
+    ) -> Result<u64, RepositoryError>;
+
+    /// Fusionne le guest `source` dans `target` (voir `merge_guests`), supprime définitivement `source`
+    /// et enregistre une redirection de son uuid vers `target` ; les relations de `source` sont reportées sur `target`. None si l'un des deux guests n'existe pas,
+    /// `RepositoryError::Conflict` si l'un des deux a été effacé.
+    async fn merge(
+        &self,
+        target: &uuid::Uuid,
+        source: &uuid::Uuid,
+        ctx: &ChangeContext,
+    ) -> Result<Option<Guest>, RepositoryError>;
+
+    /// Uuid du guest dans lequel `id` a été fusionné (redirection), None si `id` n'a pas été fusionné.
+    async fn merged_into(&self, id: &uuid::Uuid) -> Result<Option<uuid::Uuid>, RepositoryError>;
+
+    /// Uuids des guests fusionnés dans `id` (redirections vers `id`).
+    async fn merged_sources(&self, id: &uuid::Uuid) -> Result<Vec<uuid::Uuid>, RepositoryError>;
+
+    /// Date de suppression d'un guest supprimé et pas encore purgé, None sinon (actif ou inexistant).
+    async fn deleted_at(&self, id: &uuid::Uuid) -> Result<Option<DateTime<Utc>>, RepositoryError>;
+
+    /// Présélection des doublons potentiels de `guest` : guests partageant une coordonnée normalisée
+    /// ou dont le nom de famille commence par la même lettre. Le score est calculé par `rank_duplicates`.
+    async fn duplicate_candidates(&self, guest: &Guest) -> Result<Vec<Guest>, RepositoryError>;
+
+    /// Remplace le résultat du scan global de doublons (paires déjà classées).
+    async fn replace_duplicate_pairs(&self, pairs: &[DuplicatePair])
+        -> Result<(), RepositoryError>;
+
+    /// Paires du dernier scan de doublons impliquant ce guest (actif ou non).
+    async fn duplicate_pairs_of(
+        &self,
+        id: &uuid::Uuid,
+    ) -> Result<Vec<DuplicatePair>, RepositoryError>;
+
+    /// Paires du dernier scan de doublons (guests encore actifs), page par page.
+    async fn list_duplicate_pairs(
+        &self,
+        query: DuplicatePairQuery,
+    ) -> Result<DuplicatePairPage, RepositoryError>;
+
+    /// Conserve un événement opt-out et le résultat de sa publication.
+    async fn record_opt_out_event(
+        &self,
+        event: &OptOutEvent,
+        trace_id: Option<&str>,
+        published: bool,
+    ) -> Result<(), RepositoryError>;
+
+    /// Événements opt-out conservés pour ce guest, du plus ancien au plus récent.
+    async fn opt_out_events(
+        &self,
+        id: &uuid::Uuid,
+    ) -> Result<Vec<OptOutEventRecord>, RepositoryError>;
 
-    /// Supprime un guest par uuid. Retourne l'uuid si supprimé.
-    async fn delete(&self, id: &uuid::Uuid) -> Result<Option<uuid::Uuid>, RepositoryError>;
+    /// Historique des modifications d'un guest, du plus récent au plus ancien (conservé après suppression).
+    async fn history(
+        &self,
+        id: &uuid::Uuid,
+        query: HistoryQuery,
+    ) -> Result<HistoryPage, RepositoryError>;
 }
@@ -0,0 +1,820 @@
+//! Segments dynamiques : expressions de filtre nommées sur les champs du guest et les métadonnées
+//! des valeurs structurées (`value`, `from`, `updated_at`, `preferred_at`), évaluées à la demande.
+//!
+//! Grammaire :
+//! ```text
+//! expr      := and ("or" and)*
+//! and       := unary ("and" unary)*
+//! unary     := "not" unary | "(" expr ")" | predicate
+//! predicate := path "exists" | path op literal
+//! op        := "=" | "!=" | "<" | "<=" | ">" | ">=" | "contains"
+//! literal   := "chaîne" | nombre | true | false | now | now-90d | now+12h
+//! ```
+//!
+//! Chemins :
+//! - `first_name`, `last_name`, `mail`, `phone` suivis de `.value` (par défaut), `.from`, `.updated_at` ou `.preferred_at` ;
+//! - `consents.<canal>[.<finalité>]` suivi de `.status` (par défaut), `.legal_basis`, `.from` ou `.updated_at` ;
+//! - `attributes.<nom>` suivi de `.value` (par défaut), `.from` ou `.updated_at` ;
+//! - `updated_at` : dernière modification du guest.
+//!
+//! Sur une liste (`mail`, `phone`, consentements d'un canal), un prédicat est vrai si au moins un élément le vérifie.
+//! Une comparaison sur une valeur absente (ex. `from` non renseigné) est fausse ; `contains` ignore la casse.
+//! Les dates s'écrivent `"AAAA-MM-JJ"` (minuit UTC), en RFC 3339, ou relativement à l'évaluation (`now-90d`).
+//!
+//! Exemple : `mail.preferred_at exists and not consents.email = "opted_out" and updated_at >= now-90d`.
+
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde_json::Value;
+
+use crate::domain::attribute::validate_name;
+use crate::domain::{ConsentChannel, ConsentPurpose, Guest, StructuredValue, ValidationError};
+
+/// Longueur maximale d'une expression.
+const MAX_EXPRESSION_LEN: usize = 2000;
+/// Profondeur maximale d'imbrication (parenthèses, `not`).
+const MAX_DEPTH: usize = 32;
+
+/// Segment enregistré : un nom et une expression de filtre.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    /// Nom du segment : minuscules, chiffres et `_`, commençant par une lettre.
+    pub name: String,
+    pub expression: String,
+    pub description: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Segment {
+    /// Valide le nom et analyse l'expression.
+    pub fn validate(&self) -> Result<SegmentExpr, ValidationError> {
+        validate_name(&self.name)?;
+        parse_segment(&self.expression)
+    }
+}
+
+/// Opérateur de comparaison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+}
+
+/// Littéral d'une comparaison ; `Now` est résolu à l'évaluation (décalage par rapport à l'instant courant).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Text(String),
+    Number(f64),
+    Bool(bool),
+    Time(DateTime<Utc>),
+    Now(Duration),
+}
+
+/// Métadonnée d'une valeur structurée.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueField {
+    Value,
+    From,
+    UpdatedAt,
+    PreferredAt,
+}
+
+/// Champ d'un consentement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsentField {
+    Status,
+    LegalBasis,
+    From,
+    UpdatedAt,
+}
+
+/// Champ ciblé par un prédicat.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldPath {
+    /// Dernière modification du guest.
+    UpdatedAt,
+    FirstName(ValueField),
+    LastName(ValueField),
+    Mail(ValueField),
+    Phone(ValueField),
+    /// Consentements d'un canal (toutes finalités si None).
+    Consent {
+        channel: ConsentChannel,
+        purpose: Option<ConsentPurpose>,
+        field: ConsentField,
+    },
+    Attribute {
+        name: String,
+        field: ValueField,
+    },
+}
+
+impl FieldPath {
+    /// Le champ est-il une date ?
+    fn is_time(&self) -> bool {
+        match self {
+            FieldPath::UpdatedAt => true,
+            FieldPath::FirstName(f)
+            | FieldPath::LastName(f)
+            | FieldPath::Mail(f)
+            | FieldPath::Phone(f)
+            | FieldPath::Attribute { field: f, .. } => {
+                matches!(f, ValueField::UpdatedAt | ValueField::PreferredAt)
+            }
+            FieldPath::Consent { field, .. } => *field == ConsentField::UpdatedAt,
+        }
+    }
+
+    /// Valeurs du champ pour un guest (une par élément d'une liste ; None = absente).
+    fn values(&self, guest: &Guest) -> Vec<Option<Scalar>> {
+        match self {
+            FieldPath::UpdatedAt => vec![Some(Scalar::Time(guest.last_updated_at()))],
+            FieldPath::FirstName(f) => vec![string_field(&guest.first_name, *f)],
+            FieldPath::LastName(f) => vec![string_field(&guest.last_name, *f)],
+            FieldPath::Mail(f) => guest.mail.iter().map(|v| string_field(v, *f)).collect(),
+            FieldPath::Phone(f) => guest.phone.iter().map(|v| string_field(v, *f)).collect(),
+            FieldPath::Consent {
+                channel,
+                purpose,
+                field,
+            } => guest
+                .consents
+                .iter()
+                .filter(|c| {
+                    c.value.channel == *channel && purpose.is_none_or(|p| c.value.purpose == p)
+                })
+                .map(|c| match field {
+                    ConsentField::Status => Some(Scalar::Text(c.value.status.as_str().into())),
+                    ConsentField::LegalBasis => {
+                        Some(Scalar::Text(c.value.legal_basis.as_str().into()))
+                    }
+                    ConsentField::From => c.from.clone().map(Scalar::Text),
+                    ConsentField::UpdatedAt => Some(Scalar::Time(c.updated_at)),
+                })
+                .collect(),
+            FieldPath::Attribute { name, field } => guest
+                .attributes
+                .get(name)
+                .map(|v| match field {
+                    ValueField::Value => json_scalar(&v.value),
+                    other => metadata(v, *other),
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// Expression de segment analysée.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SegmentExpr {
+    And(Box<SegmentExpr>, Box<SegmentExpr>),
+    Or(Box<SegmentExpr>, Box<SegmentExpr>),
+    Not(Box<SegmentExpr>),
+    Exists(FieldPath),
+    Compare(FieldPath, CompareOp, Literal),
+}
+
+impl SegmentExpr {
+    /// Le guest appartient-il au segment ? `now` résout les dates relatives (`now-90d`).
+    pub fn matches(&self, guest: &Guest, now: DateTime<Utc>) -> bool {
+        match self {
+            SegmentExpr::And(a, b) => a.matches(guest, now) && b.matches(guest, now),
+            SegmentExpr::Or(a, b) => a.matches(guest, now) || b.matches(guest, now),
+            SegmentExpr::Not(e) => !e.matches(guest, now),
+            SegmentExpr::Exists(path) => path.values(guest).iter().any(Option::is_some),
+            SegmentExpr::Compare(path, op, literal) => {
+                let expected = literal.resolve(now);
+                path.values(guest)
+                    .iter()
+                    .flatten()
+                    .any(|actual| compare(actual, *op, &expected))
+            }
+        }
+    }
+}
+
+/// Valeur d'un champ ou d'un littéral résolu.
+#[derive(Debug, Clone, PartialEq)]
+enum Scalar {
+    Text(String),
+    Number(f64),
+    Bool(bool),
+    Time(DateTime<Utc>),
+}
+
+impl Literal {
+    fn resolve(&self, now: DateTime<Utc>) -> Scalar {
+        match self {
+            Literal::Text(s) => Scalar::Text(s.clone()),
+            Literal::Number(n) => Scalar::Number(*n),
+            Literal::Bool(b) => Scalar::Bool(*b),
+            Literal::Time(t) => Scalar::Time(*t),
+            Literal::Now(offset) => Scalar::Time(now.checked_add_signed(*offset).unwrap_or(
+                if *offset < Duration::zero() {
+                    DateTime::<Utc>::MIN_UTC
+                } else {
+                    DateTime::<Utc>::MAX_UTC
+                },
+            )),
+        }
+    }
+}
+
+fn metadata<T>(v: &StructuredValue<T>, field: ValueField) -> Option<Scalar> {
+    match field {
+        ValueField::Value => None,
+        ValueField::From => v.from.clone().map(Scalar::Text),
+        ValueField::UpdatedAt => Some(Scalar::Time(v.updated_at)),
+        ValueField::PreferredAt => v.preferred_at.map(Scalar::Time),
+    }
+}
+
+fn string_field(v: &StructuredValue<String>, field: ValueField) -> Option<Scalar> {
+    match field {
+        ValueField::Value => Some(Scalar::Text(v.value.clone())),
+        other => metadata(v, other),
+    }
+}
+
+/// Valeur JSON d'un attribut : null, tableau et objet sont traités comme absents.
+fn json_scalar(value: &Value) -> Option<Scalar> {
+    match value {
+        Value::String(s) => Some(Scalar::Text(s.clone())),
+        Value::Number(n) => n.as_f64().map(Scalar::Number),
+        Value::Bool(b) => Some(Scalar::Bool(*b)),
+        _ => None,
+    }
+}
+
+/// Compare une valeur au littéral ; des types différents ne sont jamais égaux.
+fn compare(actual: &Scalar, op: CompareOp, expected: &Scalar) -> bool {
+    let ordering = match (actual, expected) {
+        (Scalar::Text(a), Scalar::Text(b)) => {
+            if op == CompareOp::Contains {
+                return a.to_lowercase().contains(&b.to_lowercase());
+            }
+            Some(a.cmp(b))
+        }
+        (Scalar::Number(a), Scalar::Number(b)) => a.partial_cmp(b),
+        (Scalar::Bool(a), Scalar::Bool(b)) => match op {
+            CompareOp::Eq => return a == b,
+            CompareOp::Ne => return a != b,
+            _ => None,
+        },
+        (Scalar::Time(a), Scalar::Time(b)) => Some(a.cmp(b)),
+        _ => None,
+    };
+    let Some(ordering) = ordering else {
+        return false;
+    };
+    match op {
+        CompareOp::Eq => ordering == Ordering::Equal,
+        CompareOp::Ne => ordering != Ordering::Equal,
+        CompareOp::Lt => ordering == Ordering::Less,
+        CompareOp::Le => ordering != Ordering::Greater,
+        CompareOp::Gt => ordering == Ordering::Greater,
+        CompareOp::Ge => ordering != Ordering::Less,
+        CompareOp::Contains => false,
+    }
+}
+
+// ---- Analyse ----
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Text(String),
+    Number(f64),
+    Now(Duration),
+    Op(CompareOp),
+    LParen,
+    RParen,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Ident(s) => format!("'{}'", s),
+            Token::Text(s) => format!("\"{}\"", s),
+            Token::Number(n) => n.to_string(),
+            Token::Now(_) => "now".into(),
+            Token::Op(_) => "opérateur".into(),
+            Token::LParen => "'('".into(),
+            Token::RParen => "')'".into(),
+        }
+    }
+}
+
+fn syntax_error(message: impl std::fmt::Display) -> ValidationError {
+    ValidationError(format!("expression: {}", message))
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, ValidationError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            '=' => {
+                tokens.push(Token::Op(CompareOp::Eq));
+                i += 1;
+            }
+            '!' if chars.get(i + 1) == Some(&'=') => {
+                tokens.push(Token::Op(CompareOp::Ne));
+                i += 2;
+            }
+            '<' | '>' => {
+                let or_equal = chars.get(i + 1) == Some(&'=');
+                tokens.push(Token::Op(match (c, or_equal) {
+                    ('<', false) => CompareOp::Lt,
+                    ('<', true) => CompareOp::Le,
+                    ('>', false) => CompareOp::Gt,
+                    _ => CompareOp::Ge,
+                }));
+                i += if or_equal { 2 } else { 1 };
+            }
+            '"' => {
+                let mut text = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(syntax_error("chaîne non terminée")),
+                        Some('"') => break,
+                        Some('\\') if matches!(chars.get(i + 1), Some('"') | Some('\\')) => {
+                            text.push(chars[i + 1]);
+                            i += 2;
+                        }
+                        Some(&ch) => {
+                            text.push(ch);
+                            i += 1;
+                        }
+                    }
+                }
+                tokens.push(Token::Text(text));
+                i += 1;
+            }
+            _ if c.is_ascii_digit()
+                || (c == '-' && chars.get(i + 1).is_some_and(char::is_ascii_digit)) =>
+            {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let literal: String = chars[start..i].iter().collect();
+                let number = literal
+                    .parse()
+                    .map_err(|_| syntax_error(format!("nombre invalide '{}'", literal)))?;
+                tokens.push(Token::Number(number));
+            }
+            _ if c.is_ascii_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == '.')
+                {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                if word == "now" {
+                    let (offset, len) = relative_offset(&chars[i..])?;
+                    tokens.push(Token::Now(offset));
+                    i += len;
+                } else {
+                    tokens.push(Token::Ident(word));
+                }
+            }
+            _ => {
+                return Err(syntax_error(format!(
+                    "caractère inattendu '{}' (position {})",
+                    c, i
+                )))
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Décalage suivant `now` (`-90d`, `+12h`) et nombre de caractères lus ; aucun décalage si absent.
+fn relative_offset(chars: &[char]) -> Result<(Duration, usize), ValidationError> {
+    let sign = match chars.first() {
+        Some('-') => -1,
+        Some('+') => 1,
+        _ => return Ok((Duration::zero(), 0)),
+    };
+    let digits = chars[1..].iter().take_while(|c| c.is_ascii_digit()).count();
+    let amount: i64 = chars[1..1 + digits]
+        .iter()
+        .collect::<String>()
+        .parse()
+        .map_err(|_| syntax_error("décalage attendu après now (ex. now-90d, now+12h)"))?;
+    let offset = match chars.get(1 + digits) {
+        Some('d') => Duration::try_days(sign * amount),
+        Some('h') => Duration::try_hours(sign * amount),
+        _ => None,
+    }
+    .ok_or_else(|| syntax_error("décalage attendu après now (ex. now-90d, now+12h)"))?;
+    Ok((offset, digits + 2))
+}
+
+fn value_field(name: &str, allowed: &[ValueField]) -> Option<ValueField> {
+    let field = match name {
+        "value" => ValueField::Value,
+        "from" => ValueField::From,
+        "updated_at" => ValueField::UpdatedAt,
+        "preferred_at" => ValueField::PreferredAt,
+        _ => return None,
+    };
+    allowed.contains(&field).then_some(field)
+}
+
+fn parse_path(path: &str) -> Result<FieldPath, ValidationError> {
+    let unknown = || syntax_error(format!("chemin inconnu '{}'", path));
+    let segments: Vec<&str> = path.split('.').collect();
+    let all = [
+        ValueField::Value,
+        ValueField::From,
+        ValueField::UpdatedAt,
+        ValueField::PreferredAt,
+    ];
+    let sub = |rest: &[&str], allowed: &[ValueField]| match rest {
+        [] => Some(ValueField::Value),
+        [field] => value_field(field, allowed),
+        _ => None,
+    };
+    let parsed = match segments.as_slice() {
+        ["updated_at"] => Some(FieldPath::UpdatedAt),
+        ["first_name", rest @ ..] => sub(rest, &all).map(FieldPath::FirstName),
+        ["last_name", rest @ ..] => sub(rest, &all).map(FieldPath::LastName),
+        ["mail", rest @ ..] => sub(rest, &all).map(FieldPath::Mail),
+        ["phone", rest @ ..] => sub(rest, &all).map(FieldPath::Phone),
+        ["attributes", name, rest @ ..] => {
+            validate_name(name).map_err(|_| unknown())?;
+            sub(rest, &all[..3]).map(|field| FieldPath::Attribute {
+                name: name.to_string(),
+                field,
+            })
+        }
+        ["consents", channel, rest @ ..] => {
+            let channel = ConsentChannel::ALL
+                .into_iter()
+                .find(|c| c.as_str() == *channel)
+                .ok_or_else(unknown)?;
+            let (purpose, rest) = match rest.split_first() {
+                Some((first, tail)) => {
+                    match [ConsentPurpose::Marketing, ConsentPurpose::Transactional]
+                        .into_iter()
+                        .find(|p| p.as_str() == *first)
+                    {
+                        Some(purpose) => (Some(purpose), tail),
+                        None => (None, rest),
+                    }
+                }
+                None => (None, rest),
+            };
+            let field = match rest {
+                [] | ["status"] => Some(ConsentField::Status),
+                ["legal_basis"] => Some(ConsentField::LegalBasis),
+                ["from"] => Some(ConsentField::From),
+                ["updated_at"] => Some(ConsentField::UpdatedAt),
+                _ => None,
+            };
+            field.map(|field| FieldPath::Consent {
+                channel,
+                purpose,
+                field,
+            })
+        }
+        _ => None,
+    };
+    parsed.ok_or_else(unknown)
+}
+
+/// Parseur descendant récursif sur la liste de jetons.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn keyword(&mut self, word: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(w)) if w == word) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expr(&mut self) -> Result<SegmentExpr, ValidationError> {
+        let mut left = self.and()?;
+        while self.keyword("or") {
+            left = SegmentExpr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<SegmentExpr, ValidationError> {
+        let mut left = self.unary()?;
+        while self.keyword("and") {
+            left = SegmentExpr::And(Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<SegmentExpr, ValidationError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(syntax_error(format!(
+                "imbrication limitée à {} niveaux",
+                MAX_DEPTH
+            )));
+        }
+        let expr = if self.keyword("not") {
+            SegmentExpr::Not(Box::new(self.unary()?))
+        } else if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let inner = self.expr()?;
+            if self.next() != Some(Token::RParen) {
+                return Err(syntax_error("')' attendue"));
+            }
+            inner
+        } else {
+            self.predicate()?
+        };
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    fn predicate(&mut self) -> Result<SegmentExpr, ValidationError> {
+        let path = match self.next() {
+            Some(Token::Ident(word)) => word,
+            Some(other) => {
+                return Err(syntax_error(format!(
+                    "chemin attendu, {} trouvé",
+                    other.describe()
+                )))
+            }
+            None => return Err(syntax_error("fin inattendue, chemin attendu")),
+        };
+        let field = parse_path(&path)?;
+        if self.keyword("exists") {
+            return Ok(SegmentExpr::Exists(field));
+        }
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            Some(Token::Ident(word)) if word == "contains" => CompareOp::Contains,
+            _ => {
+                return Err(syntax_error(format!(
+                    "opérateur attendu après '{}' (=, !=, <, <=, >, >=, contains, exists)",
+                    path
+                )))
+            }
+        };
+        let literal = match self.next() {
+            Some(Token::Text(s)) => Literal::Text(s),
+            Some(Token::Number(n)) => Literal::Number(n),
+            Some(Token::Now(offset)) => Literal::Now(offset),
+            Some(Token::Ident(word)) if word == "true" => Literal::Bool(true),
+            Some(Token::Ident(word)) if word == "false" => Literal::Bool(false),
+            _ => return Err(syntax_error(format!("valeur attendue après '{}'", path))),
+        };
+        let literal = check_literal(&path, &field, op, literal)?;
+        Ok(SegmentExpr::Compare(field, op, literal))
+    }
+}
+
+/// Vérifie le littéral selon le champ : date pour un champ date (chaîne convertie), chaîne pour `contains`.
+fn check_literal(
+    path: &str,
+    field: &FieldPath,
+    op: CompareOp,
+    literal: Literal,
+) -> Result<Literal, ValidationError> {
+    if field.is_time() {
+        return match literal {
+            Literal::Now(_) if op != CompareOp::Contains => Ok(literal),
+            Literal::Text(s) if op != CompareOp::Contains => parse_time(&s)
+                .map(Literal::Time)
+                .ok_or_else(|| syntax_error(format!("{}: date invalide '{}'", path, s))),
+            _ => Err(syntax_error(format!(
+                "{}: date attendue (\"AAAA-MM-JJ\", RFC 3339 ou now-90d)",
+                path
+            ))),
+        };
+    }
+    match (&literal, op) {
+        (Literal::Now(_), _) => Err(syntax_error(format!("{}: champ non daté", path))),
+        (Literal::Text(_), _) => Ok(literal),
+        (_, CompareOp::Contains) => Err(syntax_error(format!(
+            "{}: contains attend une chaîne",
+            path
+        ))),
+        _ => Ok(literal),
+    }
+}
+
+fn parse_time(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|t| t.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|t| t.and_utc())
+        })
+}
+
+/// Analyse une expression de segment (erreurs préfixées par `expression:`).
+pub fn parse_segment(input: &str) -> Result<SegmentExpr, ValidationError> {
+    if input.trim().is_empty() {
+        return Err(syntax_error("ne peut pas être vide"));
+    }
+    if input.len() > MAX_EXPRESSION_LEN {
+        return Err(syntax_error(format!(
+            "{} caractères au plus",
+            MAX_EXPRESSION_LEN
+        )));
+    }
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        pos: 0,
+        depth: 0,
+    };
+    let expr = parser.expr()?;
+    if let Some(extra) = parser.peek() {
+        return Err(syntax_error(format!("{} inattendu", extra.describe())));
+    }
+    Ok(expr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Consent, ConsentStatus, GuestAttributes, LegalBasis};
+
+    fn value<T>(value: T, updated_at: DateTime<Utc>) -> StructuredValue<T> {
+        StructuredValue {
+            value,
+            from: None,
+            updated_at,
+            preferred_at: None,
+            normalized: None,
+        }
+    }
+
+    fn guest(updated_at: DateTime<Utc>) -> Guest {
+        Guest {
+            id: uuid::Uuid::new_v4(),
+            first_name: value("Ana".into(), updated_at),
+            last_name: value("Lopez".into(), updated_at),
+            mail: Vec::new(),
+            phone: Vec::new(),
+            addresses: Vec::new(),
+            consents: Vec::new(),
+            attributes: GuestAttributes::new(),
+            version: 1,
+            erased_at: None,
+        }
+    }
+
+    fn consent(
+        channel: ConsentChannel,
+        purpose: ConsentPurpose,
+        status: ConsentStatus,
+    ) -> StructuredValue<Consent> {
+        let consent = Consent {
+            channel,
+            purpose,
+            status,
+            legal_basis: LegalBasis::Consent,
+            collected_at: None,
+        };
+        value(consent, Utc::now())
+    }
+
+    fn matches(expression: &str, guest: &Guest) -> bool {
+        parse_segment(expression)
+            .unwrap()
+            .matches(guest, Utc::now())
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            parse_segment("mail exists or phone exists and first_name exists").unwrap(),
+            parse_segment("mail exists or (phone exists and first_name exists)").unwrap()
+        );
+        assert_eq!(
+            parse_segment("not mail exists and phone exists").unwrap(),
+            parse_segment("(not mail exists) and phone exists").unwrap()
+        );
+        let g = guest(Utc::now());
+        assert!(matches(
+            "first_name exists or mail exists and phone exists",
+            &g
+        ));
+        assert!(!matches(
+            "(first_name exists or mail exists) and phone exists",
+            &g
+        ));
+    }
+
+    #[test]
+    fn not_and_parentheses() {
+        let mut g = guest(Utc::now());
+        g.mail.push(value("ana@example.com".into(), Utc::now()));
+        assert!(matches("not phone exists", &g));
+        assert!(!matches("not (mail exists or phone exists)", &g));
+        assert!(matches("not not mail exists", &g));
+        assert!(matches("((mail.value contains \"EXAMPLE\"))", &g));
+        assert!(parse_segment("(mail exists").is_err());
+        assert!(parse_segment("mail exists)").is_err());
+        assert!(parse_segment("not").is_err());
+    }
+
+    #[test]
+    fn relative_dates_resolve_at_evaluation() {
+        assert_eq!(
+            parse_segment("updated_at >= now-90d").unwrap(),
+            SegmentExpr::Compare(
+                FieldPath::UpdatedAt,
+                CompareOp::Ge,
+                Literal::Now(Duration::days(-90))
+            )
+        );
+        let now = Utc::now();
+        let expr = parse_segment("updated_at >= now-90d").unwrap();
+        assert!(expr.matches(&guest(now - Duration::days(30)), now));
+        assert!(!expr.matches(&guest(now - Duration::days(120)), now));
+        assert!(expr.matches(&guest(now - Duration::days(120)), now - Duration::days(60)));
+        assert!(parse_segment("first_name.value = now-90d").is_err());
+    }
+
+    #[test]
+    fn nesting_and_length_are_limited() {
+        let nested =
+            |depth: usize| format!("{}mail exists{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(parse_segment(&nested(MAX_DEPTH - 1)).is_ok());
+        assert!(parse_segment(&nested(MAX_DEPTH + 1)).is_err());
+        assert!(parse_segment(&format!("{}mail exists", "not ".repeat(MAX_DEPTH + 1))).is_err());
+
+        let long = vec!["mail exists"; MAX_EXPRESSION_LEN / 10].join(" or ");
+        assert!(long.len() > MAX_EXPRESSION_LEN);
+        assert!(parse_segment(&long).is_err());
+        assert!(parse_segment("   ").is_err());
+    }
+
+    #[test]
+    fn list_predicates_match_any_element() {
+        let mut g = guest(Utc::now());
+        g.mail.push(value("ana@example.com".into(), Utc::now()));
+        g.mail.push(value("ana@work.example".into(), Utc::now()));
+        g.consents.push(consent(
+            ConsentChannel::Email,
+            ConsentPurpose::Transactional,
+            ConsentStatus::OptedIn,
+        ));
+        g.consents.push(consent(
+            ConsentChannel::Email,
+            ConsentPurpose::Marketing,
+            ConsentStatus::OptedOut,
+        ));
+
+        assert!(matches("mail.value = \"ana@work.example\"", &g));
+        assert!(matches("mail.value != \"ana@work.example\"", &g));
+        assert!(!matches("mail.value = \"other@example.com\"", &g));
+        assert!(matches("consents.email = \"opted_out\"", &g));
+        assert!(matches("consents.email = \"opted_in\"", &g));
+        assert!(!matches("consents.email.marketing = \"opted_in\"", &g));
+        assert!(!matches("phone.value contains \"6\"", &g));
+    }
+}
@@ -0,0 +1,613 @@
+//! Règles de survie (survivorship) : quelle valeur garder quand une mise à jour remplace une valeur stockée,
+//! selon la confiance accordée à la provenance (`StructuredValue.from`).
+//!
+//! Ordre d'évaluation : valeur vérifiée, puis priorité de la source, puis (si activée) récence.
+//! À rang égal et sans récence, la mise à jour l'emporte (comportement d'un PUT sans règles).
+//! Un retrait de consentement (`opted_out`) s'applique quelle que soit la source : le guest doit pouvoir
+//! se désinscrire par n'importe quel canal. À l'inverse, un consentement n'est jamais retiré par omission
+//! et un opt-out stocké n'est levé que par un `opted_in` plus récent.
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::changes::keep_latest_preferred;
+use crate::domain::{
+    keep_opt_outs, Address, Consent, ConsentStatus, ContactKind, Guest, GuestAttributes,
+    GuestChanges, StructuredValue,
+};
+
+/// Règles de survie configurées.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SurvivorshipRules {
+    /// Sources par confiance décroissante (ex. `reception`, `pms`, `web`) ; une source absente
+    /// de la liste (ou une valeur sans `from`) a le rang le plus bas.
+    pub source_priority: Vec<String>,
+    /// Sources dont les valeurs sont vérifiées : seule une autre source vérifiée peut les remplacer.
+    pub verified_sources: Vec<String>,
+    /// À rang égal, la valeur la plus récente (`updated_at`) l'emporte.
+    pub prefer_recent: bool,
+}
+
+/// Règle ayant tranché entre la valeur stockée et la valeur entrante.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SurvivorshipRule {
+    /// Une seule des deux valeurs provient d'une source vérifiée.
+    VerifiedWins,
+    /// Les sources n'ont pas le même rang dans `source_priority`.
+    SourcePriority,
+    /// Rang égal, la plus récente l'emporte.
+    Recency,
+    /// Rang égal, la mise à jour l'emporte.
+    LastWrite,
+    /// Retrait de consentement, appliqué quelle que soit la source.
+    ConsentWithdrawal,
+    /// Opt-out stocké conservé : seul un `opted_in` plus récent le lève.
+    OptOutKept,
+}
+
+impl SurvivorshipRule {
+    /// Nom stable de la règle (valeur exposée par l'API).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SurvivorshipRule::VerifiedWins => "verified_wins",
+            SurvivorshipRule::SourcePriority => "source_priority",
+            SurvivorshipRule::Recency => "recency",
+            SurvivorshipRule::LastWrite => "last_write",
+            SurvivorshipRule::ConsentWithdrawal => "consent_withdrawal",
+            SurvivorshipRule::OptOutKept => "opt_out_kept",
+        }
+    }
+}
+
+/// Décision prise pour un champ, un élément de liste (`mail[a@b.com]`, `consents[email/marketing]`)
+/// ou un attribut personnalisé (`attributes.loyalty_tier`).
+/// Pour le retrait d'un élément absent de la liste entrante, `incoming_from` est la source la plus fiable
+/// de cette liste.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SurvivorshipDecision {
+    pub field: String,
+    /// `true` : la valeur entrante (ou le retrait) est appliquée ; `false` : la valeur stockée est conservée.
+    pub accepted: bool,
+    pub rule: SurvivorshipRule,
+    pub stored_from: Option<String>,
+    pub incoming_from: Option<String>,
+}
+
+impl SurvivorshipRules {
+    fn is_verified(&self, from: Option<&str>) -> bool {
+        from.is_some_and(|f| self.verified_sources.iter().any(|v| v == f))
+    }
+
+    /// Rang de confiance d'une source (0 = la plus fiable).
+    fn rank(&self, from: Option<&str>) -> usize {
+        from.and_then(|f| self.source_priority.iter().position(|s| s == f))
+            .unwrap_or(self.source_priority.len())
+    }
+
+    /// La valeur entrante (source `incoming_from`, datée `incoming_at`) remplace-t-elle la valeur stockée ?
+    pub fn decide<T>(
+        &self,
+        stored: &StructuredValue<T>,
+        incoming_from: Option<&str>,
+        incoming_at: DateTime<Utc>,
+    ) -> (bool, SurvivorshipRule) {
+        let stored_from = stored.from.as_deref();
+        match (
+            self.is_verified(stored_from),
+            self.is_verified(incoming_from),
+        ) {
+            (true, false) => return (false, SurvivorshipRule::VerifiedWins),
+            (false, true) => return (true, SurvivorshipRule::VerifiedWins),
+            _ => {}
+        }
+        match self.rank(incoming_from).cmp(&self.rank(stored_from)) {
+            std::cmp::Ordering::Less => (true, SurvivorshipRule::SourcePriority),
+            std::cmp::Ordering::Greater => (false, SurvivorshipRule::SourcePriority),
+            std::cmp::Ordering::Equal if self.prefer_recent => {
+                (incoming_at >= stored.updated_at, SurvivorshipRule::Recency)
+            }
+            std::cmp::Ordering::Equal => (true, SurvivorshipRule::LastWrite),
+        }
+    }
+}
+
+fn decision<T>(
+    field: String,
+    stored: &StructuredValue<T>,
+    incoming_from: Option<&str>,
+    (accepted, rule): (bool, SurvivorshipRule),
+) -> SurvivorshipDecision {
+    SurvivorshipDecision {
+        field,
+        accepted,
+        rule,
+        stored_from: stored.from.clone(),
+        incoming_from: incoming_from.map(str::to_string),
+    }
+}
+
+/// Remplace `stored` par `incoming` si les règles le permettent (aucune décision si les valeurs sont identiques).
+fn survive_value<T: Clone + PartialEq>(
+    stored: &mut StructuredValue<T>,
+    incoming: &StructuredValue<T>,
+    field: String,
+    rules: &SurvivorshipRules,
+    decisions: &mut Vec<SurvivorshipDecision>,
+) {
+    if stored == incoming {
+        return;
+    }
+    let from = incoming.from.as_deref();
+    let outcome = rules.decide(stored, from, incoming.updated_at);
+    decisions.push(decision(field, stored, from, outcome));
+    if outcome.0 {
+        *stored = incoming.clone();
+    }
+}
+
+/// Éléments entrants d'une liste : chacun affronte l'élément stocké de même valeur (les éléments sans concurrent
+/// sont ajoutés).
+fn survive_items<T: Clone + PartialEq>(
+    stored: &[StructuredValue<T>],
+    incoming: &[StructuredValue<T>],
+    field: &str,
+    same_value: impl Fn(&StructuredValue<T>, &StructuredValue<T>) -> bool,
+    label: impl Fn(&T) -> String,
+    rules: &SurvivorshipRules,
+    decisions: &mut Vec<SurvivorshipDecision>,
+) -> Vec<StructuredValue<T>> {
+    let mut result = Vec::with_capacity(incoming.len());
+    for item in incoming {
+        match stored.iter().find(|s| same_value(s, item)) {
+            Some(existing) => {
+                let mut kept = existing.clone();
+                let name = format!("{}[{}]", field, label(&item.value));
+                survive_value(&mut kept, item, name, rules, decisions);
+                result.push(kept);
+            }
+            None => result.push(item.clone()),
+        }
+    }
+    result
+}
+
+/// Remplace une liste (sémantique PUT) élément par élément (`survive_items`) ; un élément stocké absent de la liste
+/// entrante n'est retiré que si la meilleure source de la liste entrante l'emporte sur la sienne.
+fn survive_list<T: Clone + PartialEq>(
+    stored: &[StructuredValue<T>],
+    incoming: &[StructuredValue<T>],
+    field: &str,
+    same_value: impl Fn(&StructuredValue<T>, &StructuredValue<T>) -> bool,
+    label: impl Fn(&T) -> String,
+    rules: &SurvivorshipRules,
+    decisions: &mut Vec<SurvivorshipDecision>,
+) -> Vec<StructuredValue<T>> {
+    let mut result = survive_items(
+        stored,
+        incoming,
+        field,
+        &same_value,
+        &label,
+        rules,
+        decisions,
+    );
+
+    // Le retrait est attribué à la source la plus fiable de la liste entrante.
+    let remover = incoming
+        .iter()
+        .map(|i| i.from.as_deref())
+        .min_by_key(|from| (!rules.is_verified(*from), rules.rank(*from)))
+        .flatten();
+    for existing in stored {
+        if incoming.iter().any(|i| same_value(i, existing)) {
+            continue;
+        }
+        let outcome = rules.decide(existing, remover, Utc::now());
+        let name = format!("{}[{}]", field, label(&existing.value));
+        decisions.push(decision(name, existing, remover, outcome));
+        if !outcome.0 {
+            result.push(existing.clone());
+        }
+    }
+    result
+}
+
+/// Remplace les attributs (sémantique PUT) nom par nom, avec la même règle de retrait que les listes.
+fn survive_attributes(
+    stored: &GuestAttributes,
+    incoming: &GuestAttributes,
+    rules: &SurvivorshipRules,
+    decisions: &mut Vec<SurvivorshipDecision>,
+) -> GuestAttributes {
+    let mut result = GuestAttributes::new();
+    for (name, item) in incoming {
+        let field = format!("attributes.{}", name);
+        match stored.get(name) {
+            Some(existing) => {
+                let mut kept = existing.clone();
+                survive_value(&mut kept, item, field, rules, decisions);
+                result.insert(name.clone(), kept);
+            }
+            None => {
+                result.insert(name.clone(), item.clone());
+            }
+        }
+    }
+
+    let remover = incoming
+        .values()
+        .map(|i| i.from.as_deref())
+        .min_by_key(|from| (!rules.is_verified(*from), rules.rank(*from)))
+        .flatten();
+    for (name, existing) in stored {
+        if incoming.contains_key(name) {
+            continue;
+        }
+        let outcome = rules.decide(existing, remover, Utc::now());
+        decisions.push(decision(
+            format!("attributes.{}", name),
+            existing,
+            remover,
+            outcome,
+        ));
+        if !outcome.0 {
+            result.insert(name.clone(), existing.clone());
+        }
+    }
+    result
+}
+
+fn survive_contacts(
+    stored: &[StructuredValue<String>],
+    incoming: &[StructuredValue<String>],
+    kind: ContactKind,
+    rules: &SurvivorshipRules,
+    decisions: &mut Vec<SurvivorshipDecision>,
+) -> Vec<StructuredValue<String>> {
+    let mut list = survive_list(
+        stored,
+        incoming,
+        kind.as_str(),
+        |a, b| kind.same_contact(a, b),
+        |v| v.clone(),
+        rules,
+        decisions,
+    );
+    keep_latest_preferred(&mut list);
+    list
+}
+
+/// Consentements : règles de survie, sauf pour un retrait (`opted_out` entrant), toujours appliqué ;
+/// la décision correspondante est alors acceptée avec la règle `ConsentWithdrawal`. Un consentement stocké absent
+/// de la liste entrante est conservé (pas de retrait par omission) ; un opt-out remplacé sans `opted_in` plus récent
+/// est rétabli (décision refusée, règle `OptOutKept`).
+fn survive_consents(
+    stored: &[StructuredValue<Consent>],
+    incoming: &[StructuredValue<Consent>],
+    rules: &SurvivorshipRules,
+    decisions: &mut Vec<SurvivorshipDecision>,
+) -> Vec<StructuredValue<Consent>> {
+    let first = decisions.len();
+    let mut list = survive_items(
+        stored,
+        incoming,
+        "consents",
+        |a, b| a.value.same_scope(&b.value),
+        Consent::scope,
+        rules,
+        decisions,
+    );
+    list.extend(
+        stored
+            .iter()
+            .filter(|s| !incoming.iter().any(|i| i.value.same_scope(&s.value)))
+            .cloned(),
+    );
+    for withdrawal in incoming
+        .iter()
+        .filter(|c| c.value.status == ConsentStatus::OptedOut)
+    {
+        let Some(kept) = list
+            .iter_mut()
+            .find(|c| c.value.same_scope(&withdrawal.value))
+        else {
+            continue;
+        };
+        *kept = withdrawal.clone();
+        let field = format!("consents[{}]", withdrawal.value.scope());
+        if let Some(d) = decisions[first..].iter_mut().find(|d| d.field == field) {
+            d.accepted = true;
+            d.rule = SurvivorshipRule::ConsentWithdrawal;
+        }
+    }
+    for scope in keep_opt_outs(stored, &mut list) {
+        let field = format!("consents[{}]", scope);
+        if let Some(d) = decisions[first..].iter_mut().find(|d| d.field == field) {
+            d.accepted = false;
+            d.rule = SurvivorshipRule::OptOutKept;
+        }
+    }
+    list
+}
+
+/// Applique les modifications d'une mise à jour (champs fournis uniquement) selon les règles de survie ;
+/// retourne le guest résultant et les décisions prises pour chaque valeur en concurrence.
+pub fn apply_survivorship(
+    mut guest: Guest,
+    changes: &GuestChanges,
+    rules: &SurvivorshipRules,
+) -> (Guest, Vec<SurvivorshipDecision>) {
+    let mut decisions = Vec::new();
+    if let Some(first_name) = &changes.first_name {
+        survive_value(
+            &mut guest.first_name,
+            first_name,
+            "first_name".into(),
+            rules,
+            &mut decisions,
+        );
+    }
+    if let Some(last_name) = &changes.last_name {
+        survive_value(
+            &mut guest.last_name,
+            last_name,
+            "last_name".into(),
+            rules,
+            &mut decisions,
+        );
+    }
+    if let Some(mail) = &changes.mail {
+        guest.mail = survive_contacts(&guest.mail, mail, ContactKind::Mail, rules, &mut decisions);
+    }
+    if let Some(phone) = &changes.phone {
+        guest.phone = survive_contacts(
+            &guest.phone,
+            phone,
+            ContactKind::Phone,
+            rules,
+            &mut decisions,
+        );
+    }
+    if let Some(addresses) = &changes.addresses {
+        guest.addresses = survive_list(
+            &guest.addresses,
+            addresses,
+            "addresses",
+            |a, b| a.value.same_as(&b.value),
+            Address::label,
+            rules,
+            &mut decisions,
+        );
+        keep_latest_preferred(&mut guest.addresses);
+    }
+    if let Some(consents) = &changes.consents {
+        guest.consents = survive_consents(&guest.consents, consents, rules, &mut decisions);
+    }
+    if let Some(attributes) = &changes.attributes {
+        guest.attributes = survive_attributes(&guest.attributes, attributes, rules, &mut decisions);
+    }
+    (guest, decisions)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::domain::{ConsentChannel, ConsentPurpose, LegalBasis};
+
+    fn rules(priority: &[&str], verified: &[&str], prefer_recent: bool) -> SurvivorshipRules {
+        SurvivorshipRules {
+            source_priority: priority.iter().map(|s| s.to_string()).collect(),
+            verified_sources: verified.iter().map(|s| s.to_string()).collect(),
+            prefer_recent,
+        }
+    }
+
+    fn value(v: &str, from: &str, at: DateTime<Utc>) -> StructuredValue<String> {
+        StructuredValue {
+            from: Some(from.to_string()),
+            ..StructuredValue::with_updated_at(v.to_string(), at)
+        }
+    }
+
+    fn consent(status: ConsentStatus, from: &str, at: DateTime<Utc>) -> StructuredValue<Consent> {
+        let consent = Consent {
+            channel: ConsentChannel::Email,
+            purpose: ConsentPurpose::Marketing,
+            status,
+            legal_basis: LegalBasis::Consent,
+            collected_at: None,
+        };
+        StructuredValue {
+            from: Some(from.to_string()),
+            ..StructuredValue::with_updated_at(consent, at)
+        }
+    }
+
+    /// Applique un changement de prénom (source `from`, daté `at`) sur un prénom stocké par `reception`.
+    fn rename(
+        rules: &SurvivorshipRules,
+        from: &str,
+        at: DateTime<Utc>,
+    ) -> (String, Vec<SurvivorshipDecision>) {
+        let now = Utc::now();
+        let mut guest = Guest::new(uuid::Uuid::new_v4(), "Ana".into(), "Lopez".into());
+        guest.first_name = value("Ana", "reception", now);
+        let changes = GuestChanges {
+            first_name: Some(value("Anna", from, at)),
+            ..Default::default()
+        };
+        let (guest, decisions) = apply_survivorship(guest, &changes, rules);
+        (guest.first_name.value, decisions)
+    }
+
+    #[test]
+    fn higher_ranked_source_wins() {
+        let now = Utc::now();
+        let ranked = rules(&["reception", "pms", "web"], &[], false);
+        let (name, decisions) = rename(&ranked, "web", now + Duration::hours(1));
+        assert_eq!(name, "Ana");
+        assert_eq!(decisions[0].field, "first_name");
+        assert_eq!(
+            (decisions[0].accepted, decisions[0].rule),
+            (false, SurvivorshipRule::SourcePriority)
+        );
+
+        let reversed = rules(&["pms", "reception"], &[], false);
+        let (name, decisions) = rename(&reversed, "pms", now - Duration::hours(1));
+        assert_eq!(name, "Anna");
+        assert_eq!(
+            (decisions[0].accepted, decisions[0].rule),
+            (true, SurvivorshipRule::SourcePriority)
+        );
+
+        // Une source hors liste a le rang le plus bas.
+        let (name, _) = rename(&reversed, "kiosk", now);
+        assert_eq!(name, "Ana");
+    }
+
+    #[test]
+    fn verified_value_is_only_replaced_by_a_verified_source() {
+        let rules = rules(
+            &["web", "passport", "reception"],
+            &["reception", "passport"],
+            false,
+        );
+        let (name, decisions) = rename(&rules, "web", Utc::now());
+        assert_eq!(name, "Ana");
+        assert_eq!(
+            (decisions[0].accepted, decisions[0].rule),
+            (false, SurvivorshipRule::VerifiedWins)
+        );
+
+        let (name, decisions) = rename(&rules, "passport", Utc::now());
+        assert_eq!(name, "Anna");
+        assert_eq!(
+            (decisions[0].accepted, decisions[0].rule),
+            (true, SurvivorshipRule::SourcePriority)
+        );
+    }
+
+    #[test]
+    fn equal_rank_uses_recency_when_enabled_and_the_update_otherwise() {
+        let now = Utc::now();
+        let recent = rules(&[], &[], true);
+        let (name, decisions) = rename(&recent, "web", now - Duration::hours(1));
+        assert_eq!(name, "Ana");
+        assert_eq!(
+            (decisions[0].accepted, decisions[0].rule),
+            (false, SurvivorshipRule::Recency)
+        );
+        let (name, _) = rename(&recent, "web", now + Duration::hours(1));
+        assert_eq!(name, "Anna");
+
+        let (name, decisions) = rename(
+            &SurvivorshipRules::default(),
+            "web",
+            now - Duration::hours(1),
+        );
+        assert_eq!(name, "Anna");
+        assert_eq!(
+            (decisions[0].accepted, decisions[0].rule),
+            (true, SurvivorshipRule::LastWrite)
+        );
+    }
+
+    #[test]
+    fn omitted_element_is_removed_only_by_a_better_source() {
+        let rules = rules(&["reception", "web"], &[], false);
+        let now = Utc::now();
+        let mut guest = Guest::new(uuid::Uuid::new_v4(), "Ana".into(), "Lopez".into());
+        guest.mail = vec![
+            value("ana@hotel.com", "reception", now),
+            value("ana@web.com", "web", now),
+        ];
+
+        let changes = GuestChanges {
+            mail: Some(vec![value("new@web.com", "web", now)]),
+            ..Default::default()
+        };
+        let (kept, decisions) = apply_survivorship(guest.clone(), &changes, &rules);
+        let mails: Vec<&str> = kept.mail.iter().map(|m| m.value.as_str()).collect();
+        assert_eq!(mails, ["new@web.com", "ana@hotel.com"]);
+        assert!(decisions
+            .iter()
+            .any(|d| d.field == "mail[ana@hotel.com]" && !d.accepted));
+        assert!(decisions
+            .iter()
+            .any(|d| d.field == "mail[ana@web.com]" && d.accepted));
+
+        let changes = GuestChanges {
+            mail: Some(vec![value("new@hotel.com", "reception", now)]),
+            ..Default::default()
+        };
+        let (replaced, _) = apply_survivorship(guest, &changes, &rules);
+        let mails: Vec<&str> = replaced.mail.iter().map(|m| m.value.as_str()).collect();
+        assert_eq!(mails, ["new@hotel.com"]);
+    }
+
+    #[test]
+    fn consents_are_not_removed_by_omission() {
+        let rules = rules(&["reception", "web"], &[], false);
+        let now = Utc::now();
+        let mut guest = Guest::new(uuid::Uuid::new_v4(), "Ana".into(), "Lopez".into());
+        guest.consents = vec![consent(ConsentStatus::OptedOut, "web", now)];
+
+        let changes = GuestChanges {
+            consents: Some(Vec::new()),
+            ..Default::default()
+        };
+        let (kept, decisions) = apply_survivorship(guest.clone(), &changes, &rules);
+        assert_eq!(kept.consents, guest.consents);
+        assert!(decisions.is_empty());
+
+        // Une source mieux classée ne lève l'opt-out qu'avec un opted_in plus récent.
+        let stale = GuestChanges {
+            consents: Some(vec![consent(
+                ConsentStatus::OptedIn,
+                "reception",
+                now - Duration::hours(1),
+            )]),
+            ..Default::default()
+        };
+        let (kept, decisions) = apply_survivorship(guest.clone(), &stale, &rules);
+        assert_eq!(kept.consents, guest.consents);
+        assert_eq!(
+            (decisions[0].accepted, decisions[0].rule),
+            (false, SurvivorshipRule::OptOutKept)
+        );
+
+        let newer = GuestChanges {
+            consents: Some(vec![consent(
+                ConsentStatus::OptedIn,
+                "reception",
+                now + Duration::hours(1),
+            )]),
+            ..Default::default()
+        };
+        let (lifted, _) = apply_survivorship(guest, &newer, &rules);
+        assert_eq!(lifted.consents[0].value.status, ConsentStatus::OptedIn);
+    }
+
+    #[test]
+    fn withdrawal_applies_whatever_the_source() {
+        let rules = rules(&["reception", "web"], &[], false);
+        let now = Utc::now();
+        let mut guest = Guest::new(uuid::Uuid::new_v4(), "Ana".into(), "Lopez".into());
+        guest.consents = vec![consent(ConsentStatus::OptedIn, "reception", now)];
+        let changes = GuestChanges {
+            consents: Some(vec![consent(
+                ConsentStatus::OptedOut,
+                "web",
+                now - Duration::hours(1),
+            )]),
+            ..Default::default()
+        };
+        let (guest, decisions) = apply_survivorship(guest, &changes, &rules);
+        assert_eq!(guest.consents[0].value.status, ConsentStatus::OptedOut);
+        assert_eq!(
+            (decisions[0].accepted, decisions[0].rule),
+            (true, SurvivorshipRule::ConsentWithdrawal)
+        );
+    }
+}
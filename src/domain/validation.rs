@@ -21,7 +21,9 @@ pub fn validate_item_name(name: &str) -> Result<(), ValidationError> {
         return Err(ValidationError("name must be non-empty".into()));
     }
     if name.len() > 500 {
-        return Err(ValidationError("name must be at most 500 characters".into()));
+        return Err(ValidationError(
+            "name must be at most 500 characters".into(),
+        ));
     }
     Ok(())
 }
@@ -1,4 +1,5 @@
 use std::env;
+use std::str::FromStr;
 
 /// Charge le fichier `.env` depuis le répertoire courant ou un parent.
 /// On ignore l'erreur si le fichier est absent (comportement proche de godotenv.Load).
@@ -7,6 +8,31 @@ pub struct Variables {
     pub nats_url: String,
     /// Chemin ou URL SQLite (ex: `sqlite:./data.db` ou `./data.db`).
     pub database_url: String,
+    /// Délai (jours) pendant lequel un guest supprimé peut être restauré.
+    pub guest_restore_grace_days: i64,
+    /// Durée (jours) de conservation d'un guest supprimé avant purge définitive.
+    pub guest_retention_days: i64,
+    /// Intervalle (secondes) entre deux passages de la tâche de purge.
+    pub guest_purge_interval_secs: u64,
+    /// Région (ISO 3166-1 alpha-2, ex: `FR`) des téléphones saisis sans indicatif ; vide = indicatif obligatoire.
+    pub default_phone_region: String,
+    /// Intervalle (secondes) entre deux scans globaux des doublons.
+    pub guest_duplicate_scan_interval_secs: u64,
+    /// Sources par confiance décroissante, séparées par des virgules (ex: `reception,pms,web`).
+    pub survivorship_source_priority: Vec<String>,
+    /// Sources dont les valeurs sont vérifiées, séparées par des virgules (ex: `reception`).
+    pub survivorship_verified_sources: Vec<String>,
+    /// À rang de source égal, la valeur la plus récente l'emporte.
+    pub survivorship_prefer_recent: bool,
+    /// Durée (heures) pendant laquelle une `Idempotency-Key` rejoue la réponse d'origine.
+    pub idempotency_ttl_hours: i64,
+    /// Clés de chiffrement des données personnelles des guests : `id:base64` (32 octets) séparées par des virgules ;
+    /// vide = stockage en clair.
+    pub pii_keys: String,
+    /// Id de la clé utilisée pour chiffrer (vide = dernière de `pii_keys`).
+    pub pii_current_key_id: String,
+    /// Clé HMAC (base64, au moins 32 octets) de l'index aveugle des emails et téléphones.
+    pub pii_index_key: String,
 }
 
 fn var_default(key: &str, default: &str) -> String {
@@ -16,14 +42,55 @@ fn var_default(key: &str, default: &str) -> String {
     }
 }
 
+/// Liste séparée par des virgules (éléments vides ignorés).
+fn var_list(key: &str) -> Vec<String> {
+    var_default(key, "")
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn var_parse<T: FromStr>(key: &str, default: T) -> T {
+    match env::var(key) {
+        Ok(valeur) => valeur.parse().unwrap_or(default),
+        Err(_) => default,
+    }
+}
+
 pub fn parse() -> Variables {
     let _ = dotenvy::dotenv();
 
     let nats_url = var_default("ECH_NATS_URL", "nats://localhost:4222");
     let database_url = var_default("ECH_DATABASE_URL", "sqlite::memory:");
+    let guest_restore_grace_days = var_parse("ECH_GUEST_RESTORE_GRACE_DAYS", 30);
+    let guest_retention_days = var_parse("ECH_GUEST_RETENTION_DAYS", 90);
+    let guest_purge_interval_secs = var_parse("ECH_GUEST_PURGE_INTERVAL_SECS", 3600);
+    let default_phone_region = var_default("ECH_DEFAULT_PHONE_REGION", "FR");
+    let guest_duplicate_scan_interval_secs =
+        var_parse("ECH_GUEST_DUPLICATE_SCAN_INTERVAL_SECS", 86400);
+    let survivorship_source_priority = var_list("ECH_SURVIVORSHIP_SOURCE_PRIORITY");
+    let survivorship_verified_sources = var_list("ECH_SURVIVORSHIP_VERIFIED_SOURCES");
+    let survivorship_prefer_recent = var_parse("ECH_SURVIVORSHIP_PREFER_RECENT", false);
+    let idempotency_ttl_hours = var_parse("ECH_IDEMPOTENCY_TTL_HOURS", 24);
+    let pii_keys = var_default("ECH_PII_KEYS", "");
+    let pii_current_key_id = var_default("ECH_PII_KEY_ID", "");
+    let pii_index_key = var_default("ECH_PII_INDEX_KEY", "");
 
     Variables {
         nats_url,
         database_url,
+        guest_restore_grace_days,
+        guest_retention_days,
+        guest_purge_interval_secs,
+        default_phone_region,
+        guest_duplicate_scan_interval_secs,
+        survivorship_source_priority,
+        survivorship_verified_sources,
+        survivorship_prefer_recent,
+        idempotency_ttl_hours,
+        pii_keys,
+        pii_current_key_id,
+        pii_index_key,
     }
 }
@@ -0,0 +1,42 @@
+//! DTOs API pour le registre des attributs personnalisés.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Type de la valeur d'un attribut (`date` : chaîne `AAAA-MM-JJ`, `enum` : chaîne parmi `enum_values`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AttributeTypeDto {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    Date,
+    Enum,
+}
+
+/// Corps de requête pour créer ou remplacer la définition d'un attribut (le nom est dans le chemin).
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct AttributeDefinitionRequest {
+    pub attribute_type: AttributeTypeDto,
+    /// Valeurs permises, obligatoires pour un attribut `enum`.
+    #[serde(default)]
+    pub enum_values: Option<Vec<String>>,
+    /// Tout guest doit porter une valeur (`false` par défaut).
+    #[serde(default)]
+    pub required: Option<bool>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Réponse API : la définition d'un attribut.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AttributeDefinitionResponse {
+    pub name: String,
+    pub attribute_type: AttributeTypeDto,
+    pub enum_values: Vec<String>,
+    pub required: bool,
+    pub description: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
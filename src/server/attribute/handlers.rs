@@ -0,0 +1,118 @@
+//! Handlers HTTP pour le registre des attributs personnalisés.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::domain::ValidationError;
+use crate::server::attribute::dto::AttributeDefinitionRequest;
+use crate::server::attribute::mapper::{definition_to_response, request_to_definition};
+use crate::server::error::ApiError;
+use crate::server::state::AppState;
+
+/// GET /attributes — Lister les définitions d'attributs (par nom).
+#[utoipa::path(
+    get,
+    path = "/attributes",
+    responses(
+        (status = 200, description = "Définitions d'attributs", body = Vec<crate::server::attribute::dto::AttributeDefinitionResponse>)
+    ),
+    tag = "attributes"
+)]
+pub async fn list_attributes(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let definitions = state.store.attributes.list().await?;
+    let body: Vec<_> = definitions.iter().map(definition_to_response).collect();
+    Ok((StatusCode::OK, Json(body)))
+}
+
+/// GET /attributes/{name} — Récupérer la définition d'un attribut.
+#[utoipa::path(
+    get,
+    path = "/attributes/{name}",
+    params(("name" = String, Path, description = "Nom de l'attribut")),
+    responses(
+        (status = 200, description = "Définition trouvée", body = crate::server::attribute::dto::AttributeDefinitionResponse),
+        (status = 404, description = "Attribut non défini")
+    ),
+    tag = "attributes"
+)]
+pub async fn get_attribute(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let definition = state
+        .store
+        .attributes
+        .get(&name)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    Ok((StatusCode::OK, Json(definition_to_response(&definition))))
+}
+
+/// PUT /attributes/{name} — Créer ou remplacer la définition d'un attribut.
+/// Les valeurs déjà portées par les guests ne sont pas revalidées ; elles le sont à leur prochaine écriture.
+#[utoipa::path(
+    put,
+    path = "/attributes/{name}",
+    params(("name" = String, Path, description = "Nom de l'attribut (minuscules, chiffres et _)")),
+    request_body = crate::server::attribute::dto::AttributeDefinitionRequest,
+    responses(
+        (status = 201, description = "Attribut défini", body = crate::server::attribute::dto::AttributeDefinitionResponse),
+        (status = 200, description = "Définition remplacée", body = crate::server::attribute::dto::AttributeDefinitionResponse),
+        (status = 400, description = "Définition invalide (nom, valeurs permises)")
+    ),
+    tag = "attributes"
+)]
+pub async fn put_attribute(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<AttributeDefinitionRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let definition = request_to_definition(&name, &payload);
+    definition.validate()?;
+    tracing::info!(name = %name, "handler: saving attribute definition");
+    let created = state.store.attributes.save(&definition).await?;
+    let status = if created {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    Ok((status, Json(definition_to_response(&definition))))
+}
+
+/// DELETE /attributes/{name} — Supprimer la définition d'un attribut qu'aucun guest ne porte.
+#[utoipa::path(
+    delete,
+    path = "/attributes/{name}",
+    params(("name" = String, Path, description = "Nom de l'attribut")),
+    responses(
+        (status = 204, description = "Définition supprimée"),
+        (status = 400, description = "Attribut porté par au moins un guest"),
+        (status = 404, description = "Attribut non défini")
+    ),
+    tag = "attributes"
+)]
+pub async fn delete_attribute(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    if state.store.attributes.get(&name).await?.is_none() {
+        return Err(ApiError::NotFound);
+    }
+    let usage = state.store.attributes.usage(&name).await?;
+    if usage > 0 {
+        return Err(ValidationError(format!(
+            "attribut '{}' utilisé par {} guest(s)",
+            name, usage
+        ))
+        .into());
+    }
+    tracing::info!(name = %name, "handler: deleting attribute definition");
+    if !state.store.attributes.delete(&name).await? {
+        return Err(ApiError::NotFound);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
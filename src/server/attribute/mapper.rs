@@ -0,0 +1,54 @@
+//! Mappers domaine AttributeDefinition ↔ DTOs API.
+
+use chrono::Utc;
+
+use crate::domain::{AttributeDefinition, AttributeType};
+use crate::server::attribute::dto::{
+    AttributeDefinitionRequest, AttributeDefinitionResponse, AttributeTypeDto,
+};
+
+fn attribute_type_to_dto(attribute_type: AttributeType) -> AttributeTypeDto {
+    match attribute_type {
+        AttributeType::String => AttributeTypeDto::String,
+        AttributeType::Number => AttributeTypeDto::Number,
+        AttributeType::Integer => AttributeTypeDto::Integer,
+        AttributeType::Boolean => AttributeTypeDto::Boolean,
+        AttributeType::Date => AttributeTypeDto::Date,
+        AttributeType::Enum => AttributeTypeDto::Enum,
+    }
+}
+
+fn attribute_type_to_domain(attribute_type: AttributeTypeDto) -> AttributeType {
+    match attribute_type {
+        AttributeTypeDto::String => AttributeType::String,
+        AttributeTypeDto::Number => AttributeType::Number,
+        AttributeTypeDto::Integer => AttributeType::Integer,
+        AttributeTypeDto::Boolean => AttributeType::Boolean,
+        AttributeTypeDto::Date => AttributeType::Date,
+        AttributeTypeDto::Enum => AttributeType::Enum,
+    }
+}
+
+/// Requête API + nom (chemin) → définition domaine ; la validation est faite par l'appelant.
+pub fn request_to_definition(name: &str, req: &AttributeDefinitionRequest) -> AttributeDefinition {
+    AttributeDefinition {
+        name: name.to_string(),
+        attribute_type: attribute_type_to_domain(req.attribute_type),
+        enum_values: req.enum_values.clone().unwrap_or_default(),
+        required: req.required.unwrap_or(false),
+        description: req.description.clone(),
+        updated_at: Utc::now(),
+    }
+}
+
+/// Domaine → DTO réponse API.
+pub fn definition_to_response(definition: &AttributeDefinition) -> AttributeDefinitionResponse {
+    AttributeDefinitionResponse {
+        name: definition.name.clone(),
+        attribute_type: attribute_type_to_dto(definition.attribute_type),
+        enum_values: definition.enum_values.clone(),
+        required: definition.required,
+        description: definition.description.clone(),
+        updated_at: definition.updated_at,
+    }
+}
@@ -0,0 +1,8 @@
+//! Module serveur pour le registre des attributs personnalisés des guests : DTOs, mappers, handlers.
+
+pub mod dto;
+pub mod handlers;
+mod mapper;
+
+pub use dto::{AttributeDefinitionRequest, AttributeDefinitionResponse, AttributeTypeDto};
+pub use handlers::{delete_attribute, get_attribute, list_attributes, put_attribute};
@@ -0,0 +1,45 @@
+//! Curseurs de pagination opaques : le client renvoie tel quel le `next_cursor` reçu.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+use crate::domain::ValidationError;
+
+/// Encode une position (ex. uuid du dernier élément) en curseur opaque.
+pub fn encode_cursor(position: &str) -> String {
+    URL_SAFE_NO_PAD.encode(position)
+}
+
+/// Décode un curseur reçu ; retourne une ValidationError (400) s'il n'a pas été émis par l'API.
+pub fn decode_cursor(cursor: &str) -> Result<String, ValidationError> {
+    URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| ValidationError(format!("cursor invalide: '{}'", cursor)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trips() {
+        for position in ["0f8fad5b-d9cb-469f-a165-70867728950e", "42", ""] {
+            let cursor = encode_cursor(position);
+            assert!(cursor
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+            assert_eq!(decode_cursor(&cursor).unwrap(), position);
+        }
+    }
+
+    #[test]
+    fn cursor_not_issued_by_the_api_is_rejected() {
+        // Alphabet base64 standard, remplissage, caractères hors alphabet, octets non UTF-8.
+        let not_utf8 = URL_SAFE_NO_PAD.encode([0xff, 0xfe]);
+        for cursor in ["NDI=", "a+b/", "not a cursor!", "A", not_utf8.as_str()] {
+            let err = decode_cursor(cursor).unwrap_err();
+            assert_eq!(err.0, format!("cursor invalide: '{}'", cursor));
+        }
+    }
+}
@@ -10,12 +10,18 @@ use axum::{
 
 use crate::domain::{RepositoryError, ValidationError};
 
-/// Erreur côté API : validation (400), not found (404) ou repository (500).
+/// Erreur côté API : validation (400), not found (404), précondition If-Match (412),
+/// conflit de version ou requête concurrente (409), ressource disparue (410), requête incohérente (422)
+/// ou repository (500).
 #[derive(Debug)]
 pub enum ApiError {
     Validation(ValidationError),
     Repository(RepositoryError),
     NotFound,
+    PreconditionFailed(String),
+    Conflict(String),
+    Gone(String),
+    Unprocessable(String),
 }
 
 impl From<ValidationError> for ApiError {
@@ -34,12 +40,29 @@ impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         let (status, message) = match &self {
             ApiError::Validation(e) => (StatusCode::BAD_REQUEST, e.to_string()),
+            ApiError::Repository(
+                e @ (RepositoryError::VersionConflict { .. } | RepositoryError::Conflict(_)),
+            ) => (StatusCode::CONFLICT, e.to_string()),
             ApiError::Repository(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
             ApiError::NotFound => (StatusCode::NOT_FOUND, "not found".to_string()),
+            ApiError::PreconditionFailed(msg) => (StatusCode::PRECONDITION_FAILED, msg.clone()),
+            ApiError::Conflict(msg) => (StatusCode::CONFLICT, msg.clone()),
+            ApiError::Gone(msg) => (StatusCode::GONE, msg.clone()),
+            ApiError::Unprocessable(msg) => (StatusCode::UNPROCESSABLE_ENTITY, msg.clone()),
         };
 
         // Log pour Datadog : error pour 5xx, warn pour 4xx (client / not found / validation)
         match &self {
+            ApiError::Repository(
+                e @ (RepositoryError::VersionConflict { .. } | RepositoryError::Conflict(_)),
+            ) => {
+                tracing::warn!(
+                    status = %status.as_u16(),
+                    error = %e,
+                    "api_error: {}",
+                    message
+                );
+            }
             ApiError::Repository(e) => {
                 tracing::error!(
                     status = %status.as_u16(),
@@ -59,6 +82,12 @@ impl IntoResponse for ApiError {
             ApiError::NotFound => {
                 tracing::warn!(status = %status.as_u16(), "api_error: not found");
             }
+            ApiError::PreconditionFailed(_)
+            | ApiError::Conflict(_)
+            | ApiError::Gone(_)
+            | ApiError::Unprocessable(_) => {
+                tracing::warn!(status = %status.as_u16(), "api_error: {}", message);
+            }
         }
 
         (status, Json(serde_json::json!({ "error": message }))).into_response()
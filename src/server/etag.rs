@@ -0,0 +1,111 @@
+//! ETag / If-Match : concurrence optimiste sur les ressources versionnées.
+//! L'ETag d'une ressource est sa version entre guillemets (ex. `"3"`).
+
+use axum::http::{header, HeaderMap};
+
+use crate::domain::ValidationError;
+use crate::server::error::ApiError;
+
+/// ETag fort correspondant à une version.
+pub fn etag(version: i64) -> String {
+    format!("\"{}\"", version)
+}
+
+/// Condition `If-Match` envoyée par le client.
+enum IfMatch {
+    /// `If-Match: *` : la ressource doit simplement exister.
+    Any,
+    /// Liste d'ETags acceptés (comparaison forte : les ETags faibles `W/` ne correspondent jamais).
+    Tags(Vec<String>),
+}
+
+fn parse_if_match(headers: &HeaderMap) -> Result<Option<IfMatch>, ValidationError> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return Ok(None);
+    };
+    let value = value
+        .to_str()
+        .map_err(|_| ValidationError("If-Match: en-tête illisible".into()))?
+        .trim();
+    if value == "*" {
+        return Ok(Some(IfMatch::Any));
+    }
+    let tags = value
+        .split(',')
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    Ok(Some(IfMatch::Tags(tags)))
+}
+
+/// Vérifie l'en-tête `If-Match` (s'il est présent) contre la version courante ; 412 si elle ne correspond pas.
+pub fn check_if_match(headers: &HeaderMap, current_version: i64) -> Result<(), ApiError> {
+    match parse_if_match(headers)? {
+        None | Some(IfMatch::Any) => Ok(()),
+        Some(IfMatch::Tags(tags)) if tags.contains(&etag(current_version)) => Ok(()),
+        Some(IfMatch::Tags(_)) => Err(ApiError::PreconditionFailed(format!(
+            "If-Match ne correspond pas à la version courante {}",
+            etag(current_version)
+        ))),
+    }
+}
+
+/// Indique si la requête porte un en-tête `If-Match`.
+pub fn has_if_match(headers: &HeaderMap) -> bool {
+    headers.contains_key(header::IF_MATCH)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    fn if_match(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_MATCH, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn matching_etag_passes() {
+        assert!(check_if_match(&if_match("\"3\""), 3).is_ok());
+        assert!(check_if_match(&if_match("\"1\", \"3\""), 3).is_ok());
+    }
+
+    #[test]
+    fn stale_etag_is_a_failed_precondition() {
+        for value in ["\"2\"", "W/\"3\"", "3"] {
+            match check_if_match(&if_match(value), 3) {
+                Err(ApiError::PreconditionFailed(message)) => {
+                    assert_eq!(
+                        message,
+                        "If-Match ne correspond pas à la version courante \"3\""
+                    )
+                }
+                other => panic!("{value}: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn wildcard_or_missing_header_passes() {
+        assert!(check_if_match(&if_match("*"), 3).is_ok());
+        assert!(check_if_match(&HeaderMap::new(), 3).is_ok());
+        assert!(has_if_match(&if_match("*")));
+        assert!(!has_if_match(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn unreadable_header_is_a_validation_error() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_MATCH,
+            HeaderValue::from_bytes(b"\"\xe9\"").unwrap(),
+        );
+        assert!(matches!(
+            check_if_match(&headers, 3),
+            Err(ApiError::Validation(_))
+        ));
+    }
+}
@@ -3,9 +3,11 @@
 //! Structs concrets (non génériques) pour que utoipa génère un OpenAPI valide
 //! sans $ref vers des primitives (String, bool).
 
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
 // ---- Input (requêtes) ----
 
@@ -21,19 +23,99 @@ pub struct StructuredValueStringInput {
     pub preferred_at: Option<DateTime<Utc>>,
 }
 
-/// Champ structuré en entrée, valeur bool (opt_outs).
+/// Adresse postale en entrée.
 #[derive(Debug, Clone, Deserialize, ToSchema)]
-pub struct StructuredValueBoolInput {
-    pub value: bool,
+pub struct AddressInput {
+    /// Lignes d'adresse (1 à 4), dans l'ordre d'impression.
+    pub lines: Vec<String>,
+    /// Obligatoire et contrôlé pour les pays au format connu (ex. `75002` pour `FR`).
+    #[serde(default)]
+    pub postal_code: Option<String>,
+    pub city: String,
+    /// Région, état ou province.
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Code pays ISO 3166-1 alpha-2 (ex. `FR`).
+    pub country: String,
+}
+
+/// Adresse postale structurée en entrée (au plus une avec `preferred_at`).
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct StructuredValueAddressInput {
+    pub value: AddressInput,
     #[serde(default)]
     pub from: Option<String>,
     #[serde(default)]
     pub updated_at: Option<DateTime<Utc>>,
     #[serde(default)]
-    #[allow(dead_code)] // garde la même forme que String pour l'API
     pub preferred_at: Option<DateTime<Utc>>,
 }
 
+/// Valeur d'un attribut personnalisé en entrée (JSON conforme au type de sa définition).
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct StructuredValueJsonInput {
+    pub value: serde_json::Value,
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Canal de contact soumis à consentement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentChannelDto {
+    Email,
+    Sms,
+    Phone,
+    Postal,
+}
+
+/// Finalité de la communication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentPurposeDto {
+    Marketing,
+    Transactional,
+}
+
+/// Statut du consentement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentStatusDto {
+    OptedIn,
+    OptedOut,
+}
+
+/// Base légale du traitement (RGPD art. 6.1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LegalBasisDto {
+    Consent,
+    Contract,
+    LegalObligation,
+    LegitimateInterest,
+}
+
+/// Consentement en entrée pour un canal et une finalité (au plus un par couple).
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ConsentInput {
+    pub channel: ConsentChannelDto,
+    pub purpose: ConsentPurposeDto,
+    pub status: ConsentStatusDto,
+    /// `consent` par défaut.
+    #[serde(default)]
+    pub legal_basis: Option<LegalBasisDto>,
+    /// Date à laquelle le guest a exprimé son choix.
+    #[serde(default)]
+    pub collected_at: Option<DateTime<Utc>>,
+    /// Source du consentement (ex. `web-form`, `pms`).
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
 /// Corps de requête pour créer un guest.
 #[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct CreateGuestRequest {
@@ -43,8 +125,14 @@ pub struct CreateGuestRequest {
     pub mail: Option<Vec<StructuredValueStringInput>>,
     #[serde(default)]
     pub phone: Option<Vec<StructuredValueStringInput>>,
+    /// Adresses postales.
+    #[serde(default)]
+    pub addresses: Option<Vec<StructuredValueAddressInput>>,
+    #[serde(default)]
+    pub consents: Option<Vec<ConsentInput>>,
+    /// Attributs personnalisés, par nom (définis dans le registre `/attributes`).
     #[serde(default)]
-    pub opt_outs: Option<Vec<StructuredValueBoolInput>>,
+    pub attributes: Option<BTreeMap<String, StructuredValueJsonInput>>,
 }
 
 /// Corps de requête pour mettre à jour un guest (champs optionnels).
@@ -54,7 +142,185 @@ pub struct UpdateGuestRequest {
     pub last_name: Option<StructuredValueStringInput>,
     pub mail: Option<Vec<StructuredValueStringInput>>,
     pub phone: Option<Vec<StructuredValueStringInput>>,
-    pub opt_outs: Option<Vec<StructuredValueBoolInput>>,
+    /// Adresses postales (appariées par composants, sans casse ni espaces superflus).
+    #[serde(default)]
+    pub addresses: Option<Vec<StructuredValueAddressInput>>,
+    /// Consentements : les consentements non mentionnés sont conservés ; un opt-out n'est levé que par
+    /// un `opted_in` plus récent.
+    pub consents: Option<Vec<ConsentInput>>,
+    /// Attributs personnalisés : remplacent ceux du guest selon les règles de survie (PUT) ;
+    /// en synchronisation, les attributs non mentionnés sont conservés.
+    #[serde(default)]
+    pub attributes: Option<BTreeMap<String, StructuredValueJsonInput>>,
+}
+
+/// Liste de coordonnées ciblée par une opération PATCH.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GuestContactField {
+    Mail,
+    Phone,
+}
+
+/// Opération PATCH sur un élément de mail / phone / addresses / consents / attributes.
+/// Les valeurs sont comparées sur leur forme canonique (email sans casse, téléphone en E.164,
+/// adresse composant par composant sans casse ni espaces superflus).
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum GuestPatchOperation {
+    /// Ajoute une entrée ; si la valeur existe déjà dans la liste, l'entrée est remplacée.
+    Add {
+        field: GuestContactField,
+        item: StructuredValueStringInput,
+    },
+    /// Retire l'entrée ayant cette valeur.
+    Remove {
+        field: GuestContactField,
+        value: String,
+    },
+    /// Rend préférée l'entrée ayant cette valeur ; les autres entrées perdent `preferred_at`.
+    SetPreferred {
+        field: GuestContactField,
+        value: String,
+    },
+    /// Ajoute une adresse postale ; si la même adresse existe déjà, l'entrée est remplacée.
+    AddAddress { item: StructuredValueAddressInput },
+    /// Retire cette adresse postale.
+    RemoveAddress { value: AddressInput },
+    /// Rend préférée cette adresse postale ; les autres adresses perdent `preferred_at`.
+    SetPreferredAddress { value: AddressInput },
+    /// Enregistre un consentement ; remplace celui de même canal et finalité
+    /// (un opt-out n'est remplacé que par un `opted_in` plus récent).
+    SetConsent { item: ConsentInput },
+    /// Retire le consentement de ce canal et cette finalité (refusé pour un opt-out).
+    RemoveConsent {
+        channel: ConsentChannelDto,
+        purpose: ConsentPurposeDto,
+    },
+    /// Pose ou remplace la valeur d'un attribut personnalisé.
+    SetAttribute {
+        name: String,
+        item: StructuredValueJsonInput,
+    },
+    /// Retire un attribut personnalisé.
+    RemoveAttribute { name: String },
+}
+
+/// Corps de requête PATCH : opérations appliquées dans l'ordre, puis le guest résultant est validé.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct PatchGuestRequest {
+    pub operations: Vec<GuestPatchOperation>,
+}
+
+/// Paramètres de requête pour lister les guests (filtres combinés + pagination).
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListGuestsQuery {
+    /// Provenance (`from`) d'au moins un champ du guest.
+    pub from: Option<String>,
+    /// Guests modifiés à partir de cette date (incluse, RFC 3339).
+    pub updated_after: Option<DateTime<Utc>>,
+    /// Guests modifiés avant cette date (exclue, RFC 3339).
+    pub updated_before: Option<DateTime<Utc>>,
+    /// `true` : au moins un email ; `false` : aucun email.
+    pub has_mail: Option<bool>,
+    /// `true` : au moins un téléphone ; `false` : aucun téléphone.
+    pub has_phone: Option<bool>,
+    /// Curseur opaque `next_cursor` de la page précédente.
+    pub cursor: Option<String>,
+    /// Taille de page (1 à 200, 50 par défaut).
+    pub limit: Option<u32>,
+}
+
+/// Paramètres de recherche d'un guest par coordonnée (exactement un des deux).
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchGuestsQuery {
+    /// Adresse email (comparée sans tenir compte de la casse).
+    pub mail: Option<String>,
+    /// Numéro de téléphone (comparé en E.164, numéro sans indicatif lu dans la région par défaut).
+    pub phone: Option<String>,
+}
+
+/// Paramètres de pagination de l'historique d'un guest.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GuestHistoryQuery {
+    /// Curseur opaque `next_cursor` de la page précédente.
+    pub cursor: Option<String>,
+    /// Taille de page (1 à 200, 50 par défaut).
+    pub limit: Option<u32>,
+}
+
+/// Paramètres des doublons potentiels d'un guest.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GuestDuplicatesQuery {
+    /// Nombre maximum de candidats (1 à 200, 50 par défaut).
+    pub limit: Option<u32>,
+}
+
+/// Paramètres de la joignabilité d'un guest.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ContactabilityQuery {
+    /// Canal : `email`, `sms`, `phone` ou `postal`.
+    pub channel: String,
+    /// Finalité : `marketing` (par défaut) ou `transactional`.
+    pub purpose: Option<String>,
+}
+
+/// Paramètres de pagination des paires du dernier scan de doublons.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DuplicatePairsQuery {
+    /// Curseur opaque `next_cursor` de la page précédente.
+    pub cursor: Option<String>,
+    /// Taille de page (1 à 200, 50 par défaut).
+    pub limit: Option<u32>,
+}
+
+/// Paramètres d'un import en masse : format et, pour le CSV, correspondance champ → colonne.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportGuestsQuery {
+    /// `ndjson` ou `csv` (par défaut selon le Content-Type).
+    pub format: Option<String>,
+    /// Colonne CSV du prénom (défaut `first_name`).
+    pub first_name_column: Option<String>,
+    /// Colonne CSV du nom (défaut `last_name`).
+    pub last_name_column: Option<String>,
+    /// Colonne CSV des emails, valeurs multiples séparées par `|` (défaut `mail`).
+    pub mail_column: Option<String>,
+    /// Colonne CSV des téléphones, valeurs multiples séparées par `|` (défaut `phone`).
+    pub phone_column: Option<String>,
+    /// Provenance (`from`) appliquée aux valeurs lues depuis le CSV.
+    pub from: Option<String>,
+}
+
+/// Paramètres d'un export en masse.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportGuestsQuery {
+    /// `ndjson`, `csv` ou `json` (prioritaire sur l'en-tête Accept ; NDJSON par défaut).
+    pub format: Option<String>,
+    /// Uniquement les guests modifiés à partir de cette date (incluse, RFC 3339).
+    pub updated_since: Option<DateTime<Utc>>,
+}
+
+/// Corps de requête d'un effacement RGPD.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct EraseGuestRequest {
+    /// Auteur de la demande, reporté dans le certificat (ex. `dpo@example.com`).
+    pub requester: String,
+}
+
+/// Paramètres d'une demande d'accès RGPD.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SubjectAccessQuery {
+    /// `json` (par défaut) ou `zip` (JSON et résumé lisible `summary.txt`).
+    pub format: Option<String>,
 }
 
 // ---- Response ----
@@ -66,15 +332,63 @@ pub struct StructuredValueStringResponse {
     pub from: Option<String>,
     pub updated_at: DateTime<Utc>,
     pub preferred_at: Option<DateTime<Utc>>,
+    /// Forme canonique (domaine en minuscules / punycode pour un email), absente si inconnue.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalized: Option<String>,
 }
 
-/// Champ structuré en réponse, valeur bool.
+/// Téléphone en réponse.
 #[derive(Debug, Clone, Serialize, ToSchema)]
-pub struct StructuredValueBoolResponse {
-    pub value: bool,
+pub struct StructuredValuePhoneResponse {
+    pub value: String,
     pub from: Option<String>,
     pub updated_at: DateTime<Utc>,
     pub preferred_at: Option<DateTime<Utc>>,
+    /// Forme E.164 (ex. `+33612345678`), absente si le numéro n'a pas pu être analysé.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalized: Option<String>,
+    /// Type de ligne : `mobile`, `landline` ou `unknown` ; absent sans forme E.164.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_type: Option<String>,
+}
+
+/// Adresse postale en réponse.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AddressResponse {
+    pub lines: Vec<String>,
+    pub postal_code: Option<String>,
+    pub city: String,
+    pub region: Option<String>,
+    pub country: String,
+}
+
+/// Adresse postale structurée en réponse.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct StructuredValueAddressResponse {
+    pub value: AddressResponse,
+    pub from: Option<String>,
+    pub updated_at: DateTime<Utc>,
+    pub preferred_at: Option<DateTime<Utc>>,
+}
+
+/// Valeur d'un attribut personnalisé en réponse.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct StructuredValueJsonResponse {
+    pub value: serde_json::Value,
+    pub from: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Consentement en réponse : statut, base légale, source (`from`) et dates.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ConsentResponse {
+    pub channel: ConsentChannelDto,
+    pub purpose: ConsentPurposeDto,
+    pub status: ConsentStatusDto,
+    pub legal_basis: LegalBasisDto,
+    pub collected_at: Option<DateTime<Utc>>,
+    pub from: Option<String>,
+    pub updated_at: DateTime<Utc>,
 }
 
 /// Réponse API : un guest.
@@ -84,6 +398,200 @@ pub struct GuestResponse {
     pub first_name: StructuredValueStringResponse,
     pub last_name: StructuredValueStringResponse,
     pub mail: Vec<StructuredValueStringResponse>,
-    pub phone: Vec<StructuredValueStringResponse>,
-    pub opt_outs: Vec<StructuredValueBoolResponse>,
+    pub phone: Vec<StructuredValuePhoneResponse>,
+    pub addresses: Vec<StructuredValueAddressResponse>,
+    pub consents: Vec<ConsentResponse>,
+    /// Attributs personnalisés, par nom.
+    pub attributes: BTreeMap<String, StructuredValueJsonResponse>,
+    /// Version courante (également renvoyée dans l'en-tête `ETag`).
+    pub version: i64,
+}
+
+/// Décision de survie pour un champ ou un élément de liste en concurrence avec la valeur stockée.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SurvivorshipDecisionResponse {
+    /// Champ ou élément (ex. `first_name`, `mail[a@b.com]`, `consents[email/marketing]`, `attributes.<nom>`).
+    pub field: String,
+    /// `true` : valeur entrante (ou retrait) appliquée ; `false` : valeur stockée conservée.
+    pub accepted: bool,
+    /// `verified_wins`, `source_priority`, `recency`, `last_write`, `consent_withdrawal` (retrait de
+    /// consentement, appliqué quelle que soit la source) ou `opt_out_kept` (opt-out conservé, seul un
+    /// `opted_in` plus récent le lève).
+    pub rule: String,
+    pub stored_from: Option<String>,
+    pub incoming_from: Option<String>,
+}
+
+/// Réponse API d'un PUT : le guest et les décisions des règles de survie.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GuestUpdateResponse {
+    #[serde(flatten)]
+    pub guest: GuestResponse,
+    pub survivorship: Vec<SurvivorshipDecisionResponse>,
+}
+
+/// Réponse API : une page de guests.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GuestListResponse {
+    pub items: Vec<GuestResponse>,
+    /// Curseur à passer en `cursor` pour la page suivante ; absent sur la dernière page.
+    pub next_cursor: Option<String>,
+}
+
+/// Réponse API d'une synchronisation "last writer wins".
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GuestSyncResponse {
+    pub guest: GuestResponse,
+    /// Champs appliqués (plus récents que la valeur stockée), ex. `first_name`, `mail[a@b.com]`.
+    pub accepted: Vec<String>,
+    /// Champs ignorés car leur `updated_at` n'est pas plus récent que la valeur stockée.
+    pub rejected: Vec<String>,
+}
+
+/// Réponse API : le guest peut-il être contacté sur ce canal, et à quelle adresse.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ContactabilityResponse {
+    pub channel: String,
+    pub purpose: String,
+    pub contactable: bool,
+    /// Adresse à utiliser (forme canonique : email au domaine normalisé, téléphone E.164, adresse postale sur une ligne).
+    pub address: Option<String>,
+    /// Raison du refus : `guest_deleted`, `guest_erased`, `opted_out`, `no_address` ou `no_mobile_phone`.
+    pub reason: Option<String>,
+}
+
+/// Réponse API d'un GET sur l'uuid d'un guest fusionné dans un autre (redirection).
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GuestRedirectResponse {
+    /// Uuid du guest issu de la fusion.
+    pub merged_into: String,
+}
+
+/// Raison d'un doublon potentiel : champ comparé, valeur du candidat et similarité (1.0 = égalité).
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DuplicateReasonResponse {
+    /// `mail`, `phone`, `first_name` ou `last_name`.
+    pub field: String,
+    /// Valeur du candidat (normalisée pour mail / phone).
+    pub value: String,
+    pub similarity: f64,
+}
+
+/// Réponse API : un guest candidat au doublon, classé par score (0 à 1).
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GuestDuplicateResponse {
+    pub guest: GuestResponse,
+    pub score: f64,
+    pub reasons: Vec<DuplicateReasonResponse>,
+}
+
+/// Réponse API : une paire de doublons potentiels trouvée par le scan global.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DuplicatePairResponse {
+    pub guest_id: String,
+    pub candidate_id: String,
+    pub score: f64,
+    pub reasons: Vec<DuplicateReasonResponse>,
+}
+
+/// Réponse API : page de paires du dernier scan de doublons.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DuplicatePairListResponse {
+    pub items: Vec<DuplicatePairResponse>,
+    /// Curseur à passer en `cursor` pour la page suivante (absent sur la dernière page).
+    pub next_cursor: Option<String>,
+}
+
+/// Résultat d'une ligne importée : id créé ou erreur (lecture, validation ou stockage).
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportRowResponse {
+    /// Numéro de ligne dans le fichier (à partir de 1, en-tête CSV compris).
+    pub line: usize,
+    pub id: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Rapport d'un import en masse, ligne par ligne.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportGuestsResponse {
+    pub created: usize,
+    pub failed: usize,
+    pub rows: Vec<ImportRowResponse>,
+}
+
+/// Réponse API : une entrée d'historique (un champ ou élément de liste modifié).
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GuestHistoryEntryResponse {
+    pub seq: i64,
+    /// `create`, `update`, `delete`, `restore`, `merge` ou `erase`.
+    pub operation: String,
+    /// Champ modifié : `first_name`, `last_name`, `mail`, `phone`, `addresses`, `consents` ou `attributes.<nom>`.
+    pub field: String,
+    /// Valeur structurée avant modification (absente pour un ajout).
+    pub old_value: Option<serde_json::Value>,
+    /// Valeur structurée après modification (absente pour un retrait).
+    pub new_value: Option<serde_json::Value>,
+    /// Provenance de la nouvelle valeur.
+    pub from: Option<String>,
+    pub changed_at: DateTime<Utc>,
+    pub trace_id: Option<String>,
+}
+
+/// Réponse API : une page d'historique, du plus récent au plus ancien.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GuestHistoryResponse {
+    pub items: Vec<GuestHistoryEntryResponse>,
+    /// Curseur à passer en `cursor` pour la page suivante ; absent sur la dernière page.
+    pub next_cursor: Option<String>,
+}
+
+/// Réponse API : un événement opt-out conservé (publié ou non sur NATS).
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct OptOutEventResponse {
+    pub channels: Vec<ConsentChannelDto>,
+    /// `deleted` ou `consent`.
+    pub reason: String,
+    pub occurred_at: DateTime<Utc>,
+    pub trace_id: Option<String>,
+    /// `false` si la publication NATS a échoué.
+    pub published: bool,
+}
+
+/// Réponse API : certificat d'effacement RGPD.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ErasureCertificateResponse {
+    pub guest_id: String,
+    pub erased_at: DateTime<Utc>,
+    pub requester: String,
+    pub trace_id: Option<String>,
+    /// Champs anonymisés (`first_name`, `last_name`, `mail`, `phone`, `addresses`).
+    pub fields: Vec<String>,
+}
+
+/// Historique d'un guest fusionné dans le guest demandé.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct MergedGuestAccessResponse {
+    pub guest_id: String,
+    pub history: Vec<GuestHistoryEntryResponse>,
+    pub opt_out_events: Vec<OptOutEventResponse>,
+}
+
+/// Réponse API : données détenues sur un guest (demande d'accès RGPD, art. 15).
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SubjectAccessResponse {
+    pub generated_at: DateTime<Utc>,
+    /// Guest avec la provenance (`from`, `updated_at`) de chaque valeur.
+    pub guest: GuestResponse,
+    /// Date de suppression si le guest est supprimé (en attente de purge).
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Historique complet, du plus récent au plus ancien.
+    pub history: Vec<GuestHistoryEntryResponse>,
+    /// Événements opt-out publiés, du plus ancien au plus récent.
+    pub opt_out_events: Vec<OptOutEventResponse>,
+    /// Guests fusionnés dans celui-ci.
+    pub merged_from: Vec<MergedGuestAccessResponse>,
+    /// Paires du dernier scan de doublons impliquant le guest.
+    pub duplicate_pairs: Vec<DuplicatePairResponse>,
+    /// Certificats d'effacement (données anonymisées).
+    pub erasures: Vec<ErasureCertificateResponse>,
 }
@@ -0,0 +1,79 @@
+//! Scan global des doublons : parcourt tous les guests, classe leurs doublons potentiels
+//! et enregistre les paires (tâche de fond périodique ou déclenchée par l'API).
+
+use std::time::Duration;
+
+use tracing::{error, info};
+
+use crate::domain::{rank_duplicates, DuplicatePair, GuestListQuery, RepositoryError};
+use crate::store::Store;
+
+/// Taille des pages de guests lues pendant le scan.
+const SCAN_PAGE_SIZE: u32 = 200;
+
+/// Parcourt tous les guests et remplace les paires du dernier scan. Retourne le nombre de paires trouvées.
+pub async fn scan_duplicates(store: &Store) -> Result<usize, RepositoryError> {
+    let mut pairs: Vec<DuplicatePair> = Vec::new();
+    let mut after = None;
+    loop {
+        let page = store
+            .guests
+            .list(GuestListQuery {
+                after,
+                limit: SCAN_PAGE_SIZE,
+                ..Default::default()
+            })
+            .await?;
+        for guest in &page.guests {
+            let others = store.guests.duplicate_candidates(guest).await?;
+            // Chaque paire n'est retenue qu'une fois, depuis le guest de plus petit uuid.
+            pairs.extend(
+                rank_duplicates(guest, &others)
+                    .into_iter()
+                    .filter(|c| guest.id < c.guest.id)
+                    .map(|c| DuplicatePair {
+                        guest_id: guest.id,
+                        candidate_id: c.guest.id,
+                        score: c.score,
+                        reasons: c.reasons,
+                    }),
+            );
+        }
+        match page.next {
+            Some(next) => after = Some(next),
+            None => break,
+        }
+    }
+    pairs.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.guest_id.cmp(&b.guest_id))
+            .then_with(|| a.candidate_id.cmp(&b.candidate_id))
+    });
+    store.guests.replace_duplicate_pairs(&pairs).await?;
+    Ok(pairs.len())
+}
+
+/// Lance un scan en tâche Tokio (résultat logué).
+pub fn spawn_duplicate_scan(store: Store) {
+    tokio::spawn(async move {
+        match scan_duplicates(&store).await {
+            Ok(pairs) => info!(pairs, "duplicates: scan completed"),
+            Err(e) => error!("duplicates scan: {e}"),
+        }
+    });
+}
+
+/// Démarre le scan périodique des doublons en tâche Tokio (un passage par intervalle, le premier immédiatement).
+pub fn spawn_guest_duplicate_scan_task(store: Store, every: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(every);
+        loop {
+            ticker.tick().await;
+            match scan_duplicates(&store).await {
+                Ok(pairs) => info!(pairs, "duplicates: scan completed"),
+                Err(e) => error!("duplicates scan: {e}"),
+            }
+        }
+    });
+}
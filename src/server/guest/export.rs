@@ -0,0 +1,126 @@
+//! Export en masse : sérialisation au fil de l'eau des guests lus par `GuestRepository::export`
+//! (NDJSON, CSV aplati ou tableau JSON), sans charger toute la table en mémoire.
+
+use axum::body::Body;
+use bytes::Bytes;
+use futures_util::stream::{self, BoxStream, StreamExt};
+
+use crate::domain::{Guest, RepositoryError, ValidationError};
+use crate::server::guest::dto::ExportGuestsQuery;
+use crate::server::guest::mapper::guest_to_response;
+
+/// Nombre de guests lus par requête SQLite pendant l'export.
+pub const EXPORT_PAGE_SIZE: u32 = 500;
+
+/// En-tête CSV : une ligne par guest, email et téléphone préférés aplatis.
+const CSV_HEADER: &str = "id,first_name,last_name,mail,phone,updated_at,version\n";
+
+/// Format de l'export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Ndjson,
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    /// Content-Type de la réponse.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Ndjson => "application/x-ndjson",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Json => "application/json",
+        }
+    }
+}
+
+/// Format de l'export : `?format=` prioritaire, sinon l'en-tête Accept (NDJSON par défaut).
+pub fn export_format(
+    query: &ExportGuestsQuery,
+    accept: Option<&str>,
+) -> Result<ExportFormat, ValidationError> {
+    if let Some(format) = query.format.as_deref() {
+        return match format {
+            "ndjson" => Ok(ExportFormat::Ndjson),
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            other => Err(ValidationError(format!(
+                "format invalide: '{}' (ndjson, csv ou json)",
+                other
+            ))),
+        };
+    }
+    Ok(match accept {
+        Some(a) if a.contains("text/csv") => ExportFormat::Csv,
+        Some(a) if a.contains("ndjson") => ExportFormat::Ndjson,
+        Some(a) if a.contains("application/json") => ExportFormat::Json,
+        _ => ExportFormat::Ndjson,
+    })
+}
+
+fn json_line(guest: &Guest) -> Result<Vec<u8>, RepositoryError> {
+    serde_json::to_vec(&guest_to_response(guest)).map_err(|e| RepositoryError::Other(e.to_string()))
+}
+
+/// Ligne CSV d'un guest (échappement géré par le writer csv).
+fn csv_line(guest: &Guest) -> Result<Vec<u8>, RepositoryError> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    writer
+        .write_record([
+            guest.id.to_string(),
+            guest.first_name.value.clone(),
+            guest.last_name.value.clone(),
+            guest
+                .preferred_mail()
+                .map(|m| m.value.clone())
+                .unwrap_or_default(),
+            guest
+                .preferred_phone()
+                .map(|p| p.value.clone())
+                .unwrap_or_default(),
+            guest.last_updated_at().to_rfc3339(),
+            guest.version.to_string(),
+        ])
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+    writer
+        .into_inner()
+        .map_err(|e| RepositoryError::Other(e.to_string()))
+}
+
+/// Corps de réponse streamé (transfert chunked) : un morceau par guest, encadré selon le format.
+pub fn export_body(
+    format: ExportFormat,
+    guests: BoxStream<'static, Result<Guest, RepositoryError>>,
+) -> Body {
+    let (head, tail): (&'static str, &'static str) = match format {
+        ExportFormat::Ndjson => ("", ""),
+        ExportFormat::Csv => (CSV_HEADER, ""),
+        ExportFormat::Json => ("[", "]"),
+    };
+    let rows = guests.enumerate().map(move |(i, guest)| {
+        let guest = guest?;
+        let mut chunk = match format {
+            ExportFormat::Ndjson => json_line(&guest)?,
+            ExportFormat::Csv => csv_line(&guest)?,
+            ExportFormat::Json if i > 0 => [b",".as_slice(), &json_line(&guest)?].concat(),
+            ExportFormat::Json => json_line(&guest)?,
+        };
+        if format == ExportFormat::Ndjson {
+            chunk.push(b'\n');
+        }
+        Ok(Bytes::from(chunk))
+    });
+    let chunks = stream::once(async move { Ok(Bytes::from_static(head.as_bytes())) })
+        .chain(rows)
+        .chain(stream::once(async move {
+            Ok(Bytes::from_static(tail.as_bytes()))
+        }))
+        .inspect(|chunk: &Result<Bytes, RepositoryError>| {
+            if let Err(e) = chunk {
+                tracing::error!("export: interrupted: {e}");
+            }
+        });
+    Body::from_stream(chunks)
+}
@@ -1,54 +1,191 @@
 //! Handlers HTTP pour les guests.
 
-use async_nats::jetstream::context::traits::Publisher;
 use axum::{
-    extract::{Extension, Path, State},
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{Extension, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 
 use tower_http::request_id::RequestId;
 
+use crate::domain::{
+    contactability, rank_duplicates, sync_last_writer_wins, validate_attributes, ConsentChannel,
+    Guest, GuestListQuery, OptOutEvent,
+};
 use crate::server::error::ApiError;
-use crate::server::guest::dto::{CreateGuestRequest, UpdateGuestRequest};
+use crate::server::etag::{check_if_match, etag, has_if_match};
+use crate::server::guest::dto::{
+    ContactabilityQuery, CreateGuestRequest, DuplicatePairsQuery, EraseGuestRequest,
+    ExportGuestsQuery, GuestDuplicatesQuery, GuestHistoryQuery, GuestRedirectResponse,
+    ImportGuestsQuery, ListGuestsQuery, PatchGuestRequest, SearchGuestsQuery, SubjectAccessQuery,
+    UpdateGuestRequest,
+};
+use crate::server::guest::duplicates::spawn_duplicate_scan;
+use crate::server::guest::export::{export_body, export_format, EXPORT_PAGE_SIZE};
+use crate::server::guest::import::{
+    import_format, import_rows, read_csv, read_ndjson, ImportFormat,
+};
 use crate::server::guest::mapper::{
-    apply_update_request, create_request_to_guest, guest_to_response,
+    apply_patch_request, apply_update_request, contactability_to_response, create_request_to_guest,
+    duplicate_candidate_to_response, duplicate_pair_page_to_response, erasure_to_response,
+    guest_page_to_response, guest_to_response, history_page_to_response, sync_result_to_response,
+    update_request_to_changes, update_result_to_response,
+};
+use crate::server::guest::stream::publish_opt_out;
+use crate::server::guest::subject_access::{
+    collect_subject_access, subject_access_format, subject_access_zip, SubjectAccessFormat,
 };
 use crate::server::guest::validation::{
-    parse_guest_id, validate_create_request, validate_update_request,
+    create_request_from_update, parse_guest_id, validate_contactability_query,
+    validate_create_request, validate_duplicate_pairs_query, validate_duplicates_query,
+    validate_erase_request, validate_guest_lists, validate_history_query, validate_list_query,
+    validate_merge_ids, validate_search_query, validate_update_request,
 };
 use crate::server::state::AppState;
+use crate::server::trace::{change_context, trace_id};
+
+/// Publie un événement opt-out et le conserve (avec le résultat de la publication) pour les demandes d'accès ;
+/// un échec d'enregistrement est journalisé sans faire échouer la requête.
+async fn notify_opt_out(state: &AppState, event: OptOutEvent, request_id: &RequestId) {
+    let trace_id = trace_id(request_id);
+    let published = publish_opt_out(&state.nats, &event, &trace_id).await;
+    if let Err(e) = state
+        .store
+        .guests
+        .record_opt_out_event(&event, Some(&trace_id), published)
+        .await
+    {
+        tracing::warn!(guest_id = %event.guest_id, "record opt-out: {}", e);
+    }
+}
+
+/// Un guest effacé (RGPD) ne reçoit plus de modification : 410.
+fn reject_erased(guest: &Guest) -> Result<(), ApiError> {
+    if guest.is_erased() {
+        return Err(ApiError::Gone(format!(
+            "guest {} effacé : modification impossible",
+            guest.id
+        )));
+    }
+    Ok(())
+}
+
+/// Publie un événement opt-out si l'écriture a fait passer des canaux en opt-out.
+async fn publish_consent_opt_outs(
+    state: &AppState,
+    before: Option<&Guest>,
+    saved: &Guest,
+    request_id: &RequestId,
+) {
+    let channels = saved.new_opt_outs(before);
+    if channels.is_empty() {
+        return;
+    }
+    let event = OptOutEvent {
+        guest_id: saved.id,
+        channels,
+        reason: "consent".into(),
+        occurred_at: chrono::Utc::now(),
+    };
+    notify_opt_out(state, event, request_id).await;
+}
 
 /// POST /guests — Créer un guest.
 #[utoipa::path(
     post,
     path = "/guests",
+    params(("Idempotency-Key" = Option<String>, Header,
+        description = "Clé choisie par le client : une requête retentée avec la même clé rejoue la réponse d'origine")),
     request_body = crate::server::guest::dto::CreateGuestRequest,
     responses(
-        (status = 201, description = "Guest créé", body = crate::server::guest::dto::GuestResponse),
-        (status = 400, description = "Requête invalide (ex: au plus un email/téléphone préféré, format email)")
+        (status = 201, description = "Guest créé (ou réponse d'origine rejouée, en-tête `Idempotent-Replayed`)",
+            body = crate::server::guest::dto::GuestResponse,
+            headers(("ETag" = String, description = "Version du guest"))),
+        (status = 400, description = "Requête invalide (ex: au plus un email/téléphone préféré, format email, numéro de téléphone, attribut non défini)"),
+        (status = 409, description = "Requête d'origine portant la même Idempotency-Key encore en cours"),
+        (status = 410, description = "Idempotency-Key d'un guest depuis effacé ou supprimé (réponse non rejouée)"),
+        (status = 422, description = "Idempotency-Key déjà utilisée avec un autre corps")
     ),
     tag = "guests"
 )]
 pub async fn create_guest(
     State(state): State<AppState>,
+    Extension(request_id): Extension<RequestId>,
     Json(payload): Json<CreateGuestRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
-    validate_create_request(&payload)?;
-    let guest = create_request_to_guest(&payload);
+    let region = state.settings.default_phone_region;
+    validate_create_request(&payload, region)?;
+    let mut guest = create_request_to_guest(&payload);
+    validate_attributes(&guest.attributes, &state.store.attributes.list().await?)?;
+    guest.canonicalize_contacts(region);
     tracing::info!(guest_id = %guest.id, "handler: creating guest");
-    let created = state.store.guests.create(guest).await?;
-    Ok((StatusCode::CREATED, Json(guest_to_response(&created))))
+    let created = state
+        .store
+        .guests
+        .create(guest, &change_context(&request_id))
+        .await?;
+    publish_consent_opt_outs(&state, None, &created, &request_id).await;
+    Ok((
+        StatusCode::CREATED,
+        [(header::ETAG, etag(created.version))],
+        Json(guest_to_response(&created)),
+    ))
+}
+
+/// GET /guests — Lister les guests (filtres + pagination par curseur).
+#[utoipa::path(
+    get,
+    path = "/guests",
+    params(crate::server::guest::dto::ListGuestsQuery),
+    responses(
+        (status = 200, description = "Page de guests", body = crate::server::guest::dto::GuestListResponse),
+        (status = 400, description = "Paramètres invalides (limit, curseur, plage de dates)")
+    ),
+    tag = "guests"
+)]
+pub async fn list_guests(
+    State(state): State<AppState>,
+    Query(query): Query<ListGuestsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let query = validate_list_query(&query)?;
+    let page = state.store.guests.list(query).await?;
+    Ok((StatusCode::OK, Json(guest_page_to_response(&page))))
+}
+
+/// GET /guests/search — Rechercher les guests par email ou téléphone.
+#[utoipa::path(
+    get,
+    path = "/guests/search",
+    params(crate::server::guest::dto::SearchGuestsQuery),
+    responses(
+        (status = 200, description = "Guests ayant cette coordonnée", body = [crate::server::guest::dto::GuestResponse]),
+        (status = 400, description = "Paramètres invalides (exactement un parmi mail et phone)")
+    ),
+    tag = "guests"
+)]
+pub async fn search_guests(
+    State(state): State<AppState>,
+    Query(query): Query<SearchGuestsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (kind, value) = validate_search_query(&query, state.settings.default_phone_region)?;
+    let guests = state.store.guests.find_by_contact(kind, &value).await?;
+    let body: Vec<_> = guests.iter().map(guest_to_response).collect();
+    Ok((StatusCode::OK, Json(body)))
 }
 
 /// GET /guests/{id} — Récupérer un guest par uuid.
+/// Un uuid fusionné dans un autre guest redirige (308) vers le guest issu de la fusion.
 #[utoipa::path(
     get,
     path = "/guests/{id}",
     params(("id" = String, Path, description = "UUID du guest")),
     responses(
-        (status = 200, description = "Guest trouvé", body = crate::server::guest::dto::GuestResponse),
+        (status = 200, description = "Guest trouvé", body = crate::server::guest::dto::GuestResponse,
+            headers(("ETag" = String, description = "Version du guest"))),
+        (status = 308, description = "Guest fusionné : redirection vers le guest issu de la fusion",
+            body = crate::server::guest::dto::GuestRedirectResponse,
+            headers(("Location" = String, description = "Chemin du guest issu de la fusion"))),
         (status = 400, description = "Id invalide (format UUID)"),
         (status = 404, description = "Guest non trouvé")
     ),
@@ -57,36 +194,163 @@ pub async fn create_guest(
 pub async fn get_guest(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> Result<impl IntoResponse, ApiError> {
+) -> Result<Response, ApiError> {
     let uuid = parse_guest_id(&id)?;
-    let guest = state
+    if let Some(guest) = state.store.guests.get_by_id(&uuid).await? {
+        return Ok((
+            StatusCode::OK,
+            [(header::ETAG, etag(guest.version))],
+            Json(guest_to_response(&guest)),
+        )
+            .into_response());
+    }
+    let merged_into = state
         .store
         .guests
-        .get_by_id(&uuid)
+        .merged_into(&uuid)
         .await?
         .ok_or(ApiError::NotFound)?;
-    Ok((StatusCode::OK, Json(guest_to_response(&guest))))
+    tracing::info!(guest_id = %uuid, merged_into = %merged_into, "handler: redirect to merged guest");
+    Ok((
+        StatusCode::PERMANENT_REDIRECT,
+        [(header::LOCATION, format!("/guests/{merged_into}"))],
+        Json(GuestRedirectResponse {
+            merged_into: merged_into.to_string(),
+        }),
+    )
+        .into_response())
 }
 
-/// PUT /guests/{id} — Mettre à jour un guest.
+/// PUT /guests/{id} — Mettre à jour un guest ; une valeur stockée ne cède qu'à une source de confiance au moins égale (règles de survie).
+/// Un guest inexistant est créé sous cet uuid (id choisi par le système client), avec les validations d'un POST.
 #[utoipa::path(
     put,
     path = "/guests/{id}",
-    params(("id" = String, Path, description = "UUID du guest")),
+    params(
+        ("id" = String, Path, description = "UUID du guest"),
+        ("If-Match" = Option<String>, Header, description = "ETag attendu (concurrence optimiste)")
+    ),
     request_body = crate::server::guest::dto::UpdateGuestRequest,
     responses(
-        (status = 200, description = "Guest mis à jour", body = crate::server::guest::dto::GuestResponse),
-        (status = 400, description = "Requête invalide (ex: id invalide, au plus un email/téléphone préféré)"),
-        (status = 404, description = "Guest non trouvé")
+        (status = 200, description = "Guest mis à jour, avec les décisions des règles de survie", body = crate::server::guest::dto::GuestUpdateResponse,
+            headers(("ETag" = String, description = "Nouvelle version du guest"))),
+        (status = 201, description = "Guest créé sous cet uuid (aucune décision de survie)", body = crate::server::guest::dto::GuestUpdateResponse,
+            headers(("ETag" = String, description = "Version du guest"))),
+        (status = 400, description = "Requête invalide (ex: id invalide, prénom ou nom absent à la création, au plus un email/téléphone préféré, numéro de téléphone)"),
+        (status = 409, description = "Guest modifié ou créé par une écriture concurrente, ou uuid d'un guest supprimé ou fusionné"),
+        (status = 410, description = "Guest effacé (RGPD) : modification impossible"),
+        (status = 412, description = "If-Match ne correspond pas à la version courante (ou guest inexistant)")
     ),
     tag = "guests"
 )]
 pub async fn update_guest(
     State(state): State<AppState>,
+    Extension(request_id): Extension<RequestId>,
     Path(id): Path<String>,
+    headers: HeaderMap,
     Json(payload): Json<UpdateGuestRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
-    validate_update_request(&payload)?;
+    let region = state.settings.default_phone_region;
+    let uuid = parse_guest_id(&id)?;
+    let existing = state.store.guests.get_by_id(&uuid).await?;
+    validate_update_request(&payload, existing.as_ref(), region)?;
+    let (mut updated, decisions) = match &existing {
+        Some(existing) => {
+            reject_erased(existing)?;
+            check_if_match(&headers, existing.version)?;
+            apply_update_request(
+                existing.clone(),
+                &payload,
+                &state.settings.survivorship,
+                region,
+            )
+        }
+        None => {
+            if has_if_match(&headers) {
+                return Err(ApiError::PreconditionFailed(
+                    "If-Match fourni pour un guest inexistant".into(),
+                ));
+            }
+            if state
+                .store
+                .guests
+                .get_including_deleted(&uuid)
+                .await?
+                .is_some()
+            {
+                return Err(ApiError::Conflict(format!(
+                    "guest {} supprimé : uuid non réutilisable (voir POST /guests/{}/restore)",
+                    uuid, uuid
+                )));
+            }
+            if let Some(target) = state.store.guests.merged_into(&uuid).await? {
+                return Err(ApiError::Conflict(format!(
+                    "guest {} fusionné dans {} : uuid non réutilisable",
+                    uuid, target
+                )));
+            }
+            let create = create_request_from_update(&payload)?;
+            validate_create_request(&create, region)?;
+            let mut guest = create_request_to_guest(&create);
+            guest.id = uuid;
+            tracing::info!(guest_id = %uuid, "handler: creating guest with client id");
+            (guest, Vec::new())
+        }
+    };
+    validate_guest_lists(&updated, existing.as_ref(), region)?;
+    validate_attributes(&updated.attributes, &state.store.attributes.list().await?)?;
+    updated.canonicalize_contacts(region);
+    // Toutes les valeurs stockées conservées : pas d'écriture, la version reste inchangée.
+    let (saved, created) = match existing {
+        Some(existing) if updated == existing => (existing, false),
+        existing => {
+            let (saved, created) = state
+                .store
+                .guests
+                .upsert(updated, &change_context(&request_id))
+                .await?;
+            publish_consent_opt_outs(&state, existing.as_ref(), &saved, &request_id).await;
+            (saved, created)
+        }
+    };
+    Ok((
+        if created {
+            StatusCode::CREATED
+        } else {
+            StatusCode::OK
+        },
+        [(header::ETAG, etag(saved.version))],
+        Json(update_result_to_response(&saved, &decisions)),
+    ))
+}
+
+/// PATCH /guests/{id} — Modifier élément par élément les listes mail / phone / addresses / consents et les attributs.
+#[utoipa::path(
+    patch,
+    path = "/guests/{id}",
+    params(
+        ("id" = String, Path, description = "UUID du guest"),
+        ("If-Match" = Option<String>, Header, description = "ETag attendu (concurrence optimiste)")
+    ),
+    request_body = crate::server::guest::dto::PatchGuestRequest,
+    responses(
+        (status = 200, description = "Guest modifié", body = crate::server::guest::dto::GuestResponse,
+            headers(("ETag" = String, description = "Nouvelle version du guest"))),
+        (status = 400, description = "Requête invalide (ex: valeur absente, au plus un email/téléphone/adresse préféré, format email, numéro de téléphone, adresse postale)"),
+        (status = 404, description = "Guest non trouvé"),
+        (status = 409, description = "Guest modifié par une écriture concurrente"),
+        (status = 410, description = "Guest effacé (RGPD) : modification impossible"),
+        (status = 412, description = "If-Match ne correspond pas à la version courante")
+    ),
+    tag = "guests"
+)]
+pub async fn patch_guest(
+    State(state): State<AppState>,
+    Extension(request_id): Extension<RequestId>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(payload): Json<PatchGuestRequest>,
+) -> Result<impl IntoResponse, ApiError> {
     let uuid = parse_guest_id(&id)?;
     let existing = state
         .store
@@ -94,20 +358,137 @@ pub async fn update_guest(
         .get_by_id(&uuid)
         .await?
         .ok_or(ApiError::NotFound)?;
-    let updated = apply_update_request(existing, &payload);
-    let saved = state.store.guests.update(updated).await?;
-    Ok((StatusCode::OK, Json(guest_to_response(&saved))))
+    reject_erased(&existing)?;
+    check_if_match(&headers, existing.version)?;
+    let region = state.settings.default_phone_region;
+    let mut patched = apply_patch_request(existing.clone(), &payload, region)?;
+    validate_guest_lists(&patched, Some(&existing), region)?;
+    validate_attributes(&patched.attributes, &state.store.attributes.list().await?)?;
+    patched.canonicalize_contacts(region);
+    tracing::info!(guest_id = %uuid, operations = payload.operations.len(), "handler: patching guest");
+    let saved = state
+        .store
+        .guests
+        .update(patched, &change_context(&request_id))
+        .await?;
+    publish_consent_opt_outs(&state, Some(&existing), &saved, &request_id).await;
+    Ok((
+        StatusCode::OK,
+        [(header::ETAG, etag(saved.version))],
+        Json(guest_to_response(&saved)),
+    ))
 }
 
-/// DELETE /guests/{id} — Supprimer un guest et publier un message opt-out sur NATS.
+/// POST /guests/{id}/sync — Mise à jour "last writer wins" : un champ n'est remplacé que si son `updated_at` est plus récent.
+#[utoipa::path(
+    post,
+    path = "/guests/{id}/sync",
+    params(
+        ("id" = String, Path, description = "UUID du guest"),
+        ("If-Match" = Option<String>, Header, description = "ETag attendu (concurrence optimiste)")
+    ),
+    request_body = crate::server::guest::dto::UpdateGuestRequest,
+    responses(
+        (status = 200, description = "Guest synchronisé, avec les champs acceptés et rejetés", body = crate::server::guest::dto::GuestSyncResponse,
+            headers(("ETag" = String, description = "Version du guest"))),
+        (status = 400, description = "Requête invalide (ex: id invalide, au plus un email/téléphone préféré, numéro de téléphone)"),
+        (status = 404, description = "Guest non trouvé"),
+        (status = 409, description = "Guest modifié par une écriture concurrente"),
+        (status = 410, description = "Guest effacé (RGPD) : modification impossible"),
+        (status = 412, description = "If-Match ne correspond pas à la version courante")
+    ),
+    tag = "guests"
+)]
+pub async fn sync_guest(
+    State(state): State<AppState>,
+    Extension(request_id): Extension<RequestId>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(payload): Json<UpdateGuestRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let region = state.settings.default_phone_region;
+    let uuid = parse_guest_id(&id)?;
+    let existing = state
+        .store
+        .guests
+        .get_by_id(&uuid)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    reject_erased(&existing)?;
+    validate_update_request(&payload, Some(&existing), region)?;
+    check_if_match(&headers, existing.version)?;
+    let (mut merged, report) = sync_last_writer_wins(
+        existing.clone(),
+        &update_request_to_changes(&payload, region),
+    );
+    tracing::info!(
+        guest_id = %uuid,
+        accepted = report.accepted.len(),
+        rejected = report.rejected.len(),
+        "handler: syncing guest"
+    );
+    // Rien de plus récent : pas d'écriture, la version reste inchangée.
+    let saved = if report.accepted.is_empty() {
+        merged
+    } else {
+        validate_guest_lists(&merged, Some(&existing), region)?;
+        validate_attributes(&merged.attributes, &state.store.attributes.list().await?)?;
+        merged.canonicalize_contacts(region);
+        let saved = state
+            .store
+            .guests
+            .update(merged, &change_context(&request_id))
+            .await?;
+        publish_consent_opt_outs(&state, Some(&existing), &saved, &request_id).await;
+        saved
+    };
+    Ok((
+        StatusCode::OK,
+        [(header::ETAG, etag(saved.version))],
+        Json(sync_result_to_response(&saved, report)),
+    ))
+}
+
+/// GET /guests/{id}/history — Historique des modifications d'un guest (du plus récent au plus ancien).
+#[utoipa::path(
+    get,
+    path = "/guests/{id}/history",
+    params(
+        ("id" = String, Path, description = "UUID du guest"),
+        crate::server::guest::dto::GuestHistoryQuery
+    ),
+    responses(
+        (status = 200, description = "Page d'historique (conservé après suppression du guest)", body = crate::server::guest::dto::GuestHistoryResponse),
+        (status = 400, description = "Paramètres invalides (id, limit, curseur)")
+    ),
+    tag = "guests"
+)]
+pub async fn get_guest_history(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<GuestHistoryQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let uuid = parse_guest_id(&id)?;
+    let query = validate_history_query(&query)?;
+    let page = state.store.guests.history(&uuid, query).await?;
+    Ok((StatusCode::OK, Json(history_page_to_response(&page))))
+}
+
+/// DELETE /guests/{id} — Supprimer (logiquement) un guest et publier un message opt-out sur NATS.
+/// Le guest reste restaurable pendant le délai de grâce puis est purgé par une tâche de fond.
 #[utoipa::path(
     delete,
     path = "/guests/{id}",
-    params(("id" = String, Path, description = "UUID du guest")),
+    params(
+        ("id" = String, Path, description = "UUID du guest"),
+        ("If-Match" = Option<String>, Header, description = "ETag attendu (concurrence optimiste)")
+    ),
     responses(
         (status = 204, description = "Guest supprimé"),
         (status = 400, description = "Id invalide (format UUID)"),
-        (status = 404, description = "Guest non trouvé")
+        (status = 404, description = "Guest non trouvé"),
+        (status = 409, description = "Guest modifié par une écriture concurrente"),
+        (status = 412, description = "If-Match ne correspond pas à la version courante")
     ),
     tag = "guests"
 )]
@@ -115,28 +496,424 @@ pub async fn delete_guest(
     State(state): State<AppState>,
     Extension(request_id): Extension<RequestId>,
     Path(id): Path<String>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, ApiError> {
     let uuid = parse_guest_id(&id)?;
-    let deleted = state.store.guests.delete(&uuid).await?;
+    // Avec If-Match : la suppression n'a lieu que si la version vérifiée est toujours la version stockée.
+    let expected_version = if has_if_match(&headers) {
+        let existing = state
+            .store
+            .guests
+            .get_by_id(&uuid)
+            .await?
+            .ok_or(ApiError::NotFound)?;
+        check_if_match(&headers, existing.version)?;
+        Some(existing.version)
+    } else {
+        None
+    };
+    let deleted = state
+        .store
+        .guests
+        .delete(&uuid, expected_version, &change_context(&request_id))
+        .await?;
     let deleted_id = deleted.ok_or(ApiError::NotFound)?;
 
-    let trace_id = request_id
-        .header_value()
-        .to_str()
-        .unwrap_or("")
-        .to_string();
+    let event = OptOutEvent {
+        guest_id: deleted_id,
+        channels: ConsentChannel::ALL.to_vec(),
+        reason: "deleted".into(),
+        occurred_at: chrono::Utc::now(),
+    };
+    notify_opt_out(&state, event, &request_id).await;
 
-    let outbound = async_nats::jetstream::message::PublishMessage::build()
-        .header(super::stream::TRACE_ID_HEADER, trace_id.as_str())
-        .payload(bytes::Bytes::from(super::stream::OPT_OUT_MESSAGE))
-        .outbound_message(super::stream::OPT_OUT_SUBJECT);
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    let js = async_nats::jetstream::new(state.nats.clone());
-    if let Err(e) = js.publish_message(outbound).await {
-        tracing::warn!(guest_id = %deleted_id, subject = super::stream::OPT_OUT_SUBJECT, "publish opt-out: {}", e);
-    } else {
-        tracing::info!(guest_id = %deleted_id, "published opt-out to {}", super::stream::OPT_OUT_SUBJECT);
+/// POST /guests/{id}/erase — Effacement RGPD : anonymise les données personnelles du guest (id conservé),
+/// retourne le certificat d'effacement et publie le même message opt-out que la suppression.
+#[utoipa::path(
+    post,
+    path = "/guests/{id}/erase",
+    params(("id" = String, Path, description = "UUID du guest")),
+    request_body = crate::server::guest::dto::EraseGuestRequest,
+    responses(
+        (status = 200, description = "Guest effacé (y compris supprimé en attente de purge) ; certificat d'origine si déjà effacé",
+            body = crate::server::guest::dto::ErasureCertificateResponse),
+        (status = 400, description = "Id invalide ou requester vide"),
+        (status = 404, description = "Guest non trouvé")
+    ),
+    tag = "guests"
+)]
+pub async fn erase_guest(
+    State(state): State<AppState>,
+    Extension(request_id): Extension<RequestId>,
+    Path(id): Path<String>,
+    Json(payload): Json<EraseGuestRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let uuid = parse_guest_id(&id)?;
+    let requester = validate_erase_request(&payload)?;
+    let certificate = state
+        .store
+        .guests
+        .erase(&uuid, requester, &change_context(&request_id))
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    // Même événement que DELETE /guests/{id} : les consommateurs retirent le guest de tous les canaux.
+    let event = OptOutEvent {
+        guest_id: certificate.guest_id,
+        channels: ConsentChannel::ALL.to_vec(),
+        reason: "deleted".into(),
+        occurred_at: certificate.erased_at,
+    };
+    notify_opt_out(&state, event, &request_id).await;
+
+    Ok((StatusCode::OK, Json(erasure_to_response(&certificate))))
+}
+
+/// POST /guests/{id}/restore — Restaurer un guest supprimé pendant le délai de grâce.
+#[utoipa::path(
+    post,
+    path = "/guests/{id}/restore",
+    params(("id" = String, Path, description = "UUID du guest")),
+    responses(
+        (status = 200, description = "Guest restauré", body = crate::server::guest::dto::GuestResponse,
+            headers(("ETag" = String, description = "Version du guest"))),
+        (status = 400, description = "Id invalide (format UUID)"),
+        (status = 404, description = "Aucun guest supprimé restaurable (inexistant, actif ou délai de grâce dépassé)")
+    ),
+    tag = "guests"
+)]
+pub async fn restore_guest(
+    State(state): State<AppState>,
+    Extension(request_id): Extension<RequestId>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let uuid = parse_guest_id(&id)?;
+    let deleted_since = chrono::Utc::now() - state.settings.guest_restore_grace;
+    let restored = state
+        .store
+        .guests
+        .restore(&uuid, deleted_since, &change_context(&request_id))
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    tracing::info!(guest_id = %uuid, "handler: guest restored");
+    Ok((
+        StatusCode::OK,
+        [(header::ETAG, etag(restored.version))],
+        Json(guest_to_response(&restored)),
+    ))
+}
+
+/// POST /guests/{target}/merge/{source} — Fusionner le guest `source` (doublon) dans `target`.
+/// Le guest `source` est supprimé ; un GET sur son uuid redirige ensuite vers `target`.
+#[utoipa::path(
+    post,
+    path = "/guests/{target}/merge/{source}",
+    params(
+        ("target" = String, Path, description = "UUID du guest conservé"),
+        ("source" = String, Path, description = "UUID du guest fusionné puis supprimé")
+    ),
+    responses(
+        (status = 200, description = "Guest issu de la fusion", body = crate::server::guest::dto::GuestResponse,
+            headers(("ETag" = String, description = "Version du guest"))),
+        (status = 400, description = "Id invalide (format UUID) ou guest fusionné avec lui-même"),
+        (status = 404, description = "Guest target ou source non trouvé"),
+        (status = 409, description = "Guest target ou source effacé (RGPD)")
+    ),
+    tag = "guests"
+)]
+pub async fn merge_guest(
+    State(state): State<AppState>,
+    Extension(request_id): Extension<RequestId>,
+    Path((target, source)): Path<(String, String)>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (target, source) = validate_merge_ids(&target, &source)?;
+    let merged = state
+        .store
+        .guests
+        .merge(&target, &source, &change_context(&request_id))
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    Ok((
+        StatusCode::OK,
+        [(header::ETAG, etag(merged.version))],
+        Json(guest_to_response(&merged)),
+    ))
+}
+
+/// GET /guests/{id}/contactability — Le guest peut-il être contacté sur ce canal, et à quelle adresse.
+#[utoipa::path(
+    get,
+    path = "/guests/{id}/contactability",
+    params(
+        ("id" = String, Path, description = "UUID du guest"),
+        crate::server::guest::dto::ContactabilityQuery
+    ),
+    responses(
+        (status = 200, description = "Adresse à utiliser, ou raison du refus (y compris guest supprimé)",
+            body = crate::server::guest::dto::ContactabilityResponse),
+        (status = 308, description = "Guest fusionné : redirection vers la joignabilité du guest issu de la fusion",
+            body = crate::server::guest::dto::GuestRedirectResponse,
+            headers(("Location" = String, description = "Joignabilité du guest issu de la fusion"))),
+        (status = 400, description = "Id, canal ou finalité invalide"),
+        (status = 404, description = "Guest non trouvé")
+    ),
+    tag = "guests"
+)]
+pub async fn get_guest_contactability(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<ContactabilityQuery>,
+) -> Result<Response, ApiError> {
+    let uuid = parse_guest_id(&id)?;
+    let (channel, purpose) = validate_contactability_query(&query)?;
+    let guest = state.store.guests.get_by_id(&uuid).await?;
+    if guest.is_none() && state.store.guests.deleted_at(&uuid).await?.is_none() {
+        let merged_into = state
+            .store
+            .guests
+            .merged_into(&uuid)
+            .await?
+            .ok_or(ApiError::NotFound)?;
+        let location = format!(
+            "/guests/{merged_into}/contactability?channel={}&purpose={}",
+            channel.as_str(),
+            purpose.as_str()
+        );
+        return Ok((
+            StatusCode::PERMANENT_REDIRECT,
+            [(header::LOCATION, location)],
+            Json(GuestRedirectResponse {
+                merged_into: merged_into.to_string(),
+            }),
+        )
+            .into_response());
     }
+    let result = contactability(guest.as_ref(), channel, purpose);
+    Ok((
+        StatusCode::OK,
+        Json(contactability_to_response(channel, purpose, result)),
+    )
+        .into_response())
+}
 
-    Ok(StatusCode::NO_CONTENT)
+/// GET /guests/{id}/subject-access — Demande d'accès RGPD : données détenues sur le guest (JSON ou zip avec résumé).
+#[utoipa::path(
+    get,
+    path = "/guests/{id}/subject-access",
+    params(
+        ("id" = String, Path, description = "UUID du guest"),
+        crate::server::guest::dto::SubjectAccessQuery
+    ),
+    responses(
+        (status = 200, description = "Fiche avec provenance, historique, événements opt-out, guests fusionnés et doublons \
+            (y compris guest supprimé en attente de purge) ; en zip : subject-access.json et summary.txt",
+            content(
+                (crate::server::guest::dto::SubjectAccessResponse = "application/json"),
+                (Vec<u8> = "application/zip")
+            )),
+        (status = 308, description = "Guest fusionné : redirection vers le guest issu de la fusion",
+            body = crate::server::guest::dto::GuestRedirectResponse,
+            headers(("Location" = String, description = "Demande d'accès du guest issu de la fusion"))),
+        (status = 400, description = "Id ou format invalide"),
+        (status = 404, description = "Guest non trouvé")
+    ),
+    tag = "guests"
+)]
+pub async fn get_guest_subject_access(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<SubjectAccessQuery>,
+) -> Result<Response, ApiError> {
+    let uuid = parse_guest_id(&id)?;
+    let format = subject_access_format(&query)?;
+    let Some(guest) = state.store.guests.get_including_deleted(&uuid).await? else {
+        let merged_into = state
+            .store
+            .guests
+            .merged_into(&uuid)
+            .await?
+            .ok_or(ApiError::NotFound)?;
+        let mut location = format!("/guests/{merged_into}/subject-access");
+        if let Some(format) = &query.format {
+            location.push_str(&format!("?format={format}"));
+        }
+        return Ok((
+            StatusCode::PERMANENT_REDIRECT,
+            [(header::LOCATION, location)],
+            Json(GuestRedirectResponse {
+                merged_into: merged_into.to_string(),
+            }),
+        )
+            .into_response());
+    };
+    tracing::info!(guest_id = %uuid, ?format, "handler: subject access");
+    let package = collect_subject_access(state.store.guests.as_ref(), &guest).await?;
+    match format {
+        SubjectAccessFormat::Json => Ok((StatusCode::OK, Json(package)).into_response()),
+        SubjectAccessFormat::Zip => {
+            let disposition = format!("attachment; filename=\"subject-access-{uuid}.zip\"");
+            Ok((
+                StatusCode::OK,
+                [
+                    (header::CONTENT_TYPE, "application/zip".to_string()),
+                    (header::CONTENT_DISPOSITION, disposition),
+                ],
+                subject_access_zip(&package)?,
+            )
+                .into_response())
+        }
+    }
+}
+
+/// GET /guests/{id}/duplicates — Doublons potentiels d'un guest, classés par score.
+#[utoipa::path(
+    get,
+    path = "/guests/{id}/duplicates",
+    params(
+        ("id" = String, Path, description = "UUID du guest"),
+        crate::server::guest::dto::GuestDuplicatesQuery
+    ),
+    responses(
+        (status = 200, description = "Candidats classés (score décroissant) avec les raisons",
+            body = [crate::server::guest::dto::GuestDuplicateResponse]),
+        (status = 400, description = "Id ou limit invalide"),
+        (status = 404, description = "Guest non trouvé")
+    ),
+    tag = "guests"
+)]
+pub async fn get_guest_duplicates(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<GuestDuplicatesQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let uuid = parse_guest_id(&id)?;
+    let limit = validate_duplicates_query(&query)?;
+    let guest = state
+        .store
+        .guests
+        .get_by_id(&uuid)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    let others = state.store.guests.duplicate_candidates(&guest).await?;
+    let body: Vec<_> = rank_duplicates(&guest, &others)
+        .iter()
+        .take(limit)
+        .map(duplicate_candidate_to_response)
+        .collect();
+    Ok((StatusCode::OK, Json(body)))
+}
+
+/// GET /guests/duplicates — Paires de doublons potentiels trouvées par le dernier scan global.
+#[utoipa::path(
+    get,
+    path = "/guests/duplicates",
+    params(crate::server::guest::dto::DuplicatePairsQuery),
+    responses(
+        (status = 200, description = "Page de paires (score décroissant)", body = crate::server::guest::dto::DuplicatePairListResponse),
+        (status = 400, description = "Paramètres invalides (limit, curseur)")
+    ),
+    tag = "guests"
+)]
+pub async fn list_duplicate_pairs(
+    State(state): State<AppState>,
+    Query(query): Query<DuplicatePairsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let query = validate_duplicate_pairs_query(&query)?;
+    let page = state.store.guests.list_duplicate_pairs(query).await?;
+    Ok((StatusCode::OK, Json(duplicate_pair_page_to_response(&page))))
+}
+
+/// POST /guests/duplicates/scan — Lancer un scan global des doublons (asynchrone).
+#[utoipa::path(
+    post,
+    path = "/guests/duplicates/scan",
+    responses(
+        (status = 202, description = "Scan lancé ; résultats via GET /guests/duplicates")
+    ),
+    tag = "guests"
+)]
+pub async fn scan_guest_duplicates(State(state): State<AppState>) -> impl IntoResponse {
+    spawn_duplicate_scan(state.store.clone());
+    StatusCode::ACCEPTED
+}
+
+/// POST /guests/import — Importer des guests en masse (NDJSON ou CSV), avec un rapport par ligne.
+#[utoipa::path(
+    post,
+    path = "/guests/import",
+    params(crate::server::guest::dto::ImportGuestsQuery),
+    request_body(content = String, description = "NDJSON (une CreateGuestRequest par ligne) ou CSV avec en-tête",
+        content_type = "application/x-ndjson"),
+    responses(
+        (status = 200, description = "Rapport d'import (ids créés et erreurs par ligne)", body = crate::server::guest::dto::ImportGuestsResponse),
+        (status = 400, description = "Format inconnu ou en-tête CSV invalide (colonne absente)")
+    ),
+    tag = "guests"
+)]
+pub async fn import_guests(
+    State(state): State<AppState>,
+    Extension(request_id): Extension<RequestId>,
+    Query(query): Query<ImportGuestsQuery>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<impl IntoResponse, ApiError> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok());
+    let rows = match import_format(&query, content_type)? {
+        ImportFormat::Ndjson => read_ndjson(&body),
+        ImportFormat::Csv => read_csv(&body, &query)?,
+    };
+    tracing::info!(rows = rows.len(), "handler: importing guests");
+    let report = import_rows(
+        &state.store,
+        rows,
+        state.settings.default_phone_region,
+        &state.store.attributes.list().await?,
+        &change_context(&request_id),
+    )
+    .await;
+    Ok((StatusCode::OK, Json(report)))
+}
+
+/// GET /guests/export — Exporter tous les guests en flux (NDJSON, CSV ou JSON, transfert chunked).
+#[utoipa::path(
+    get,
+    path = "/guests/export",
+    params(
+        crate::server::guest::dto::ExportGuestsQuery,
+        ("Accept" = Option<String>, Header, description = "application/x-ndjson, text/csv ou application/json (si pas de ?format=)")
+    ),
+    responses(
+        (status = 200, description = "Guests triés par uuid ; CSV aplati sur l'email et le téléphone préférés",
+            content(
+                (String = "application/x-ndjson"),
+                (String = "text/csv"),
+                ([crate::server::guest::dto::GuestResponse] = "application/json")
+            )),
+        (status = 400, description = "Format invalide")
+    ),
+    tag = "guests"
+)]
+pub async fn export_guests(
+    State(state): State<AppState>,
+    Query(query): Query<ExportGuestsQuery>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, ApiError> {
+    let accept = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok());
+    let format = export_format(&query, accept)?;
+    tracing::info!(format = format.content_type(), "handler: exporting guests");
+    let guests = state.store.guests.export(GuestListQuery {
+        updated_after: query.updated_since,
+        limit: EXPORT_PAGE_SIZE,
+        ..Default::default()
+    });
+    Ok((
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, format.content_type())],
+        export_body(format, guests),
+    ))
 }
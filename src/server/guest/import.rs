@@ -0,0 +1,260 @@
+//! Import en masse : lecture des lignes NDJSON / CSV en `CreateGuestRequest` (une entrée par ligne du fichier),
+//! validation ligne par ligne et insertion par lots transactionnels.
+
+use crate::domain::{
+    validate_attributes, AttributeDefinition, ChangeContext, Guest, PhoneRegion, ValidationError,
+};
+use crate::server::guest::dto::{
+    CreateGuestRequest, ImportGuestsQuery, ImportGuestsResponse, ImportRowResponse,
+    StructuredValueStringInput,
+};
+use crate::server::guest::mapper::create_request_to_guest;
+use crate::server::guest::validation::validate_create_request;
+use crate::store::Store;
+
+/// Taille maximale du corps d'un import (le défaut axum de 2 Mo est trop petit pour un import d'hôtel).
+pub const IMPORT_BODY_LIMIT: usize = 64 * 1024 * 1024;
+
+/// Nombre de guests insérés par transaction SQLite.
+const IMPORT_BATCH_SIZE: usize = 500;
+
+/// Séparateur de plusieurs valeurs dans une cellule CSV mail / phone (ex. `a@x.com|b@x.com`).
+const CSV_MULTI_VALUE_SEPARATOR: char = '|';
+
+/// Format du corps d'un import.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Ndjson,
+    Csv,
+}
+
+/// Ligne lue : numéro de ligne dans le fichier (à partir de 1) et requête ou erreur de lecture.
+pub struct ImportRow {
+    pub line: usize,
+    pub request: Result<CreateGuestRequest, String>,
+}
+
+/// Format de l'import : `?format=` prioritaire, sinon déduit du Content-Type.
+pub fn import_format(
+    query: &ImportGuestsQuery,
+    content_type: Option<&str>,
+) -> Result<ImportFormat, ValidationError> {
+    if let Some(format) = query.format.as_deref() {
+        return match format {
+            "ndjson" => Ok(ImportFormat::Ndjson),
+            "csv" => Ok(ImportFormat::Csv),
+            other => Err(ValidationError(format!(
+                "format invalide: '{}' (ndjson ou csv)",
+                other
+            ))),
+        };
+    }
+    match content_type {
+        Some(ct) if ct.contains("csv") => Ok(ImportFormat::Csv),
+        Some(ct) if ct.contains("ndjson") || ct.contains("jsonl") => Ok(ImportFormat::Ndjson),
+        _ => Err(ValidationError(
+            "format d'import inconnu: Content-Type text/csv ou application/x-ndjson, ou ?format="
+                .into(),
+        )),
+    }
+}
+
+/// Lit un corps NDJSON : une `CreateGuestRequest` par ligne non vide.
+pub fn read_ndjson(body: &str) -> Vec<ImportRow> {
+    body.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| ImportRow {
+            line: i + 1,
+            request: serde_json::from_str(line).map_err(|e| format!("JSON invalide: {}", e)),
+        })
+        .collect()
+}
+
+/// Colonnes CSV (index) utilisées pour chaque champ du guest.
+struct CsvColumns {
+    first_name: usize,
+    last_name: usize,
+    mail: Option<usize>,
+    phone: Option<usize>,
+}
+
+/// Index d'une colonne ; `mapped` (nom donné en paramètre) est obligatoire s'il est fourni.
+fn column_index(
+    headers: &csv::StringRecord,
+    field: &str,
+    mapped: Option<&String>,
+) -> Result<Option<usize>, ValidationError> {
+    let name = mapped.map(String::as_str).unwrap_or(field);
+    let index = headers.iter().position(|h| h.trim() == name);
+    if index.is_none() && mapped.is_some() {
+        return Err(ValidationError(format!(
+            "{}: colonne '{}' absente de l'en-tête CSV",
+            field, name
+        )));
+    }
+    Ok(index)
+}
+
+fn csv_columns(
+    headers: &csv::StringRecord,
+    query: &ImportGuestsQuery,
+) -> Result<CsvColumns, ValidationError> {
+    let required = |field: &str, mapped: Option<&String>| {
+        column_index(headers, field, mapped)?
+            .ok_or_else(|| ValidationError(format!("{}: colonne absente de l'en-tête CSV", field)))
+    };
+    Ok(CsvColumns {
+        first_name: required("first_name", query.first_name_column.as_ref())?,
+        last_name: required("last_name", query.last_name_column.as_ref())?,
+        mail: column_index(headers, "mail", query.mail_column.as_ref())?,
+        phone: column_index(headers, "phone", query.phone_column.as_ref())?,
+    })
+}
+
+fn csv_value(value: &str, from: Option<&String>) -> StructuredValueStringInput {
+    StructuredValueStringInput {
+        value: value.trim().to_string(),
+        from: from.cloned(),
+        updated_at: None,
+        preferred_at: None,
+    }
+}
+
+/// Valeurs d'une cellule mail / phone (séparées par `|`), None si la colonne est absente ou la cellule vide.
+fn csv_list(
+    record: &csv::StringRecord,
+    column: Option<usize>,
+    from: Option<&String>,
+) -> Option<Vec<StructuredValueStringInput>> {
+    let values: Vec<_> = column
+        .and_then(|i| record.get(i))
+        .map(|cell| {
+            cell.split(CSV_MULTI_VALUE_SEPARATOR)
+                .filter(|v| !v.trim().is_empty())
+                .map(|v| csv_value(v, from))
+                .collect()
+        })
+        .unwrap_or_default();
+    (!values.is_empty()).then_some(values)
+}
+
+/// Lit un corps CSV avec en-tête ; les colonnes sont celles du mapping (`*_column`) ou, à défaut,
+/// les colonnes nommées comme les champs (`first_name`, `last_name`, `mail`, `phone`).
+pub fn read_csv(body: &str, query: &ImportGuestsQuery) -> Result<Vec<ImportRow>, ValidationError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(body.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| ValidationError(format!("en-tête CSV invalide: {}", e)))?
+        .clone();
+    let columns = csv_columns(&headers, query)?;
+    let from = query.from.as_ref();
+
+    let rows = reader
+        .records()
+        .enumerate()
+        .map(|(i, record)| {
+            // Ligne 1 = en-tête ; la position du record est utilisée quand elle est connue (cellules multilignes).
+            let line = record
+                .as_ref()
+                .ok()
+                .and_then(|r| r.position())
+                .map_or(i + 2, |p| p.line() as usize);
+            let request = record
+                .map_err(|e| format!("CSV invalide: {}", e))
+                .map(|record| CreateGuestRequest {
+                    first_name: csv_value(record.get(columns.first_name).unwrap_or(""), from),
+                    last_name: csv_value(record.get(columns.last_name).unwrap_or(""), from),
+                    mail: csv_list(&record, columns.mail, from),
+                    phone: csv_list(&record, columns.phone, from),
+                    addresses: None,
+                    consents: None,
+                    attributes: None,
+                });
+            ImportRow { line, request }
+        })
+        .collect();
+    Ok(rows)
+}
+
+/// Insère un lot de guests valides ; en cas d'échec du lot, chaque ligne du lot est reportée en erreur.
+async fn import_batch(
+    store: &Store,
+    batch: Vec<(usize, Guest)>,
+    ctx: &ChangeContext,
+    report: &mut Vec<ImportRowResponse>,
+) {
+    let (lines, guests): (Vec<usize>, Vec<Guest>) = batch.into_iter().unzip();
+    match store.guests.create_many(guests, ctx).await {
+        Ok(created) => {
+            report.extend(
+                lines
+                    .into_iter()
+                    .zip(created)
+                    .map(|(line, guest)| ImportRowResponse {
+                        line,
+                        id: Some(guest.id.to_string()),
+                        error: None,
+                    }),
+            )
+        }
+        Err(e) => {
+            tracing::error!(rows = lines.len(), "import: batch failed: {e}");
+            report.extend(lines.into_iter().map(|line| ImportRowResponse {
+                line,
+                id: None,
+                error: Some(format!("erreur de stockage: {}", e)),
+            }));
+        }
+    }
+}
+
+/// Valide chaque ligne avec `validate_create_request` et le registre des attributs, et insère les lignes
+/// valides par lots ; les lignes invalides sont reportées sans interrompre l'import.
+pub async fn import_rows(
+    store: &Store,
+    rows: Vec<ImportRow>,
+    region: Option<PhoneRegion>,
+    definitions: &[AttributeDefinition],
+    ctx: &ChangeContext,
+) -> ImportGuestsResponse {
+    let mut report = Vec::with_capacity(rows.len());
+    let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+    for row in rows {
+        let guest = row.request.and_then(|req| {
+            validate_create_request(&req, region).map_err(|e| e.to_string())?;
+            let mut guest = create_request_to_guest(&req);
+            validate_attributes(&guest.attributes, definitions).map_err(|e| e.to_string())?;
+            guest.canonicalize_contacts(region);
+            Ok(guest)
+        });
+        match guest {
+            Ok(guest) => {
+                batch.push((row.line, guest));
+                if batch.len() == IMPORT_BATCH_SIZE {
+                    import_batch(store, std::mem::take(&mut batch), ctx, &mut report).await;
+                }
+            }
+            Err(error) => report.push(ImportRowResponse {
+                line: row.line,
+                id: None,
+                error: Some(error),
+            }),
+        }
+    }
+    if !batch.is_empty() {
+        import_batch(store, batch, ctx, &mut report).await;
+    }
+    report.sort_by_key(|r| r.line);
+
+    let created = report.iter().filter(|r| r.id.is_some()).count();
+    let failed = report.len() - created;
+    tracing::info!(created, failed, "import: guests imported");
+    ImportGuestsResponse {
+        created,
+        failed,
+        rows: report,
+    }
+}
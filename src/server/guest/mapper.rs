@@ -1,48 +1,247 @@
 //! Mappers domaine Guest ↔ DTOs API.
 
+use std::collections::BTreeMap;
+
 use chrono::Utc;
 
-use crate::domain::{Guest, StructuredValue};
+use crate::domain::{
+    apply_survivorship, may_replace_consent, phone_line_type, Address, Consent, ConsentChannel,
+    ConsentPurpose, ConsentStatus, ContactKind, Contactability, DuplicateCandidate, DuplicatePair,
+    DuplicatePairPage, DuplicateReason, ErasureCertificate, Guest, GuestAttributes, GuestChanges,
+    GuestHistoryEntry, GuestPage, HistoryPage, LegalBasis, OptOutEventRecord, PhoneRegion,
+    StructuredValue, SurvivorshipDecision, SurvivorshipRules, SyncReport, ValidationError,
+};
+use crate::server::cursor::encode_cursor;
 use crate::server::guest::dto::{
-    CreateGuestRequest, GuestResponse, StructuredValueBoolInput, StructuredValueBoolResponse,
-    StructuredValueStringInput, StructuredValueStringResponse, UpdateGuestRequest,
+    AddressInput, AddressResponse, ConsentChannelDto, ConsentInput, ConsentPurposeDto,
+    ConsentResponse, ConsentStatusDto, ContactabilityResponse, CreateGuestRequest,
+    DuplicatePairListResponse, DuplicatePairResponse, DuplicateReasonResponse,
+    ErasureCertificateResponse, GuestContactField, GuestDuplicateResponse,
+    GuestHistoryEntryResponse, GuestHistoryResponse, GuestListResponse, GuestPatchOperation,
+    GuestResponse, GuestSyncResponse, GuestUpdateResponse, LegalBasisDto, OptOutEventResponse,
+    PatchGuestRequest, StructuredValueAddressInput, StructuredValueAddressResponse,
+    StructuredValueJsonInput, StructuredValueJsonResponse, StructuredValuePhoneResponse,
+    StructuredValueStringInput, StructuredValueStringResponse, SurvivorshipDecisionResponse,
+    UpdateGuestRequest,
 };
 
-fn structured_value_string_to_response(s: &StructuredValue<String>) -> StructuredValueStringResponse {
+fn structured_value_string_to_response(
+    s: &StructuredValue<String>,
+) -> StructuredValueStringResponse {
     StructuredValueStringResponse {
         value: s.value.clone(),
         from: s.from.clone(),
         updated_at: s.updated_at,
         preferred_at: s.preferred_at,
+        normalized: s.normalized.clone(),
     }
 }
 
-fn structured_value_bool_to_response(s: &StructuredValue<bool>) -> StructuredValueBoolResponse {
-    StructuredValueBoolResponse {
-        value: s.value,
+/// Téléphone en réponse : champ structuré et type de ligne déduit de la forme E.164.
+fn structured_value_phone_to_response(s: &StructuredValue<String>) -> StructuredValuePhoneResponse {
+    StructuredValuePhoneResponse {
+        value: s.value.clone(),
         from: s.from.clone(),
         updated_at: s.updated_at,
         preferred_at: s.preferred_at,
+        normalized: s.normalized.clone(),
+        line_type: phone_line_type(s).map(|t| t.as_str().to_string()),
+    }
+}
+
+fn structured_value_json_to_response(
+    s: &StructuredValue<serde_json::Value>,
+) -> StructuredValueJsonResponse {
+    StructuredValueJsonResponse {
+        value: s.value.clone(),
+        from: s.from.clone(),
+        updated_at: s.updated_at,
+    }
+}
+
+fn structured_value_address_to_response(
+    s: &StructuredValue<Address>,
+) -> StructuredValueAddressResponse {
+    StructuredValueAddressResponse {
+        value: AddressResponse {
+            lines: s.value.lines.clone(),
+            postal_code: s.value.postal_code.clone(),
+            city: s.value.city.clone(),
+            region: s.value.region.clone(),
+            country: s.value.country.clone(),
+        },
+        from: s.from.clone(),
+        updated_at: s.updated_at,
+        preferred_at: s.preferred_at,
+    }
+}
+
+fn consent_channel_to_dto(channel: ConsentChannel) -> ConsentChannelDto {
+    match channel {
+        ConsentChannel::Email => ConsentChannelDto::Email,
+        ConsentChannel::Sms => ConsentChannelDto::Sms,
+        ConsentChannel::Phone => ConsentChannelDto::Phone,
+        ConsentChannel::Postal => ConsentChannelDto::Postal,
+    }
+}
+
+fn consent_channel_to_domain(channel: ConsentChannelDto) -> ConsentChannel {
+    match channel {
+        ConsentChannelDto::Email => ConsentChannel::Email,
+        ConsentChannelDto::Sms => ConsentChannel::Sms,
+        ConsentChannelDto::Phone => ConsentChannel::Phone,
+        ConsentChannelDto::Postal => ConsentChannel::Postal,
+    }
+}
+
+fn consent_purpose_to_dto(purpose: ConsentPurpose) -> ConsentPurposeDto {
+    match purpose {
+        ConsentPurpose::Marketing => ConsentPurposeDto::Marketing,
+        ConsentPurpose::Transactional => ConsentPurposeDto::Transactional,
     }
 }
 
-fn structured_value_input_to_domain_string(input: StructuredValueStringInput) -> StructuredValue<String> {
+fn consent_purpose_to_domain(purpose: ConsentPurposeDto) -> ConsentPurpose {
+    match purpose {
+        ConsentPurposeDto::Marketing => ConsentPurpose::Marketing,
+        ConsentPurposeDto::Transactional => ConsentPurpose::Transactional,
+    }
+}
+
+fn consent_status_to_dto(status: ConsentStatus) -> ConsentStatusDto {
+    match status {
+        ConsentStatus::OptedIn => ConsentStatusDto::OptedIn,
+        ConsentStatus::OptedOut => ConsentStatusDto::OptedOut,
+    }
+}
+
+fn consent_status_to_domain(status: ConsentStatusDto) -> ConsentStatus {
+    match status {
+        ConsentStatusDto::OptedIn => ConsentStatus::OptedIn,
+        ConsentStatusDto::OptedOut => ConsentStatus::OptedOut,
+    }
+}
+
+fn legal_basis_to_dto(basis: LegalBasis) -> LegalBasisDto {
+    match basis {
+        LegalBasis::Consent => LegalBasisDto::Consent,
+        LegalBasis::Contract => LegalBasisDto::Contract,
+        LegalBasis::LegalObligation => LegalBasisDto::LegalObligation,
+        LegalBasis::LegitimateInterest => LegalBasisDto::LegitimateInterest,
+    }
+}
+
+fn legal_basis_to_domain(basis: LegalBasisDto) -> LegalBasis {
+    match basis {
+        LegalBasisDto::Consent => LegalBasis::Consent,
+        LegalBasisDto::Contract => LegalBasis::Contract,
+        LegalBasisDto::LegalObligation => LegalBasis::LegalObligation,
+        LegalBasisDto::LegitimateInterest => LegalBasis::LegitimateInterest,
+    }
+}
+
+fn consent_to_response(s: &StructuredValue<Consent>) -> ConsentResponse {
+    ConsentResponse {
+        channel: consent_channel_to_dto(s.value.channel),
+        purpose: consent_purpose_to_dto(s.value.purpose),
+        status: consent_status_to_dto(s.value.status),
+        legal_basis: legal_basis_to_dto(s.value.legal_basis),
+        collected_at: s.value.collected_at,
+        from: s.from.clone(),
+        updated_at: s.updated_at,
+    }
+}
+
+fn structured_value_input_to_domain_string(
+    input: StructuredValueStringInput,
+) -> StructuredValue<String> {
     let updated_at = input.updated_at.unwrap_or_else(Utc::now);
     StructuredValue {
         value: input.value,
         from: input.from,
         updated_at,
         preferred_at: input.preferred_at,
+        normalized: None,
     }
 }
 
-fn structured_value_input_to_domain_bool(input: StructuredValueBoolInput) -> StructuredValue<bool> {
+fn structured_value_input_to_domain_json(
+    input: StructuredValueJsonInput,
+) -> StructuredValue<serde_json::Value> {
     let updated_at = input.updated_at.unwrap_or_else(Utc::now);
     StructuredValue {
         value: input.value,
         from: input.from,
         updated_at,
         preferred_at: None,
+        normalized: None,
+    }
+}
+
+/// Adresse postale en entrée → domaine.
+fn address_input_to_domain(input: AddressInput) -> Address {
+    let AddressInput {
+        lines,
+        postal_code,
+        city,
+        region,
+        country,
+    } = input;
+    Address {
+        lines,
+        postal_code,
+        city,
+        region,
+        country,
+    }
+}
+
+/// Adresse postale structurée en entrée → domaine.
+pub fn structured_value_input_to_domain_address(
+    input: StructuredValueAddressInput,
+) -> StructuredValue<Address> {
+    let updated_at = input.updated_at.unwrap_or_else(Utc::now);
+    StructuredValue {
+        value: address_input_to_domain(input.value),
+        from: input.from,
+        updated_at,
+        preferred_at: input.preferred_at,
+        normalized: None,
+    }
+}
+
+/// Attributs personnalisés en entrée → domaine.
+fn attributes_input_to_domain(
+    input: &BTreeMap<String, StructuredValueJsonInput>,
+) -> GuestAttributes {
+    input
+        .iter()
+        .map(|(name, v)| {
+            (
+                name.clone(),
+                structured_value_input_to_domain_json(v.clone()),
+            )
+        })
+        .collect()
+}
+
+/// Consentement en entrée → domaine (base légale `consent` par défaut).
+pub fn consent_input_to_domain(input: ConsentInput) -> StructuredValue<Consent> {
+    let updated_at = input.updated_at.unwrap_or_else(Utc::now);
+    StructuredValue {
+        value: Consent {
+            channel: consent_channel_to_domain(input.channel),
+            purpose: consent_purpose_to_domain(input.purpose),
+            status: consent_status_to_domain(input.status),
+            legal_basis: input
+                .legal_basis
+                .map_or(LegalBasis::Consent, legal_basis_to_domain),
+            collected_at: input.collected_at,
+        },
+        from: input.from,
+        updated_at,
+        preferred_at: None,
+        normalized: None,
     }
 }
 
@@ -51,9 +250,143 @@ pub fn guest_to_response(guest: &Guest) -> GuestResponse {
         id: guest.id,
         first_name: structured_value_string_to_response(&guest.first_name),
         last_name: structured_value_string_to_response(&guest.last_name),
-        mail: guest.mail.iter().map(structured_value_string_to_response).collect(),
-        phone: guest.phone.iter().map(structured_value_string_to_response).collect(),
-        opt_outs: guest.opt_outs.iter().map(structured_value_bool_to_response).collect(),
+        mail: guest
+            .mail
+            .iter()
+            .map(structured_value_string_to_response)
+            .collect(),
+        phone: guest
+            .phone
+            .iter()
+            .map(structured_value_phone_to_response)
+            .collect(),
+        addresses: guest
+            .addresses
+            .iter()
+            .map(structured_value_address_to_response)
+            .collect(),
+        consents: guest.consents.iter().map(consent_to_response).collect(),
+        attributes: guest
+            .attributes
+            .iter()
+            .map(|(name, v)| (name.clone(), structured_value_json_to_response(v)))
+            .collect(),
+        version: guest.version,
+    }
+}
+
+/// Page domaine → réponse API (le curseur suivant est rendu opaque).
+pub fn guest_page_to_response(page: &GuestPage) -> GuestListResponse {
+    GuestListResponse {
+        items: page.guests.iter().map(guest_to_response).collect(),
+        next_cursor: page.next.map(|id| encode_cursor(&id.to_string())),
+    }
+}
+
+/// Entrée d'historique domaine → réponse API.
+pub fn history_entry_to_response(entry: &GuestHistoryEntry) -> GuestHistoryEntryResponse {
+    GuestHistoryEntryResponse {
+        seq: entry.seq,
+        operation: entry.operation.as_str().to_string(),
+        field: entry.change.field.clone(),
+        old_value: entry.change.old_value.clone(),
+        new_value: entry.change.new_value.clone(),
+        from: entry.change.from.clone(),
+        changed_at: entry.changed_at,
+        trace_id: entry.trace_id.clone(),
+    }
+}
+
+/// Page d'historique domaine → réponse API (le curseur suivant est rendu opaque).
+pub fn history_page_to_response(page: &HistoryPage) -> GuestHistoryResponse {
+    GuestHistoryResponse {
+        items: page.entries.iter().map(history_entry_to_response).collect(),
+        next_cursor: page.next.map(|seq| encode_cursor(&seq.to_string())),
+    }
+}
+
+fn duplicate_reasons_to_response(reasons: &[DuplicateReason]) -> Vec<DuplicateReasonResponse> {
+    reasons
+        .iter()
+        .map(|r| DuplicateReasonResponse {
+            field: r.field.clone(),
+            value: r.value.clone(),
+            similarity: r.similarity,
+        })
+        .collect()
+}
+
+/// Convertit un candidat au doublon en réponse API.
+pub fn duplicate_candidate_to_response(candidate: &DuplicateCandidate) -> GuestDuplicateResponse {
+    GuestDuplicateResponse {
+        guest: guest_to_response(&candidate.guest),
+        score: candidate.score,
+        reasons: duplicate_reasons_to_response(&candidate.reasons),
+    }
+}
+
+/// Convertit une paire du scan de doublons en réponse API.
+pub fn duplicate_pair_to_response(pair: &DuplicatePair) -> DuplicatePairResponse {
+    DuplicatePairResponse {
+        guest_id: pair.guest_id.to_string(),
+        candidate_id: pair.candidate_id.to_string(),
+        score: pair.score,
+        reasons: duplicate_reasons_to_response(&pair.reasons),
+    }
+}
+
+/// Convertit une page de paires du scan de doublons en réponse API (curseur opaque).
+pub fn duplicate_pair_page_to_response(page: &DuplicatePairPage) -> DuplicatePairListResponse {
+    DuplicatePairListResponse {
+        items: page.pairs.iter().map(duplicate_pair_to_response).collect(),
+        next_cursor: page.next.map(|seq| encode_cursor(&seq.to_string())),
+    }
+}
+
+/// Événement opt-out conservé → réponse API.
+pub fn opt_out_event_to_response(record: &OptOutEventRecord) -> OptOutEventResponse {
+    OptOutEventResponse {
+        channels: record
+            .event
+            .channels
+            .iter()
+            .copied()
+            .map(consent_channel_to_dto)
+            .collect(),
+        reason: record.event.reason.clone(),
+        occurred_at: record.event.occurred_at,
+        trace_id: record.trace_id.clone(),
+        published: record.published,
+    }
+}
+
+/// Certificat d'effacement → réponse API.
+pub fn erasure_to_response(certificate: &ErasureCertificate) -> ErasureCertificateResponse {
+    ErasureCertificateResponse {
+        guest_id: certificate.guest_id.to_string(),
+        erased_at: certificate.erased_at,
+        requester: certificate.requester.clone(),
+        trace_id: certificate.trace_id.clone(),
+        fields: certificate.fields.clone(),
+    }
+}
+
+/// Joignabilité domaine → réponse API.
+pub fn contactability_to_response(
+    channel: ConsentChannel,
+    purpose: ConsentPurpose,
+    result: Contactability,
+) -> ContactabilityResponse {
+    let (address, reason) = match result {
+        Contactability::Contactable { address } => (Some(address), None),
+        Contactability::NotContactable { reason } => (None, Some(reason.as_str().to_string())),
+    };
+    ContactabilityResponse {
+        channel: channel.as_str().to_string(),
+        purpose: purpose.as_str().to_string(),
+        contactable: address.is_some(),
+        address,
+        reason,
     }
 }
 
@@ -62,17 +395,37 @@ pub fn create_request_to_guest(req: &CreateGuestRequest) -> Guest {
     let mail = req
         .mail
         .as_ref()
-        .map(|v| v.iter().cloned().map(structured_value_input_to_domain_string).collect())
+        .map(|v| {
+            v.iter()
+                .cloned()
+                .map(structured_value_input_to_domain_string)
+                .collect()
+        })
         .unwrap_or_default();
     let phone = req
         .phone
         .as_ref()
-        .map(|v| v.iter().cloned().map(structured_value_input_to_domain_string).collect())
+        .map(|v| {
+            v.iter()
+                .cloned()
+                .map(structured_value_input_to_domain_string)
+                .collect()
+        })
         .unwrap_or_default();
-    let opt_outs = req
-        .opt_outs
+    let addresses = req
+        .addresses
         .as_ref()
-        .map(|v| v.iter().cloned().map(structured_value_input_to_domain_bool).collect())
+        .map(|v| {
+            v.iter()
+                .cloned()
+                .map(structured_value_input_to_domain_address)
+                .collect()
+        })
+        .unwrap_or_default();
+    let consents = req
+        .consents
+        .as_ref()
+        .map(|v| v.iter().cloned().map(consent_input_to_domain).collect())
         .unwrap_or_default();
     Guest {
         id: uuid::Uuid::new_v4(),
@@ -80,43 +433,378 @@ pub fn create_request_to_guest(req: &CreateGuestRequest) -> Guest {
         last_name: structured_value_input_to_domain_string(req.last_name.clone()),
         mail,
         phone,
-        opt_outs,
+        addresses,
+        consents,
+        attributes: req
+            .attributes
+            .as_ref()
+            .map(attributes_input_to_domain)
+            .unwrap_or_default(),
+        version: 0,
+        erased_at: None,
     }
 }
 
-/// Applique UpdateGuestRequest sur un guest existant (met à jour les champs fournis).
-pub fn apply_update_request(guest: Guest, req: &UpdateGuestRequest) -> Guest {
-    let first_name = req
-        .first_name
-        .as_ref()
-        .map(|v| structured_value_input_to_domain_string(v.clone()))
-        .unwrap_or(guest.first_name);
-    let last_name = req
-        .last_name
-        .as_ref()
-        .map(|v| structured_value_input_to_domain_string(v.clone()))
-        .unwrap_or(guest.last_name);
-    let mail = req
-        .mail
-        .as_ref()
-        .map(|v| v.iter().cloned().map(structured_value_input_to_domain_string).collect())
-        .unwrap_or(guest.mail);
-    let phone = req
-        .phone
-        .as_ref()
-        .map(|v| v.iter().cloned().map(structured_value_input_to_domain_string).collect())
-        .unwrap_or(guest.phone);
-    let opt_outs = req
-        .opt_outs
-        .as_ref()
-        .map(|v| v.iter().cloned().map(structured_value_input_to_domain_bool).collect())
-        .unwrap_or(guest.opt_outs);
-    Guest {
-        id: guest.id,
-        first_name,
-        last_name,
-        mail,
-        phone,
-        opt_outs,
+/// UpdateGuestRequest → modifications domaine (champs fournis uniquement), emails et téléphones canonicalisés
+/// pour être appariés aux valeurs stockées (ex. `06 12 34 56 78` et `+33612345678`).
+pub fn update_request_to_changes(
+    req: &UpdateGuestRequest,
+    region: Option<PhoneRegion>,
+) -> GuestChanges {
+    let mut changes = GuestChanges {
+        first_name: req
+            .first_name
+            .as_ref()
+            .map(|v| structured_value_input_to_domain_string(v.clone())),
+        last_name: req
+            .last_name
+            .as_ref()
+            .map(|v| structured_value_input_to_domain_string(v.clone())),
+        mail: req.mail.as_ref().map(|v| {
+            v.iter()
+                .cloned()
+                .map(structured_value_input_to_domain_string)
+                .collect()
+        }),
+        phone: req.phone.as_ref().map(|v| {
+            v.iter()
+                .cloned()
+                .map(structured_value_input_to_domain_string)
+                .collect()
+        }),
+        addresses: req.addresses.as_ref().map(|v| {
+            v.iter()
+                .cloned()
+                .map(structured_value_input_to_domain_address)
+                .collect()
+        }),
+        consents: req
+            .consents
+            .as_ref()
+            .map(|v| v.iter().cloned().map(consent_input_to_domain).collect()),
+        attributes: req.attributes.as_ref().map(attributes_input_to_domain),
+    };
+    changes.canonicalize_contacts(region);
+    changes
+}
+
+/// Applique UpdateGuestRequest sur un guest existant (champs fournis) selon les règles de survie.
+pub fn apply_update_request(
+    guest: Guest,
+    req: &UpdateGuestRequest,
+    rules: &SurvivorshipRules,
+    region: Option<PhoneRegion>,
+) -> (Guest, Vec<SurvivorshipDecision>) {
+    apply_survivorship(guest, &update_request_to_changes(req, region), rules)
+}
+
+/// Guest mis à jour + décisions de survie → réponse API.
+pub fn update_result_to_response(
+    guest: &Guest,
+    decisions: &[SurvivorshipDecision],
+) -> GuestUpdateResponse {
+    GuestUpdateResponse {
+        guest: guest_to_response(guest),
+        survivorship: decisions
+            .iter()
+            .map(|d| SurvivorshipDecisionResponse {
+                field: d.field.clone(),
+                accepted: d.accepted,
+                rule: d.rule.as_str().to_string(),
+                stored_from: d.stored_from.clone(),
+                incoming_from: d.incoming_from.clone(),
+            })
+            .collect(),
+    }
+}
+
+/// Guest synchronisé + rapport → réponse API.
+pub fn sync_result_to_response(guest: &Guest, report: SyncReport) -> GuestSyncResponse {
+    GuestSyncResponse {
+        guest: guest_to_response(guest),
+        accepted: report.accepted,
+        rejected: report.rejected,
+    }
+}
+
+fn contact_kind(field: GuestContactField) -> ContactKind {
+    match field {
+        GuestContactField::Mail => ContactKind::Mail,
+        GuestContactField::Phone => ContactKind::Phone,
+    }
+}
+
+fn contact_list(guest: &mut Guest, field: GuestContactField) -> &mut Vec<StructuredValue<String>> {
+    match field {
+        GuestContactField::Mail => &mut guest.mail,
+        GuestContactField::Phone => &mut guest.phone,
+    }
+}
+
+/// Position de l'entrée ayant cette valeur (comparaison sur la forme canonique, ex. E.164).
+fn position_of(
+    list: &[StructuredValue<String>],
+    kind: ContactKind,
+    value: &str,
+    region: Option<PhoneRegion>,
+) -> Option<usize> {
+    let key = kind.input_key(value, region);
+    list.iter().position(|v| kind.key(v) == key)
+}
+
+/// Position de cette adresse dans la liste ; erreur si elle est absente du guest.
+fn address_position(
+    guest: &Guest,
+    index: usize,
+    value: &AddressInput,
+) -> Result<usize, ValidationError> {
+    let address = address_input_to_domain(value.clone());
+    guest
+        .addresses
+        .iter()
+        .position(|a| a.value.same_as(&address))
+        .ok_or_else(|| {
+            ValidationError(format!(
+                "operations[{}]: adresse '{}' absente du guest",
+                index,
+                address.label()
+            ))
+        })
+}
+
+fn missing_value(index: usize, kind: ContactKind, value: &str) -> ValidationError {
+    ValidationError(format!(
+        "operations[{}]: {} '{}' absent du guest",
+        index,
+        kind.as_str(),
+        value
+    ))
+}
+
+/// Applique les opérations PATCH, dans l'ordre, sur un guest existant.
+/// Erreur si une opération vise une valeur absente ; la validation du résultat est faite par l'appelant.
+pub fn apply_patch_request(
+    mut guest: Guest,
+    req: &PatchGuestRequest,
+    region: Option<PhoneRegion>,
+) -> Result<Guest, ValidationError> {
+    for (i, operation) in req.operations.iter().enumerate() {
+        match operation {
+            GuestPatchOperation::Add { field, item } => {
+                let kind = contact_kind(*field);
+                let entry = structured_value_input_to_domain_string(item.clone());
+                let list = contact_list(&mut guest, *field);
+                match position_of(list, kind, &entry.value, region) {
+                    Some(pos) => list[pos] = entry,
+                    None => list.push(entry),
+                }
+                // Forme canonique renseignée pour que les opérations suivantes apparient cette entrée.
+                guest.canonicalize_contacts(region);
+            }
+            GuestPatchOperation::Remove { field, value } => {
+                let kind = contact_kind(*field);
+                let list = contact_list(&mut guest, *field);
+                let pos = position_of(list, kind, value, region)
+                    .ok_or_else(|| missing_value(i, kind, value))?;
+                list.remove(pos);
+            }
+            GuestPatchOperation::SetPreferred { field, value } => {
+                let kind = contact_kind(*field);
+                let list = contact_list(&mut guest, *field);
+                let pos = position_of(list, kind, value, region)
+                    .ok_or_else(|| missing_value(i, kind, value))?;
+                let now = Utc::now();
+                for (j, entry) in list.iter_mut().enumerate() {
+                    let preferred = j == pos;
+                    if entry.preferred_at.is_some() != preferred {
+                        entry.preferred_at = preferred.then_some(now);
+                        entry.updated_at = now;
+                    }
+                }
+            }
+            GuestPatchOperation::AddAddress { item } => {
+                let entry = structured_value_input_to_domain_address(item.clone());
+                match guest
+                    .addresses
+                    .iter()
+                    .position(|a| a.value.same_as(&entry.value))
+                {
+                    Some(pos) => guest.addresses[pos] = entry,
+                    None => guest.addresses.push(entry),
+                }
+            }
+            GuestPatchOperation::RemoveAddress { value } => {
+                let pos = address_position(&guest, i, value)?;
+                guest.addresses.remove(pos);
+            }
+            GuestPatchOperation::SetPreferredAddress { value } => {
+                let pos = address_position(&guest, i, value)?;
+                let now = Utc::now();
+                for (j, entry) in guest.addresses.iter_mut().enumerate() {
+                    let preferred = j == pos;
+                    if entry.preferred_at.is_some() != preferred {
+                        entry.preferred_at = preferred.then_some(now);
+                        entry.updated_at = now;
+                    }
+                }
+            }
+            GuestPatchOperation::SetConsent { item } => {
+                let entry = consent_input_to_domain(item.clone());
+                match guest
+                    .consents
+                    .iter()
+                    .position(|c| c.value.same_scope(&entry.value))
+                {
+                    Some(pos) if !may_replace_consent(&guest.consents[pos], &entry) => {
+                        return Err(ValidationError(format!(
+                            "operations[{}]: opt-out {} levé uniquement par un opted_in plus récent",
+                            i,
+                            entry.value.scope()
+                        )));
+                    }
+                    Some(pos) => guest.consents[pos] = entry,
+                    None => guest.consents.push(entry),
+                }
+            }
+            GuestPatchOperation::RemoveConsent { channel, purpose } => {
+                let (channel, purpose) = (
+                    consent_channel_to_domain(*channel),
+                    consent_purpose_to_domain(*purpose),
+                );
+                let pos = guest
+                    .consents
+                    .iter()
+                    .position(|c| c.value.channel == channel && c.value.purpose == purpose)
+                    .ok_or_else(|| {
+                        ValidationError(format!(
+                            "operations[{}]: consentement {}/{} absent du guest",
+                            i,
+                            channel.as_str(),
+                            purpose.as_str()
+                        ))
+                    })?;
+                if guest.consents[pos].value.status == ConsentStatus::OptedOut {
+                    return Err(ValidationError(format!(
+                        "operations[{}]: opt-out {}/{} non supprimable (le lever par un opted_in)",
+                        i,
+                        channel.as_str(),
+                        purpose.as_str()
+                    )));
+                }
+                guest.consents.remove(pos);
+            }
+            GuestPatchOperation::SetAttribute { name, item } => {
+                guest.attributes.insert(
+                    name.clone(),
+                    structured_value_input_to_domain_json(item.clone()),
+                );
+            }
+            GuestPatchOperation::RemoveAttribute { name } => {
+                guest.attributes.remove(name).ok_or_else(|| {
+                    ValidationError(format!(
+                        "operations[{}]: attribut '{}' absent du guest",
+                        i, name
+                    ))
+                })?;
+            }
+        }
+    }
+    Ok(guest)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn address(line: &str, postal_code: &str, city: &str) -> serde_json::Value {
+        json!({ "lines": [line], "postal_code": postal_code, "city": city, "country": "FR" })
+    }
+
+    /// Guest avec deux adresses, la première préférée.
+    fn guest_with_addresses() -> Guest {
+        let mut guest = Guest::new(uuid::Uuid::new_v4(), "Ana".into(), "Lopez".into());
+        for (i, (line, postal_code, city)) in [
+            ("1 rue de la Paix", "75002", "Paris"),
+            ("5 avenue Foch", "69006", "Lyon"),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let input: StructuredValueAddressInput = serde_json::from_value(json!({
+                "value": address(line, postal_code, city),
+                "preferred_at": (i == 0).then(Utc::now),
+            }))
+            .unwrap();
+            guest
+                .addresses
+                .push(structured_value_input_to_domain_address(input));
+        }
+        guest
+    }
+
+    fn patch(guest: Guest, operations: serde_json::Value) -> Result<Guest, ValidationError> {
+        let req: PatchGuestRequest =
+            serde_json::from_value(json!({ "operations": operations })).unwrap();
+        apply_patch_request(guest, &req, None)
+    }
+
+    fn preferred(guest: &Guest) -> Vec<&str> {
+        guest
+            .addresses
+            .iter()
+            .filter(|a| a.preferred_at.is_some())
+            .map(|a| a.value.city.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn set_preferred_address_keeps_a_single_preferred() {
+        let patched = patch(
+            guest_with_addresses(),
+            json!([{ "op": "set_preferred_address", "value": address("5 AVENUE  foch", "69006", "lyon") }]),
+        )
+        .unwrap();
+        assert_eq!(preferred(&patched), ["Lyon"]);
+    }
+
+    #[test]
+    fn removing_the_preferred_address_leaves_none_preferred() {
+        let patched = patch(
+            guest_with_addresses(),
+            json!([{ "op": "remove_address", "value": address("1 rue de la Paix", "75002", "Paris") }]),
+        )
+        .unwrap();
+        assert_eq!(patched.addresses.len(), 1);
+        assert_eq!(patched.addresses[0].value.city, "Lyon");
+        assert!(preferred(&patched).is_empty());
+    }
+
+    #[test]
+    fn add_address_replaces_the_same_place() {
+        let patched = patch(
+            guest_with_addresses(),
+            json!([{ "op": "add_address", "item": { "value": address("1 Rue de la Paix", "75002", "PARIS"), "from": "web" } }]),
+        )
+        .unwrap();
+        assert_eq!(patched.addresses.len(), 2);
+        assert_eq!(patched.addresses[0].from.as_deref(), Some("web"));
+        assert!(preferred(&patched).is_empty());
+    }
+
+    #[test]
+    fn unknown_address_is_rejected() {
+        for op in ["remove_address", "set_preferred_address"] {
+            let err = patch(
+                guest_with_addresses(),
+                json!([{ "op": op, "value": address("9 quai de Saône", "69002", "Lyon") }]),
+            )
+            .unwrap_err();
+            assert_eq!(
+                err.0,
+                "operations[0]: adresse '9 quai de Saône, 69002 Lyon, FR' absente du guest"
+            );
+        }
     }
 }
@@ -1,14 +1,39 @@
-//! Module serveur pour les guests : DTOs, mappers, handlers, validation, stream NATS.
+//! Module serveur pour les guests : DTOs, mappers, handlers, validation, stream NATS, purge, doublons, import / export,
+//! demande d'accès RGPD.
 
 pub mod dto;
+mod duplicates;
+mod export;
 pub mod handlers;
+mod import;
 mod mapper;
+mod purge;
 pub mod stream;
+mod subject_access;
 mod validation;
 
 pub use dto::{
-    CreateGuestRequest, GuestResponse, StructuredValueBoolInput, StructuredValueBoolResponse,
-    StructuredValueStringInput, StructuredValueStringResponse, UpdateGuestRequest,
+    AddressInput, AddressResponse, ConsentChannelDto, ConsentInput, ConsentPurposeDto,
+    ConsentResponse, ConsentStatusDto, ContactabilityResponse, CreateGuestRequest,
+    DuplicatePairListResponse, DuplicatePairResponse, DuplicateReasonResponse, EraseGuestRequest,
+    ErasureCertificateResponse, GuestContactField, GuestDuplicateResponse,
+    GuestHistoryEntryResponse, GuestHistoryResponse, GuestListResponse, GuestPatchOperation,
+    GuestRedirectResponse, GuestResponse, GuestSyncResponse, GuestUpdateResponse,
+    ImportGuestsResponse, ImportRowResponse, LegalBasisDto, MergedGuestAccessResponse,
+    OptOutEventResponse, PatchGuestRequest, StructuredValueAddressInput,
+    StructuredValueAddressResponse, StructuredValueJsonInput, StructuredValueJsonResponse,
+    StructuredValuePhoneResponse, StructuredValueStringInput, StructuredValueStringResponse,
+    SubjectAccessResponse, SurvivorshipDecisionResponse, UpdateGuestRequest,
 };
-pub use handlers::{create_guest, delete_guest, get_guest, update_guest};
+pub use duplicates::spawn_guest_duplicate_scan_task;
+pub use handlers::{
+    create_guest, delete_guest, erase_guest, export_guests, get_guest, get_guest_contactability,
+    get_guest_duplicates, get_guest_history, get_guest_subject_access, import_guests,
+    list_duplicate_pairs, list_guests, merge_guest, patch_guest, restore_guest,
+    scan_guest_duplicates, search_guests, sync_guest, update_guest,
+};
+pub use import::IMPORT_BODY_LIMIT;
+pub(crate) use mapper::guest_to_response;
+pub use purge::spawn_guest_purge_task;
 pub use stream::spawn_guests_stream_tasks;
+pub(crate) use validation::{page_limit, parse_guest_id};
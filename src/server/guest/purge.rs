@@ -0,0 +1,23 @@
+//! Tâche de fond : purge définitive des guests supprimés depuis plus que la durée de rétention.
+
+use std::time::Duration;
+
+use tracing::{error, info};
+
+use crate::store::Store;
+
+/// Démarre la tâche de purge en tâche Tokio (un passage par intervalle, le premier immédiatement).
+pub fn spawn_guest_purge_task(store: Store, retention: chrono::Duration, every: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(every);
+        loop {
+            ticker.tick().await;
+            let cutoff = chrono::Utc::now() - retention;
+            match store.guests.purge_deleted(cutoff).await {
+                Ok(0) => {}
+                Ok(purged) => info!(purged, %cutoff, "purge: deleted guests removed"),
+                Err(e) => error!("purge guests: {e}"),
+            }
+        }
+    });
+}
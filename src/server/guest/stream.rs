@@ -2,6 +2,7 @@
 //! Écoute les messages (ex. opt-out à la suppression d'un guest) et affiche le payload.
 
 use async_nats::jetstream::consumer::PullConsumer;
+use async_nats::jetstream::context::traits::Publisher;
 use async_nats::jetstream::stream::Config;
 use async_nats::jetstream::Context;
 use async_nats::Client;
@@ -9,19 +10,43 @@ use futures_util::StreamExt;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Semaphore;
-use tracing::{error, info};
+use tracing::{error, info, warn};
+
+use crate::domain::OptOutEvent;
 
 // --- Constantes exposées (handler et consumer) ---
 
-/// Sujet sur lequel publier l’événement opt-out lors d’un DELETE guest.
+/// Sujet sur lequel publier l’événement opt-out (DELETE guest ou consentement passé en opt-out).
 pub const OPT_OUT_SUBJECT: &str = "stream.guest.opt-out";
 
-/// Message envoyé sur OPT_OUT_SUBJECT lors d’un opt-out.
-pub const OPT_OUT_MESSAGE: &str = "Guest opt-out from sparkpost";
-
 /// Header NATS pour propager le trace_id (request_id HTTP) jusqu'au consumer.
 pub const TRACE_ID_HEADER: &str = "trace-id";
 
+/// Publie un événement opt-out (JSON) sur OPT_OUT_SUBJECT ; un échec de publication est journalisé
+/// sans faire échouer la requête. Retourne `true` si la publication a réussi.
+pub async fn publish_opt_out(client: &Client, event: &OptOutEvent, trace_id: &str) -> bool {
+    let payload = match serde_json::to_vec(event) {
+        Ok(payload) => payload,
+        Err(e) => {
+            warn!(guest_id = %event.guest_id, "publish opt-out: {}", e);
+            return false;
+        }
+    };
+    let outbound = async_nats::jetstream::message::PublishMessage::build()
+        .header(TRACE_ID_HEADER, trace_id)
+        .payload(bytes::Bytes::from(payload))
+        .outbound_message(OPT_OUT_SUBJECT);
+
+    let js = async_nats::jetstream::new(client.clone());
+    if let Err(e) = js.publish_message(outbound).await {
+        warn!(guest_id = %event.guest_id, subject = OPT_OUT_SUBJECT, "publish opt-out: {}", e);
+        false
+    } else {
+        info!(guest_id = %event.guest_id, channels = ?event.channels, "published opt-out to {}", OPT_OUT_SUBJECT);
+        true
+    }
+}
+
 // --- Config stream / consumer ---
 
 /// Stream dédié opt-out (évite les conflits avec un ancien stream GUESTS mal configuré).
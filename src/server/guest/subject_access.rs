@@ -0,0 +1,365 @@
+//! Demande d'accès RGPD (art. 15) : paquet JSON des données détenues sur un guest (fiche avec provenance,
+//! historique, événements opt-out, guests fusionnés, doublons), éventuellement zippé avec un résumé lisible.
+
+use std::fmt::Write as _;
+use std::io::Write as _;
+
+use chrono::Utc;
+
+use crate::domain::{
+    Guest, GuestHistoryEntry, GuestRepository, HistoryQuery, RepositoryError, ValidationError,
+};
+use crate::server::guest::dto::{
+    ConsentChannelDto, ConsentPurposeDto, ConsentStatusDto, GuestHistoryEntryResponse,
+    LegalBasisDto, MergedGuestAccessResponse, OptOutEventResponse, SubjectAccessQuery,
+    SubjectAccessResponse,
+};
+use crate::server::guest::mapper::{
+    duplicate_pair_to_response, erasure_to_response, guest_to_response, history_entry_to_response,
+    opt_out_event_to_response,
+};
+
+/// Nombre d'entrées d'historique lues par requête SQLite.
+const HISTORY_PAGE_SIZE: u32 = 200;
+
+/// Nom du fichier JSON dans l'archive zip.
+const JSON_FILE_NAME: &str = "subject-access.json";
+/// Nom du résumé lisible dans l'archive zip.
+const SUMMARY_FILE_NAME: &str = "summary.txt";
+
+/// Format du paquet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubjectAccessFormat {
+    Json,
+    Zip,
+}
+
+/// Format du paquet (`?format=`, JSON par défaut).
+pub fn subject_access_format(
+    query: &SubjectAccessQuery,
+) -> Result<SubjectAccessFormat, ValidationError> {
+    match query.format.as_deref() {
+        None | Some("json") => Ok(SubjectAccessFormat::Json),
+        Some("zip") => Ok(SubjectAccessFormat::Zip),
+        Some(other) => Err(ValidationError(format!(
+            "format invalide: '{}' (json ou zip)",
+            other
+        ))),
+    }
+}
+
+/// Historique complet d'un guest (toutes les pages), du plus récent au plus ancien.
+async fn full_history(
+    guests: &dyn GuestRepository,
+    id: &uuid::Uuid,
+) -> Result<Vec<GuestHistoryEntry>, RepositoryError> {
+    let mut entries = Vec::new();
+    let mut before = None;
+    loop {
+        let page = guests
+            .history(
+                id,
+                HistoryQuery {
+                    before,
+                    limit: HISTORY_PAGE_SIZE,
+                },
+            )
+            .await?;
+        entries.extend(page.entries);
+        match page.next {
+            Some(next) => before = Some(next),
+            None => return Ok(entries),
+        }
+    }
+}
+
+async fn history_response(
+    guests: &dyn GuestRepository,
+    id: &uuid::Uuid,
+) -> Result<Vec<GuestHistoryEntryResponse>, RepositoryError> {
+    Ok(full_history(guests, id)
+        .await?
+        .iter()
+        .map(history_entry_to_response)
+        .collect())
+}
+
+async fn opt_out_events_response(
+    guests: &dyn GuestRepository,
+    id: &uuid::Uuid,
+) -> Result<Vec<OptOutEventResponse>, RepositoryError> {
+    Ok(guests
+        .opt_out_events(id)
+        .await?
+        .iter()
+        .map(opt_out_event_to_response)
+        .collect())
+}
+
+/// Rassemble les données détenues sur `guest` (actif ou supprimé en attente de purge).
+pub async fn collect_subject_access(
+    guests: &dyn GuestRepository,
+    guest: &Guest,
+) -> Result<SubjectAccessResponse, RepositoryError> {
+    let mut merged_from = Vec::new();
+    for source in guests.merged_sources(&guest.id).await? {
+        merged_from.push(MergedGuestAccessResponse {
+            guest_id: source.to_string(),
+            history: history_response(guests, &source).await?,
+            opt_out_events: opt_out_events_response(guests, &source).await?,
+        });
+    }
+    Ok(SubjectAccessResponse {
+        generated_at: Utc::now(),
+        guest: guest_to_response(guest),
+        deleted_at: guests.deleted_at(&guest.id).await?,
+        history: history_response(guests, &guest.id).await?,
+        opt_out_events: opt_out_events_response(guests, &guest.id).await?,
+        merged_from,
+        duplicate_pairs: guests
+            .duplicate_pairs_of(&guest.id)
+            .await?
+            .iter()
+            .map(duplicate_pair_to_response)
+            .collect(),
+        erasures: guests
+            .erasures(&guest.id)
+            .await?
+            .iter()
+            .map(erasure_to_response)
+            .collect(),
+    })
+}
+
+fn channel_label(channel: ConsentChannelDto) -> &'static str {
+    match channel {
+        ConsentChannelDto::Email => "email",
+        ConsentChannelDto::Sms => "SMS",
+        ConsentChannelDto::Phone => "téléphone",
+        ConsentChannelDto::Postal => "courrier",
+    }
+}
+
+fn purpose_label(purpose: ConsentPurposeDto) -> &'static str {
+    match purpose {
+        ConsentPurposeDto::Marketing => "marketing",
+        ConsentPurposeDto::Transactional => "transactionnel",
+    }
+}
+
+fn status_label(status: ConsentStatusDto) -> &'static str {
+    match status {
+        ConsentStatusDto::OptedIn => "accepté",
+        ConsentStatusDto::OptedOut => "refusé",
+    }
+}
+
+fn legal_basis_label(basis: LegalBasisDto) -> &'static str {
+    match basis {
+        LegalBasisDto::Consent => "consentement",
+        LegalBasisDto::Contract => "contrat",
+        LegalBasisDto::LegalObligation => "obligation légale",
+        LegalBasisDto::LegitimateInterest => "intérêt légitime",
+    }
+}
+
+/// Ligne du résumé pour une valeur : valeur, provenance et date.
+fn value_line(
+    out: &mut String,
+    label: &str,
+    value: &str,
+    from: Option<&str>,
+    updated_at: chrono::DateTime<Utc>,
+) {
+    let _ = writeln!(
+        out,
+        "  - {label} : {value} (source : {}, le {})",
+        from.unwrap_or("inconnue"),
+        updated_at.format("%Y-%m-%d %H:%M UTC")
+    );
+}
+
+/// Résumé lisible (texte) du paquet.
+pub fn subject_access_summary(package: &SubjectAccessResponse) -> String {
+    let guest = &package.guest;
+    let mut out = String::new();
+    let _ = writeln!(out, "Demande d'accès aux données personnelles");
+    let _ = writeln!(out, "Guest : {}", guest.id);
+    let _ = writeln!(
+        out,
+        "Généré le : {}",
+        package.generated_at.format("%Y-%m-%d %H:%M UTC")
+    );
+    for erasure in &package.erasures {
+        let _ = writeln!(
+            out,
+            "Données effacées le {} à la demande de {}",
+            erasure.erased_at.format("%Y-%m-%d %H:%M UTC"),
+            erasure.requester
+        );
+    }
+    if let Some(deleted_at) = package.deleted_at {
+        let _ = writeln!(
+            out,
+            "Fiche supprimée le {} (en attente de purge)",
+            deleted_at.format("%Y-%m-%d %H:%M UTC")
+        );
+    }
+
+    let _ = writeln!(out, "\nIdentité");
+    let names = [("Prénom", &guest.first_name), ("Nom", &guest.last_name)];
+    for (label, v) in names {
+        value_line(&mut out, label, &v.value, v.from.as_deref(), v.updated_at);
+    }
+
+    let _ = writeln!(out, "\nCoordonnées");
+    let mails = guest
+        .mail
+        .iter()
+        .map(|m| ("Email", &m.value, m.from.as_deref(), m.updated_at));
+    let phones = guest
+        .phone
+        .iter()
+        .map(|p| ("Téléphone", &p.value, p.from.as_deref(), p.updated_at));
+    let mut any_contact = false;
+    for (label, value, from, updated_at) in mails.chain(phones) {
+        any_contact = true;
+        value_line(&mut out, label, value, from, updated_at);
+    }
+    for a in &guest.addresses {
+        any_contact = true;
+        let locality = [
+            a.value.postal_code.as_deref(),
+            Some(a.value.city.as_str()),
+            a.value.region.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+        let value = format!(
+            "{}, {}, {}",
+            a.value.lines.join(", "),
+            locality,
+            a.value.country
+        );
+        value_line(&mut out, "Adresse", &value, a.from.as_deref(), a.updated_at);
+    }
+    if !any_contact {
+        let _ = writeln!(out, "  (aucune)");
+    }
+
+    let _ = writeln!(out, "\nConsentements");
+    if guest.consents.is_empty() {
+        let _ = writeln!(out, "  (aucun)");
+    }
+    for c in &guest.consents {
+        let label = format!(
+            "{} ({})",
+            channel_label(c.channel),
+            purpose_label(c.purpose)
+        );
+        let value = format!(
+            "{} (base légale : {})",
+            status_label(c.status),
+            legal_basis_label(c.legal_basis)
+        );
+        value_line(&mut out, &label, &value, c.from.as_deref(), c.updated_at);
+    }
+
+    if !guest.attributes.is_empty() {
+        let _ = writeln!(out, "\nAttributs");
+        for (name, v) in &guest.attributes {
+            let value = match &v.value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            value_line(&mut out, name, &value, v.from.as_deref(), v.updated_at);
+        }
+    }
+
+    let _ = writeln!(
+        out,
+        "\nHistorique : {} modification(s)",
+        package.history.len()
+    );
+    if let (Some(last), Some(first)) = (package.history.first(), package.history.last()) {
+        let _ = writeln!(
+            out,
+            "  du {} au {}",
+            first.changed_at.format("%Y-%m-%d"),
+            last.changed_at.format("%Y-%m-%d")
+        );
+    }
+
+    let _ = writeln!(
+        out,
+        "\nDésinscriptions (opt-out) notifiées : {}",
+        package.opt_out_events.len()
+    );
+    for e in &package.opt_out_events {
+        let channels = e
+            .channels
+            .iter()
+            .map(|c| channel_label(*c))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = writeln!(
+            out,
+            "  - {} : {} ({}{})",
+            e.occurred_at.format("%Y-%m-%d %H:%M UTC"),
+            channels,
+            e.reason,
+            if e.published {
+                ""
+            } else {
+                ", publication échouée"
+            }
+        );
+    }
+
+    if !package.merged_from.is_empty() {
+        let _ = writeln!(out, "\nFiches fusionnées dans celle-ci");
+        for m in &package.merged_from {
+            let _ = writeln!(
+                out,
+                "  - {} ({} modification(s))",
+                m.guest_id,
+                m.history.len()
+            );
+        }
+    }
+    if !package.duplicate_pairs.is_empty() {
+        let _ = writeln!(
+            out,
+            "\nDoublons potentiels détectés : {}",
+            package.duplicate_pairs.len()
+        );
+    }
+    let _ = writeln!(out, "\nLe détail complet figure dans {JSON_FILE_NAME}.");
+    out
+}
+
+fn zip_error(e: impl std::fmt::Display) -> RepositoryError {
+    RepositoryError::Other(format!("subject access zip: {e}"))
+}
+
+/// Archive zip : paquet JSON et résumé lisible.
+pub fn subject_access_zip(package: &SubjectAccessResponse) -> Result<Vec<u8>, RepositoryError> {
+    let json =
+        serde_json::to_vec_pretty(package).map_err(|e| RepositoryError::Other(e.to_string()))?;
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    writer
+        .start_file(JSON_FILE_NAME, options)
+        .map_err(zip_error)?;
+    writer.write_all(&json).map_err(zip_error)?;
+    writer
+        .start_file(SUMMARY_FILE_NAME, options)
+        .map_err(zip_error)?;
+    writer
+        .write_all(subject_access_summary(package).as_bytes())
+        .map_err(zip_error)?;
+    Ok(writer.finish().map_err(zip_error)?.into_inner())
+}
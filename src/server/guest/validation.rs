@@ -1,55 +1,131 @@
-//! Validation des requêtes guest : au plus un préféré par liste, format email, id UUID.
+//! Validation des requêtes guest : au plus un préféré par liste, format email, numéro de téléphone,
+//! adresse postale, un consentement par canal et finalité, id UUID.
 
-use crate::domain::ValidationError;
+use chrono::{DateTime, Utc};
+
+use crate::domain::{
+    parse_email, parse_phone, validate_addresses, validate_consents, validate_emails,
+    ConsentChannel, ConsentPurpose, ContactKind, DuplicatePairQuery, Guest, GuestListQuery,
+    HistoryQuery, PhoneRegion, StructuredValue, ValidationError,
+};
+use crate::server::cursor::decode_cursor;
 use crate::server::guest::dto::{
-    CreateGuestRequest, StructuredValueStringInput, UpdateGuestRequest,
+    ConsentInput, ContactabilityQuery, CreateGuestRequest, DuplicatePairsQuery, EraseGuestRequest,
+    GuestDuplicatesQuery, GuestHistoryQuery, ListGuestsQuery, SearchGuestsQuery,
+    StructuredValueAddressInput, StructuredValueStringInput, UpdateGuestRequest,
+};
+use crate::server::guest::mapper::{
+    consent_input_to_domain, structured_value_input_to_domain_address,
 };
 
+/// Taille de page par défaut (listing, historique, doublons).
+const DEFAULT_PAGE_SIZE: u32 = 50;
+/// Taille de page maximale (listing, historique, doublons).
+const MAX_PAGE_SIZE: u32 = 200;
+
 /// Vérifie qu'au plus un élément a `preferred_at` renseigné.
-fn at_most_one_preferred(items: &[StructuredValueStringInput]) -> bool {
-    items.iter().filter(|i| i.preferred_at.is_some()).count() <= 1
+fn at_most_one_preferred(preferred: impl IntoIterator<Item = Option<DateTime<Utc>>>) -> bool {
+    preferred.into_iter().filter(Option::is_some).count() <= 1
 }
 
-/// Valide le format d'une adresse email (simple : contient @, non vide, longueur raisonnable).
-fn is_valid_email_format(s: &str) -> bool {
-    let s = s.trim();
-    if s.is_empty() || s.len() > 254 {
-        return false;
+/// Valide une liste d'emails (value, preferred_at) : au plus un préféré, chaque value ajoutée ou modifiée
+/// par rapport à `stored` au format email (RFC 5321 / 5322) et sans doublon une fois normalisée.
+fn validate_mail_values<'a>(
+    mails: impl IntoIterator<Item = (&'a str, Option<DateTime<Utc>>)>,
+    stored: &[StructuredValue<String>],
+) -> Result<(), ValidationError> {
+    let (values, preferred): (Vec<&str>, Vec<Option<DateTime<Utc>>>) = mails.into_iter().unzip();
+    if !at_most_one_preferred(preferred) {
+        return Err(ValidationError(
+            "mail: au plus un email peut avoir preferred_at".into(),
+        ));
     }
-    let parts: Vec<&str> = s.split('@').collect();
-    parts.len() == 2 && !parts[0].is_empty() && parts[1].contains('.')
+    validate_emails(values, stored)
+}
+
+/// La valeur est-elle déjà stockée sur le guest ? Une valeur stockée n'est pas recontrôlée : données antérieures
+/// non analysables (ex. `tel: 0612`) et valeurs effacées restent acceptées tant qu'elles ne changent pas.
+fn is_stored(stored: &[StructuredValue<String>], value: &str) -> bool {
+    stored.iter().any(|s| s.value == value)
 }
 
-/// Valide une liste d'emails : au plus un préféré, chaque value au format email.
-fn validate_mail_list(mails: &[StructuredValueStringInput]) -> Result<(), ValidationError> {
-    if !at_most_one_preferred(mails) {
+/// Valide une liste de téléphones (value, preferred_at) : au plus un préféré, chaque value ajoutée ou modifiée
+/// par rapport à `stored` analysable (numéros sans indicatif lus dans `region`).
+fn validate_phone_values<'a>(
+    phones: impl IntoIterator<Item = (&'a str, Option<DateTime<Utc>>)>,
+    stored: &[StructuredValue<String>],
+    region: Option<PhoneRegion>,
+) -> Result<(), ValidationError> {
+    let (values, preferred): (Vec<&str>, Vec<Option<DateTime<Utc>>>) = phones.into_iter().unzip();
+    if !at_most_one_preferred(preferred) {
         return Err(ValidationError(
-            "mail: au plus un email peut avoir preferred_at".into(),
+            "phone: au plus un numéro peut avoir preferred_at".into(),
         ));
     }
-    for (i, m) in mails.iter().enumerate() {
-        if !is_valid_email_format(m.value.trim()) {
+    for (i, value) in values.iter().enumerate() {
+        if is_stored(stored, value) {
+            continue;
+        }
+        if let Err(e) = parse_phone(value, region) {
             return Err(ValidationError(format!(
-                "mail[{}]: value doit être une adresse email valide",
-                i
+                "phone[{}]: value doit être un numéro de téléphone valide ({})",
+                i, e
             )));
         }
     }
     Ok(())
 }
 
-/// Valide une liste de téléphones : au plus un préféré.
-fn validate_phone_list(phones: &[StructuredValueStringInput]) -> Result<(), ValidationError> {
-    if !at_most_one_preferred(phones) {
-        return Err(ValidationError(
-            "phone: au plus un numéro peut avoir preferred_at".into(),
-        ));
-    }
-    Ok(())
+/// Valide une liste d'emails en entrée : au plus un préféré, chaque value absente de `stored` au format email
+/// et sans doublon.
+fn validate_mail_list(
+    mails: &[StructuredValueStringInput],
+    stored: &[StructuredValue<String>],
+) -> Result<(), ValidationError> {
+    validate_mail_values(
+        mails.iter().map(|m| (m.value.as_str(), m.preferred_at)),
+        stored,
+    )
+}
+
+/// Valide une liste de téléphones en entrée : au plus un préféré, chaque value absente de `stored` analysable.
+fn validate_phone_list(
+    phones: &[StructuredValueStringInput],
+    stored: &[StructuredValue<String>],
+    region: Option<PhoneRegion>,
+) -> Result<(), ValidationError> {
+    validate_phone_values(
+        phones.iter().map(|p| (p.value.as_str(), p.preferred_at)),
+        stored,
+        region,
+    )
+}
+
+/// Valide une liste d'adresses en entrée : au plus une préférée, composants et code postal selon le pays, sans doublon.
+fn validate_address_list(addresses: &[StructuredValueAddressInput]) -> Result<(), ValidationError> {
+    let addresses: Vec<_> = addresses
+        .iter()
+        .cloned()
+        .map(structured_value_input_to_domain_address)
+        .collect();
+    validate_addresses(addresses.iter().map(|a| (&a.value, a.preferred_at)))
+}
+
+/// Valide une liste de consentements en entrée : au plus un par canal et finalité.
+fn validate_consent_list(consents: &[ConsentInput]) -> Result<(), ValidationError> {
+    let consents: Vec<_> = consents
+        .iter()
+        .cloned()
+        .map(consent_input_to_domain)
+        .collect();
+    validate_consents(consents.iter().map(|c| &c.value))
 }
 
 /// Valide qu'une valeur string structurée est non vide (après trim).
-fn required_value_non_empty(field: &str, input: &StructuredValueStringInput) -> Result<(), ValidationError> {
+fn required_value_non_empty(
+    field: &str,
+    input: &StructuredValueStringInput,
+) -> Result<(), ValidationError> {
     if input.value.trim().is_empty() {
         return Err(ValidationError(format!(
             "{}: value est obligatoire et ne peut pas être vide",
@@ -60,20 +136,33 @@ fn required_value_non_empty(field: &str, input: &StructuredValueStringInput) ->
 }
 
 /// Valide le corps de la requête de création.
-pub fn validate_create_request(req: &CreateGuestRequest) -> Result<(), ValidationError> {
+pub fn validate_create_request(
+    req: &CreateGuestRequest,
+    region: Option<PhoneRegion>,
+) -> Result<(), ValidationError> {
     required_value_non_empty("first_name", &req.first_name)?;
     required_value_non_empty("last_name", &req.last_name)?;
     if let Some(ref mails) = req.mail {
-        validate_mail_list(mails)?;
+        validate_mail_list(mails, &[])?;
     }
     if let Some(ref phones) = req.phone {
-        validate_phone_list(phones)?;
+        validate_phone_list(phones, &[], region)?;
+    }
+    if let Some(ref addresses) = req.addresses {
+        validate_address_list(addresses)?;
+    }
+    if let Some(ref consents) = req.consents {
+        validate_consent_list(consents)?;
     }
     Ok(())
 }
 
-/// Valide le corps de la requête de mise à jour.
-pub fn validate_update_request(req: &UpdateGuestRequest) -> Result<(), ValidationError> {
+/// Valide le corps de la requête de mise à jour ; les valeurs déjà stockées sur `stored` ne sont pas recontrôlées.
+pub fn validate_update_request(
+    req: &UpdateGuestRequest,
+    stored: Option<&Guest>,
+    region: Option<PhoneRegion>,
+) -> Result<(), ValidationError> {
     if let Some(ref first) = req.first_name {
         required_value_non_empty("first_name", first)?;
     }
@@ -81,17 +170,288 @@ pub fn validate_update_request(req: &UpdateGuestRequest) -> Result<(), Validatio
         required_value_non_empty("last_name", last)?;
     }
     if let Some(ref mails) = req.mail {
-        validate_mail_list(mails)?;
+        validate_mail_list(mails, stored.map_or(&[], |g| &g.mail))?;
     }
     if let Some(ref phones) = req.phone {
-        validate_phone_list(phones)?;
+        validate_phone_list(phones, stored.map_or(&[], |g| &g.phone), region)?;
+    }
+    if let Some(ref addresses) = req.addresses {
+        validate_address_list(addresses)?;
+    }
+    if let Some(ref consents) = req.consents {
+        validate_consent_list(consents)?;
     }
     Ok(())
 }
 
+/// PUT d'un guest inexistant : la requête de mise à jour devient une requête de création (prénom et nom
+/// obligatoires), à valider ensuite comme un POST.
+pub fn create_request_from_update(
+    req: &UpdateGuestRequest,
+) -> Result<CreateGuestRequest, ValidationError> {
+    let required = |field: &str, input: &Option<StructuredValueStringInput>| {
+        input
+            .clone()
+            .ok_or_else(|| ValidationError(format!("{}: obligatoire pour créer le guest", field)))
+    };
+    Ok(CreateGuestRequest {
+        first_name: required("first_name", &req.first_name)?,
+        last_name: required("last_name", &req.last_name)?,
+        mail: req.mail.clone(),
+        phone: req.phone.clone(),
+        addresses: req.addresses.clone(),
+        consents: req.consents.clone(),
+        attributes: req.attributes.clone(),
+    })
+}
+
+/// Valide les listes d'un guest déjà construit (ex. après PATCH) : mêmes règles que les requêtes, les valeurs
+/// inchangées par rapport au guest stocké `stored` n'étant pas recontrôlées.
+pub fn validate_guest_lists(
+    guest: &Guest,
+    stored: Option<&Guest>,
+    region: Option<PhoneRegion>,
+) -> Result<(), ValidationError> {
+    validate_mail_values(
+        guest
+            .mail
+            .iter()
+            .map(|m| (m.value.as_str(), m.preferred_at)),
+        stored.map_or(&[], |g| &g.mail),
+    )?;
+    validate_phone_values(
+        guest
+            .phone
+            .iter()
+            .map(|p| (p.value.as_str(), p.preferred_at)),
+        stored.map_or(&[], |g| &g.phone),
+        region,
+    )?;
+    validate_addresses(guest.addresses.iter().map(|a| (&a.value, a.preferred_at)))?;
+    validate_consents(guest.consents.iter().map(|c| &c.value))
+}
+
 /// Parse l'id path en UUID ; retourne une ValidationError si le format est invalide (pour 400).
 pub fn parse_guest_id(id: &str) -> Result<uuid::Uuid, ValidationError> {
-    uuid::Uuid::parse_str(id).map_err(|_| {
-        ValidationError(format!("id invalide: '{}' n'est pas un UUID valide", id))
+    uuid::Uuid::parse_str(id)
+        .map_err(|_| ValidationError(format!("id invalide: '{}' n'est pas un UUID valide", id)))
+}
+
+/// Effacement : auteur de la demande non vide ; retourne l'auteur sans espaces autour.
+pub fn validate_erase_request(req: &EraseGuestRequest) -> Result<&str, ValidationError> {
+    let requester = req.requester.trim();
+    if requester.is_empty() {
+        return Err(ValidationError(
+            "requester: est obligatoire et ne peut pas être vide".into(),
+        ));
+    }
+    Ok(requester)
+}
+
+/// Ids d'une fusion : deux UUID valides et distincts (target, source).
+pub fn validate_merge_ids(
+    target: &str,
+    source: &str,
+) -> Result<(uuid::Uuid, uuid::Uuid), ValidationError> {
+    let target = parse_guest_id(target)?;
+    let source = parse_guest_id(source)?;
+    if target == source {
+        return Err(ValidationError(
+            "un guest ne peut pas être fusionné avec lui-même".into(),
+        ));
+    }
+    Ok((target, source))
+}
+
+/// Taille de page demandée (défaut si absente), bornée à MAX_PAGE_SIZE.
+pub(crate) fn page_limit(limit: Option<u32>) -> Result<u32, ValidationError> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if limit == 0 || limit > MAX_PAGE_SIZE {
+        return Err(ValidationError(format!(
+            "limit: doit être compris entre 1 et {}",
+            MAX_PAGE_SIZE
+        )));
+    }
+    Ok(limit)
+}
+
+/// Valide les paramètres de listing et les convertit en requête domaine (limite, curseur, plage de dates).
+pub fn validate_list_query(query: &ListGuestsQuery) -> Result<GuestListQuery, ValidationError> {
+    let limit = page_limit(query.limit)?;
+    if let (Some(after), Some(before)) = (query.updated_after, query.updated_before) {
+        if after >= before {
+            return Err(ValidationError(
+                "updated_after doit être antérieur à updated_before".into(),
+            ));
+        }
+    }
+    let after = query
+        .cursor
+        .as_deref()
+        .map(|c| {
+            decode_cursor(c).and_then(|position| {
+                uuid::Uuid::parse_str(&position)
+                    .map_err(|_| ValidationError(format!("cursor invalide: '{}'", c)))
+            })
+        })
+        .transpose()?;
+    Ok(GuestListQuery {
+        from: query.from.clone(),
+        updated_after: query.updated_after,
+        updated_before: query.updated_before,
+        has_mail: query.has_mail,
+        has_phone: query.has_phone,
+        after,
+        limit,
     })
 }
+
+/// Valide une recherche par coordonnée : exactement un critère (mail ou phone), non vide une fois normalisé.
+/// Un téléphone analysable est recherché sous sa forme E.164 (numéros sans indicatif lus dans `region`),
+/// un email analysable sous sa forme canonique (domaine punycode).
+pub fn validate_search_query(
+    query: &SearchGuestsQuery,
+    region: Option<PhoneRegion>,
+) -> Result<(ContactKind, String), ValidationError> {
+    let (kind, value) = match (&query.mail, &query.phone) {
+        (Some(mail), None) => (ContactKind::Mail, mail),
+        (None, Some(phone)) => (ContactKind::Phone, phone),
+        _ => {
+            return Err(ValidationError(
+                "recherche: renseigner exactement un paramètre parmi mail et phone".into(),
+            ))
+        }
+    };
+    if kind.normalize(value).is_empty() {
+        return Err(ValidationError(format!(
+            "{}: valeur de recherche vide",
+            kind.as_str()
+        )));
+    }
+    let value = match kind {
+        ContactKind::Phone => parse_phone(value, region).map_or_else(|_| value.clone(), |p| p.e164),
+        ContactKind::Mail => parse_email(value).unwrap_or_else(|_| value.clone()),
+    };
+    Ok((kind, value))
+}
+
+/// Valide les paramètres de joignabilité : canal obligatoire, finalité `marketing` par défaut.
+pub fn validate_contactability_query(
+    query: &ContactabilityQuery,
+) -> Result<(ConsentChannel, ConsentPurpose), ValidationError> {
+    let channel = match query.channel.as_str() {
+        "email" => ConsentChannel::Email,
+        "sms" => ConsentChannel::Sms,
+        "phone" => ConsentChannel::Phone,
+        "postal" => ConsentChannel::Postal,
+        other => {
+            return Err(ValidationError(format!(
+                "channel invalide: '{}' (email, sms, phone ou postal)",
+                other
+            )))
+        }
+    };
+    let purpose = match query.purpose.as_deref() {
+        None | Some("marketing") => ConsentPurpose::Marketing,
+        Some("transactional") => ConsentPurpose::Transactional,
+        Some(other) => {
+            return Err(ValidationError(format!(
+                "purpose invalide: '{}' (marketing ou transactional)",
+                other
+            )))
+        }
+    };
+    Ok((channel, purpose))
+}
+
+/// Valide les paramètres de pagination de l'historique.
+pub fn validate_history_query(query: &GuestHistoryQuery) -> Result<HistoryQuery, ValidationError> {
+    let limit = page_limit(query.limit)?;
+    let before = sequence_cursor(query.cursor.as_deref())?;
+    Ok(HistoryQuery { before, limit })
+}
+
+/// Valide les paramètres des doublons potentiels d'un guest et retourne le nombre maximum de candidats.
+pub fn validate_duplicates_query(query: &GuestDuplicatesQuery) -> Result<usize, ValidationError> {
+    Ok(page_limit(query.limit)? as usize)
+}
+
+/// Valide la pagination des paires du dernier scan de doublons.
+pub fn validate_duplicate_pairs_query(
+    query: &DuplicatePairsQuery,
+) -> Result<DuplicatePairQuery, ValidationError> {
+    let limit = page_limit(query.limit)?;
+    let after = sequence_cursor(query.cursor.as_deref())?;
+    Ok(DuplicatePairQuery { after, limit })
+}
+
+/// Décode un curseur opaque portant un numéro de séquence.
+fn sequence_cursor(cursor: Option<&str>) -> Result<Option<i64>, ValidationError> {
+    cursor
+        .map(|c| {
+            decode_cursor(c).and_then(|position| {
+                position
+                    .parse::<i64>()
+                    .map_err(|_| ValidationError(format!("cursor invalide: '{}'", c)))
+            })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ERASED_VALUE;
+    use crate::server::cursor::encode_cursor;
+
+    fn stored_guest() -> Guest {
+        let mut guest = Guest::new(uuid::Uuid::new_v4(), "Ana".into(), "Lopez".into());
+        guest.phone.push(StructuredValue::new("tel: 0612".into()));
+        guest.phone.push(StructuredValue::new(ERASED_VALUE.into()));
+        guest
+    }
+
+    #[test]
+    fn stored_phones_that_cannot_be_parsed_are_kept() {
+        let stored = stored_guest();
+        let mut updated = stored.clone();
+        updated.first_name = StructuredValue::new("Anna".into());
+        assert!(validate_guest_lists(&updated, Some(&stored), Some(PhoneRegion::FR)).is_ok());
+        assert!(validate_guest_lists(&updated, None, Some(PhoneRegion::FR)).is_err());
+    }
+
+    #[test]
+    fn added_or_changed_phones_are_checked() {
+        let stored = stored_guest();
+        let mut updated = stored.clone();
+        updated
+            .phone
+            .push(StructuredValue::new("06 12 34 56 78".into()));
+        assert!(validate_guest_lists(&updated, Some(&stored), Some(PhoneRegion::FR)).is_ok());
+
+        updated.phone[0].value = "tel: 0613".into();
+        let err = validate_guest_lists(&updated, Some(&stored), Some(PhoneRegion::FR)).unwrap_err();
+        assert!(err.0.starts_with("phone[0]"), "{err}");
+    }
+
+    #[test]
+    fn tampered_list_cursor_is_rejected() {
+        let id = uuid::Uuid::new_v4();
+        let query = |cursor: String| ListGuestsQuery {
+            cursor: Some(cursor),
+            ..Default::default()
+        };
+        let listed = validate_list_query(&query(encode_cursor(&id.to_string()))).unwrap();
+        assert_eq!(listed.after, Some(id));
+
+        // Curseur bien encodé mais dont la position a été modifiée : ni uuid ni numéro de séquence attendu.
+        let tampered = encode_cursor(&format!("{}x", id));
+        let err = validate_list_query(&query(tampered.clone())).unwrap_err();
+        assert_eq!(err.0, format!("cursor invalide: '{}'", tampered));
+        assert!(sequence_cursor(Some(&encode_cursor("12a"))).is_err());
+        assert_eq!(
+            sequence_cursor(Some(&encode_cursor("12"))).unwrap(),
+            Some(12)
+        );
+    }
+}
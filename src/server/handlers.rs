@@ -1,12 +1,9 @@
 //! Router HTTP et point d’entrée des handlers.
 //!
 //! Middlewares (ServiceBuilder) : TraceLayer → Timeout → ConcurrencyLimit → RequestId → Routes.
-//! Les handlers par ressource (items, guests) sont dans leurs modules dédiés.
+//! Les handlers par ressource (items, guests, attributs, segments, relations) sont dans leurs modules dédiés.
 
-use axum::{
-    routing::get,
-    Router,
-};
+use axum::{extract::DefaultBodyLimit, middleware, routing::get, Router};
 use std::time::Duration;
 use tower::{limit::ConcurrencyLimitLayer, ServiceBuilder};
 use tower_http::{
@@ -17,8 +14,21 @@ use tower_http::{
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
-use crate::server::guest::{create_guest, delete_guest, get_guest, update_guest};
+use crate::server::attribute::{delete_attribute, get_attribute, list_attributes, put_attribute};
+use crate::server::guest::{
+    create_guest, delete_guest, erase_guest, export_guests, get_guest, get_guest_contactability,
+    get_guest_duplicates, get_guest_history, get_guest_subject_access, import_guests,
+    list_duplicate_pairs, list_guests, merge_guest, patch_guest, restore_guest,
+    scan_guest_duplicates, search_guests, sync_guest, update_guest, IMPORT_BODY_LIMIT,
+};
+use crate::server::idempotency::idempotency;
 use crate::server::item::{create_item, get_item};
+use crate::server::relationship::{
+    add_guest_relationship, list_guest_relationships, remove_guest_relationship,
+};
+use crate::server::segment::{
+    count_segment, delete_segment, get_segment, list_segment_members, list_segments, put_segment,
+};
 use crate::server::state::AppState;
 
 /// GET / — Hello World
@@ -32,20 +42,91 @@ pub async fn hello() -> &'static str {
         crate::server::item::handlers::create_item,
         crate::server::item::handlers::get_item,
         crate::server::guest::handlers::create_guest,
+        crate::server::guest::handlers::list_guests,
+        crate::server::guest::handlers::search_guests,
         crate::server::guest::handlers::get_guest,
         crate::server::guest::handlers::update_guest,
+        crate::server::guest::handlers::patch_guest,
+        crate::server::guest::handlers::sync_guest,
+        crate::server::guest::handlers::get_guest_history,
         crate::server::guest::handlers::delete_guest,
+        crate::server::guest::handlers::restore_guest,
+        crate::server::guest::handlers::erase_guest,
+        crate::server::guest::handlers::merge_guest,
+        crate::server::guest::handlers::get_guest_contactability,
+        crate::server::guest::handlers::get_guest_subject_access,
+        crate::server::guest::handlers::get_guest_duplicates,
+        crate::server::guest::handlers::list_duplicate_pairs,
+        crate::server::guest::handlers::scan_guest_duplicates,
+        crate::server::guest::handlers::import_guests,
+        crate::server::guest::handlers::export_guests,
+        crate::server::relationship::handlers::list_guest_relationships,
+        crate::server::relationship::handlers::add_guest_relationship,
+        crate::server::relationship::handlers::remove_guest_relationship,
+        crate::server::attribute::handlers::list_attributes,
+        crate::server::attribute::handlers::get_attribute,
+        crate::server::attribute::handlers::put_attribute,
+        crate::server::attribute::handlers::delete_attribute,
+        crate::server::segment::handlers::list_segments,
+        crate::server::segment::handlers::get_segment,
+        crate::server::segment::handlers::put_segment,
+        crate::server::segment::handlers::delete_segment,
+        crate::server::segment::handlers::count_segment,
+        crate::server::segment::handlers::list_segment_members,
     ),
     components(schemas(
         crate::server::item::CreateItemRequest,
         crate::server::item::ItemResponse,
         crate::server::guest::CreateGuestRequest,
         crate::server::guest::UpdateGuestRequest,
+        crate::server::guest::PatchGuestRequest,
+        crate::server::guest::GuestPatchOperation,
+        crate::server::guest::GuestContactField,
         crate::server::guest::GuestResponse,
+        crate::server::guest::GuestListResponse,
+        crate::server::guest::GuestSyncResponse,
+        crate::server::guest::GuestUpdateResponse,
+        crate::server::guest::SurvivorshipDecisionResponse,
+        crate::server::guest::GuestRedirectResponse,
+        crate::server::guest::ContactabilityResponse,
+        crate::server::guest::GuestDuplicateResponse,
+        crate::server::guest::DuplicateReasonResponse,
+        crate::server::guest::DuplicatePairResponse,
+        crate::server::guest::DuplicatePairListResponse,
+        crate::server::guest::ImportGuestsResponse,
+        crate::server::guest::ImportRowResponse,
+        crate::server::guest::GuestHistoryResponse,
+        crate::server::guest::GuestHistoryEntryResponse,
+        crate::server::guest::SubjectAccessResponse,
+        crate::server::guest::EraseGuestRequest,
+        crate::server::guest::ErasureCertificateResponse,
+        crate::server::guest::MergedGuestAccessResponse,
+        crate::server::guest::OptOutEventResponse,
         crate::server::guest::StructuredValueStringInput,
         crate::server::guest::StructuredValueStringResponse,
-        crate::server::guest::StructuredValueBoolInput,
-        crate::server::guest::StructuredValueBoolResponse,
+        crate::server::guest::StructuredValuePhoneResponse,
+        crate::server::guest::StructuredValueJsonInput,
+        crate::server::guest::AddressInput,
+        crate::server::guest::AddressResponse,
+        crate::server::guest::StructuredValueAddressInput,
+        crate::server::guest::StructuredValueAddressResponse,
+        crate::server::guest::StructuredValueJsonResponse,
+        crate::server::guest::ConsentInput,
+        crate::server::guest::ConsentResponse,
+        crate::server::guest::ConsentChannelDto,
+        crate::server::guest::ConsentPurposeDto,
+        crate::server::guest::ConsentStatusDto,
+        crate::server::guest::LegalBasisDto,
+        crate::server::relationship::AddRelationshipRequest,
+        crate::server::relationship::RelationshipKindDto,
+        crate::server::relationship::RelationshipResponse,
+        crate::server::attribute::AttributeDefinitionRequest,
+        crate::server::attribute::AttributeDefinitionResponse,
+        crate::server::attribute::AttributeTypeDto,
+        crate::server::segment::SegmentRequest,
+        crate::server::segment::SegmentResponse,
+        crate::server::segment::SegmentCountResponse,
+        crate::server::segment::SegmentMembersResponse,
     )),
     info(
         title = "Hello World API",
@@ -54,7 +135,10 @@ pub async fn hello() -> &'static str {
     ),
     tags(
         (name = "items", description = "Items en mémoire"),
-        (name = "guests", description = "Guests en SQLite")
+        (name = "guests", description = "Guests en SQLite"),
+        (name = "relationships", description = "Relations entre guests (foyers, familles, groupes)"),
+        (name = "attributes", description = "Registre des attributs personnalisés des guests"),
+        (name = "segments", description = "Segments de guests (expressions de filtre enregistrées)")
     )
 )]
 struct ApiDoc;
@@ -62,39 +146,93 @@ struct ApiDoc;
 /// Construit le routeur Axum avec Swagger UI.
 pub fn router(state: AppState) -> Router {
     let middleware = ServiceBuilder::new()
-        .layer(
-            TraceLayer::new_for_http().make_span_with(
-                |req: &axum::http::Request<axum::body::Body>| {
-                    let trace_id = req
-                        .extensions()
-                        .get::<RequestId>()
-                        .and_then(|id: &RequestId| id.header_value().to_str().ok())
-                        .map(String::from)
-                        .filter(|s: &String| !s.is_empty())
-                        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
-                    tracing::info_span!("request", trace_id = %trace_id)
-                },
-            ),
-        )
+        .layer(TraceLayer::new_for_http().make_span_with(
+            |req: &axum::http::Request<axum::body::Body>| {
+                let trace_id = req
+                    .extensions()
+                    .get::<RequestId>()
+                    .and_then(|id: &RequestId| id.header_value().to_str().ok())
+                    .map(String::from)
+                    .filter(|s: &String| !s.is_empty())
+                    .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+                tracing::info_span!("request", trace_id = %trace_id)
+            },
+        ))
         .layer(HttpTimeoutLayer::with_status_code(
             axum::http::StatusCode::GATEWAY_TIMEOUT,
             Duration::from_secs(30),
         ))
         .layer(ConcurrencyLimitLayer::new(100))
-        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid::default()))
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
         .layer(PropagateRequestIdLayer::x_request_id());
 
+    // Créations rejouables via l'en-tête Idempotency-Key.
+    let idempotent = middleware::from_fn_with_state(state.clone(), idempotency);
+
     Router::new()
         .route("/", get(hello))
-        .route("/items", axum::routing::post(create_item))
+        .route(
+            "/items",
+            axum::routing::post(create_item).layer(idempotent.clone()),
+        )
         .route("/items/:id", get(get_item))
-        .route("/guests", axum::routing::post(create_guest))
+        .route(
+            "/guests",
+            axum::routing::post(create_guest)
+                .layer(idempotent)
+                .get(list_guests),
+        )
+        .route("/guests/search", get(search_guests))
+        .route(
+            "/guests/import",
+            axum::routing::post(import_guests).layer(DefaultBodyLimit::max(IMPORT_BODY_LIMIT)),
+        )
+        .route("/guests/export", get(export_guests))
+        .route("/guests/duplicates", get(list_duplicate_pairs))
+        .route(
+            "/guests/duplicates/scan",
+            axum::routing::post(scan_guest_duplicates),
+        )
         .route(
             "/guests/:id",
             get(get_guest)
                 .put(update_guest)
+                .patch(patch_guest)
                 .delete(delete_guest),
         )
+        .route("/guests/:id/sync", axum::routing::post(sync_guest))
+        .route("/guests/:id/history", get(get_guest_history))
+        .route("/guests/:id/contactability", get(get_guest_contactability))
+        .route("/guests/:id/subject-access", get(get_guest_subject_access))
+        .route("/guests/:id/duplicates", get(get_guest_duplicates))
+        .route("/guests/:id/restore", axum::routing::post(restore_guest))
+        .route("/guests/:id/erase", axum::routing::post(erase_guest))
+        .route(
+            "/guests/:target/merge/:source",
+            axum::routing::post(merge_guest),
+        )
+        .route(
+            "/guests/:id/relationships",
+            get(list_guest_relationships).post(add_guest_relationship),
+        )
+        .route(
+            "/guests/:id/relationships/:related_id",
+            axum::routing::delete(remove_guest_relationship),
+        )
+        .route("/attributes", get(list_attributes))
+        .route(
+            "/attributes/:name",
+            get(get_attribute)
+                .put(put_attribute)
+                .delete(delete_attribute),
+        )
+        .route("/segments", get(list_segments))
+        .route(
+            "/segments/:name",
+            get(get_segment).put(put_segment).delete(delete_segment),
+        )
+        .route("/segments/:name/count", get(count_segment))
+        .route("/segments/:name/members", get(list_segment_members))
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
         .layer(middleware)
         .with_state(state)
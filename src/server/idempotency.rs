@@ -0,0 +1,388 @@
+//! Middleware `Idempotency-Key` des créations (POST /guests, POST /items) : une requête retentée avec la même
+//! clé et le même corps reçoit la réponse d'origine (en-tête `Idempotent-Replayed: true`) au lieu de créer
+//! un doublon ; la même clé avec un autre corps est refusée (422). Les réponses 5xx ne sont pas enregistrées ;
+//! une réponse effacée avec la ressource créée (guest effacé ou supprimé) n'est plus rejouée (410).
+//! Une clé réservée est libérée si la requête n'aboutit pas (5xx, panique du handler, requête abandonnée).
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+use tracing::{error, info};
+
+use crate::domain::{
+    validate_idempotency_key, IdempotencyRepository, IdempotencyReservation, RepositoryError,
+    StoredResponse, ValidationError,
+};
+use crate::server::error::ApiError;
+use crate::server::state::AppState;
+use crate::store::Store;
+
+/// En-tête portant la clé choisie par le client.
+const IDEMPOTENCY_KEY: &str = "idempotency-key";
+/// En-tête ajouté à une réponse rejouée.
+const REPLAYED: &str = "idempotent-replayed";
+/// Taille maximale du corps lu pour calculer l'empreinte (limite par défaut des extracteurs axum).
+const MAX_BODY_LEN: usize = 2 * 1024 * 1024;
+/// En-têtes de réponse enregistrés et rejoués ; les autres (ex. `x-request-id`, `date`) sont propres à chaque requête.
+const REPLAYED_HEADERS: [&str; 3] = ["content-type", "location", "etag"];
+
+/// Clé réservée par la requête en cours : libérée à l'abandon (erreur, panique du handler, requête
+/// annulée par le client) tant que la réponse n'a pas été enregistrée.
+struct Reservation {
+    repository: Arc<dyn IdempotencyRepository>,
+    scope: String,
+    key: String,
+    settled: bool,
+}
+
+impl Reservation {
+    /// Libère la clé : la requête pourra être retentée.
+    async fn release(mut self) -> Result<(), RepositoryError> {
+        self.repository.release(&self.scope, &self.key).await?;
+        self.settled = true;
+        Ok(())
+    }
+
+    /// Enregistre la réponse ; la clé est libérée si l'enregistrement échoue.
+    async fn complete(
+        mut self,
+        resource_id: Option<&str>,
+        stored: &StoredResponse,
+    ) -> Result<(), RepositoryError> {
+        if let Err(e) = self
+            .repository
+            .complete(&self.scope, &self.key, resource_id, stored)
+            .await
+        {
+            error!(scope = %self.scope, key = %self.key, "idempotency: storing response failed: {e}");
+            return self.release().await;
+        }
+        self.settled = true;
+        Ok(())
+    }
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        if self.settled {
+            return;
+        }
+        let repository = Arc::clone(&self.repository);
+        let (scope, key) = (
+            std::mem::take(&mut self.scope),
+            std::mem::take(&mut self.key),
+        );
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            error!(scope = %scope, key = %key, "idempotency: abandoned key not released (no runtime)");
+            return;
+        };
+        runtime.spawn(async move {
+            match repository.release(&scope, &key).await {
+                Ok(()) => info!(scope = %scope, key = %key, "idempotency: abandoned key released"),
+                Err(e) => error!(scope = %scope, key = %key, "idempotency: releasing abandoned key failed: {e}"),
+            }
+        });
+    }
+}
+
+/// Empreinte SHA-256 (hex) du corps : JSON re-sérialisé (clés triées, sans espaces) pour qu'une différence
+/// de mise en forme ne compte pas ; octets bruts si le corps n'est pas du JSON.
+fn body_fingerprint(body: &[u8]) -> String {
+    let canonical = serde_json::from_slice::<serde_json::Value>(body)
+        .map(|json| json.to_string().into_bytes())
+        .unwrap_or_else(|_| body.to_vec());
+    Sha256::digest(&canonical)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Id de la ressource créée : champ `id` du corps JSON d'une réponse 2xx.
+fn created_resource_id(stored: &StoredResponse) -> Option<String> {
+    if !(200..300).contains(&stored.status) {
+        return None;
+    }
+    let body: serde_json::Value = serde_json::from_str(&stored.body).ok()?;
+    body.get("id")?.as_str().map(str::to_string)
+}
+
+/// Réponse enregistrée → réponse HTTP rejouée.
+fn replay(stored: StoredResponse) -> Response {
+    let status = StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK);
+    let mut response = (status, stored.body).into_response();
+    let headers = response.headers_mut();
+    for (name, value) in &stored.headers {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::try_from(name.as_str()),
+            HeaderValue::from_str(value),
+        ) {
+            headers.insert(name, value);
+        }
+    }
+    headers.insert(REPLAYED, HeaderValue::from_static("true"));
+    response
+}
+
+/// Applique l'idempotence si la requête porte un en-tête `Idempotency-Key` (sinon la requête passe telle quelle).
+pub async fn idempotency(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let repository = Arc::clone(&state.store.idempotency);
+    with_idempotency(
+        repository,
+        state.settings.idempotency_ttl,
+        request,
+        |request| next.run(request),
+    )
+    .await
+}
+
+/// Idempotence autour de `run` (le reste de la chaîne de traitement), clés conservées pendant `ttl`.
+async fn with_idempotency<F, Fut>(
+    repository: Arc<dyn IdempotencyRepository>,
+    ttl: chrono::Duration,
+    request: Request,
+    run: F,
+) -> Result<Response, ApiError>
+where
+    F: FnOnce(Request) -> Fut,
+    Fut: Future<Output = Response>,
+{
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY) else {
+        return Ok(run(request).await);
+    };
+    let key = key
+        .to_str()
+        .map_err(|_| {
+            ValidationError("Idempotency-Key: caractères ASCII visibles uniquement".into())
+        })?
+        .to_string();
+    validate_idempotency_key(&key)?;
+    let scope = format!("{} {}", request.method(), request.uri().path());
+
+    let (parts, body) = request.into_parts();
+    let body = to_bytes(body, MAX_BODY_LEN)
+        .await
+        .map_err(|e| ValidationError(format!("corps de requête illisible: {}", e)))?;
+    let fingerprint = body_fingerprint(&body);
+
+    let now = chrono::Utc::now();
+    let reservation = repository
+        .reserve(&scope, &key, &fingerprint, now, now - ttl)
+        .await?;
+    if let IdempotencyReservation::Existing(record) = reservation {
+        if record.fingerprint != fingerprint {
+            return Err(ApiError::Unprocessable(
+                "Idempotency-Key déjà utilisée avec un autre corps de requête".into(),
+            ));
+        }
+        if record.erased_at.is_some() {
+            return Err(ApiError::Gone(
+                "Idempotency-Key : la ressource créée par la requête d'origine a été effacée"
+                    .into(),
+            ));
+        }
+        let stored = record.response.ok_or_else(|| {
+            ApiError::Conflict(
+                "Idempotency-Key : la requête d'origine est en cours de traitement".into(),
+            )
+        })?;
+        info!(scope = %scope, key = %key, "idempotency: replaying stored response");
+        return Ok(replay(stored));
+    }
+
+    let reservation = Reservation {
+        repository,
+        scope,
+        key,
+        settled: false,
+    };
+    let response = run(Request::from_parts(parts, Body::from(body))).await;
+    if response.status().is_server_error() {
+        reservation.release().await?;
+        return Ok(response);
+    }
+    let (parts, body) = response.into_parts();
+    let body = to_bytes(body, usize::MAX)
+        .await
+        .map_err(|e| ApiError::Repository(RepositoryError::Other(e.to_string())))?;
+    let stored = StoredResponse {
+        status: parts.status.as_u16(),
+        headers: REPLAYED_HEADERS
+            .iter()
+            .filter_map(|name| {
+                Some((
+                    name.to_string(),
+                    parts.headers.get(*name)?.to_str().ok()?.to_string(),
+                ))
+            })
+            .collect(),
+        body: String::from_utf8_lossy(&body).into_owned(),
+    };
+    let resource_id = created_resource_id(&stored);
+    // La réponse est renvoyée même si son enregistrement échoue : la clé est alors libérée.
+    reservation
+        .complete(resource_id.as_deref(), &stored)
+        .await?;
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+/// Démarre la purge des clés expirées en tâche Tokio (un passage par intervalle, le premier immédiatement).
+pub fn spawn_idempotency_purge_task(store: Store, ttl: chrono::Duration, every: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(every);
+        loop {
+            ticker.tick().await;
+            let cutoff = chrono::Utc::now() - ttl;
+            match store.idempotency.purge_expired(cutoff).await {
+                Ok(0) => {}
+                Ok(purged) => info!(purged, %cutoff, "purge: expired idempotency keys removed"),
+                Err(e) => error!("purge idempotency keys: {e}"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::header;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+
+    /// Clés d'idempotence sur une base SQLite en mémoire.
+    async fn repository() -> Arc<dyn IdempotencyRepository> {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        Store::new(pool, None).idempotency
+    }
+
+    fn post(key: &str, body: &str) -> Request {
+        Request::post("/guests")
+            .header(IDEMPOTENCY_KEY, key)
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    /// Réponse de création : statut 201, en-têtes `location` et `x-request-id` (propre à la requête).
+    async fn created(id: &str) -> Response {
+        (
+            StatusCode::CREATED,
+            [
+                (header::LOCATION, format!("/guests/{id}")),
+                (HeaderName::from_static("x-request-id"), "req-1".to_string()),
+            ],
+            format!(r#"{{"id":"{id}"}}"#),
+        )
+            .into_response()
+    }
+
+    async fn send(
+        repository: &Arc<dyn IdempotencyRepository>,
+        request: Request,
+        id: &str,
+    ) -> Result<Response, ApiError> {
+        let ttl = chrono::Duration::hours(1);
+        with_idempotency(Arc::clone(repository), ttl, request, |_| created(id)).await
+    }
+
+    async fn body_of(response: Response) -> String {
+        String::from_utf8(
+            to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap()
+                .to_vec(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn retry_replays_the_stored_response() {
+        let repository = repository().await;
+        let first = send(&repository, post("k1", r#"{"a": 1, "b": 2}"#), "g1")
+            .await
+            .unwrap();
+        assert_eq!(first.headers().get(REPLAYED), None);
+
+        // Même corps à la mise en forme près : réponse d'origine, pas de nouvelle création.
+        let replayed = send(&repository, post("k1", r#"{"b":2,"a":1}"#), "g2")
+            .await
+            .unwrap();
+        assert_eq!(replayed.status(), StatusCode::CREATED);
+        assert_eq!(replayed.headers().get(REPLAYED).unwrap(), "true");
+        assert_eq!(
+            replayed.headers().get(header::LOCATION).unwrap(),
+            "/guests/g1"
+        );
+        assert_eq!(replayed.headers().get("x-request-id"), None);
+        assert_eq!(body_of(replayed).await, r#"{"id":"g1"}"#);
+    }
+
+    #[tokio::test]
+    async fn same_key_with_another_body_is_rejected() {
+        let repository = repository().await;
+        send(&repository, post("k1", r#"{"a":1}"#), "g1")
+            .await
+            .unwrap();
+        let err = send(&repository, post("k1", r#"{"a":2}"#), "g2")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ApiError::Unprocessable(_)));
+    }
+
+    #[tokio::test]
+    async fn key_in_flight_is_a_conflict_until_abandoned() {
+        let repository = repository().await;
+        let (started, in_flight) = tokio::sync::oneshot::channel();
+        let original = tokio::spawn(with_idempotency(
+            Arc::clone(&repository),
+            chrono::Duration::hours(1),
+            post("k1", "{}"),
+            |_| async move {
+                started.send(()).unwrap();
+                std::future::pending::<Response>().await
+            },
+        ));
+        in_flight.await.unwrap();
+        let err = send(&repository, post("k1", "{}"), "g1").await.unwrap_err();
+        assert!(matches!(err, ApiError::Conflict(_)));
+
+        // Requête d'origine abandonnée (client déconnecté) : la clé est libérée et la requête peut être retentée.
+        original.abort();
+        assert!(original.await.unwrap_err().is_cancelled());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let retried = send(&repository, post("k1", "{}"), "g1").await.unwrap();
+        assert_eq!(retried.status(), StatusCode::CREATED);
+        assert_eq!(retried.headers().get(REPLAYED), None);
+    }
+
+    #[tokio::test]
+    async fn server_error_releases_the_key() {
+        let repository = repository().await;
+        let ttl = chrono::Duration::hours(1);
+        let failed = with_idempotency(Arc::clone(&repository), ttl, post("k1", "{}"), |_| async {
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        });
+        assert_eq!(
+            failed.await.unwrap().status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        let retried = send(&repository, post("k1", "{}"), "g1").await.unwrap();
+        assert_eq!(retried.headers().get(REPLAYED), None);
+    }
+}
@@ -16,10 +16,15 @@ use crate::server::state::AppState;
 #[utoipa::path(
     post,
     path = "/items",
+    params(("Idempotency-Key" = Option<String>, Header,
+        description = "Clé choisie par le client : une requête retentée avec la même clé rejoue la réponse d'origine")),
     request_body = crate::server::item::dto::CreateItemRequest,
     responses(
-        (status = 201, description = "Item créé", body = crate::server::item::dto::ItemResponse),
-        (status = 400, description = "Requête invalide")
+        (status = 201, description = "Item créé (ou réponse d'origine rejouée, en-tête `Idempotent-Replayed`)",
+            body = crate::server::item::dto::ItemResponse),
+        (status = 400, description = "Requête invalide"),
+        (status = 409, description = "Requête d'origine portant la même Idempotency-Key encore en cours"),
+        (status = 422, description = "Idempotency-Key déjà utilisée avec un autre corps")
     ),
     tag = "items"
 )]
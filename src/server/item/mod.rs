@@ -5,4 +5,4 @@ pub mod handlers;
 mod mapper;
 
 pub use dto::{CreateItemRequest, ItemResponse};
-pub use handlers::{create_item, get_item};
\ No newline at end of file
+pub use handlers::{create_item, get_item};
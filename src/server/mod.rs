@@ -1,11 +1,21 @@
-//! Server HTTP : modules par ressource (item, guest), état, router.
+//! Server HTTP : modules par ressource (item, guest, attribute, segment, relationship), état, router.
 
+mod attribute;
+mod cursor;
 mod error;
+mod etag;
 mod guest;
 mod handlers;
+mod idempotency;
 mod item;
+mod relationship;
+mod segment;
 mod state;
+mod trace;
 
-pub use guest::spawn_guests_stream_tasks;
+pub use guest::{
+    spawn_guest_duplicate_scan_task, spawn_guest_purge_task, spawn_guests_stream_tasks,
+};
 pub use handlers::router;
-pub use state::AppState;
\ No newline at end of file
+pub use idempotency::spawn_idempotency_purge_task;
+pub use state::{AppState, Settings};
@@ -0,0 +1,45 @@
+//! DTOs API pour les relations entre guests.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// Nature de la relation : `child` et `booker_for` sont orientées (du guest vers `related_id`),
+/// les autres symétriques.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationshipKindDto {
+    Spouse,
+    /// `related_id` est l'enfant du guest.
+    Child,
+    Colleague,
+    /// Le guest réserve pour `related_id`.
+    BookerFor,
+    HouseholdMember,
+}
+
+/// Corps de requête pour relier un guest à un autre (le guest est dans le chemin).
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct AddRelationshipRequest {
+    /// UUID de l'autre guest.
+    pub related_id: String,
+    pub kind: RelationshipKindDto,
+}
+
+/// Filtre de suppression des relations entre deux guests.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RemoveRelationshipQuery {
+    /// Type de relation à supprimer (toutes les relations entre les deux guests si absent).
+    pub kind: Option<RelationshipKindDto>,
+}
+
+/// Réponse API : une relation, vue depuis le guest du chemin (`guest_id`), sauf relation orientée
+/// enregistrée depuis l'autre guest.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RelationshipResponse {
+    pub guest_id: String,
+    pub related_id: String,
+    pub kind: RelationshipKindDto,
+    pub created_at: DateTime<Utc>,
+}
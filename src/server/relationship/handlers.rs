@@ -0,0 +1,137 @@
+//! Handlers HTTP pour les relations entre guests (foyers, familles, groupes d'entreprise).
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::domain::ValidationError;
+use crate::server::error::ApiError;
+use crate::server::guest::parse_guest_id;
+use crate::server::relationship::dto::{AddRelationshipRequest, RemoveRelationshipQuery};
+use crate::server::relationship::mapper::{
+    new_relationship, relationship_kind_to_domain, relationship_to_response,
+};
+use crate::server::relationship::validation::parse_related_id;
+use crate::server::state::AppState;
+
+/// GET /guests/{id}/relationships — Relations du guest, dans les deux sens.
+#[utoipa::path(
+    get,
+    path = "/guests/{id}/relationships",
+    params(("id" = String, Path, description = "UUID du guest")),
+    responses(
+        (status = 200, description = "Relations du guest (par date de création)",
+            body = Vec<crate::server::relationship::dto::RelationshipResponse>),
+        (status = 400, description = "Id invalide (format UUID)"),
+        (status = 404, description = "Guest non trouvé")
+    ),
+    tag = "relationships"
+)]
+pub async fn list_guest_relationships(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let uuid = parse_guest_id(&id)?;
+    state
+        .store
+        .guests
+        .get_by_id(&uuid)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    let relationships = state.store.relationships.list(&uuid).await?;
+    let body: Vec<_> = relationships.iter().map(relationship_to_response).collect();
+    Ok((StatusCode::OK, Json(body)))
+}
+
+/// POST /guests/{id}/relationships — Relier le guest à un autre guest (sans effet si la relation existe déjà).
+#[utoipa::path(
+    post,
+    path = "/guests/{id}/relationships",
+    params(("id" = String, Path, description = "UUID du guest")),
+    request_body = crate::server::relationship::dto::AddRelationshipRequest,
+    responses(
+        (status = 201, description = "Relation créée", body = crate::server::relationship::dto::RelationshipResponse),
+        (status = 200, description = "Relation déjà enregistrée", body = crate::server::relationship::dto::RelationshipResponse),
+        (status = 400, description = "Id invalide, relation avec soi-même ou guest lié inexistant"),
+        (status = 404, description = "Guest non trouvé")
+    ),
+    tag = "relationships"
+)]
+pub async fn add_guest_relationship(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<AddRelationshipRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let uuid = parse_guest_id(&id)?;
+    let related_id = parse_related_id(&payload.related_id)?;
+    let relationship = new_relationship(uuid, related_id, payload.kind);
+    relationship.validate()?;
+    state
+        .store
+        .guests
+        .get_by_id(&uuid)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    if state.store.guests.get_by_id(&related_id).await?.is_none() {
+        return Err(
+            ValidationError(format!("related_id: guest '{}' non trouvé", related_id)).into(),
+        );
+    }
+    tracing::info!(guest_id = %uuid, related_id = %related_id, kind = relationship.kind.as_str(), "handler: adding relationship");
+    if state.store.relationships.add(&relationship).await? {
+        return Ok((
+            StatusCode::CREATED,
+            Json(relationship_to_response(&relationship)),
+        ));
+    }
+    // Relation déjà enregistrée : renvoyée telle que stockée (date de création d'origine).
+    let existing = state
+        .store
+        .relationships
+        .list(&uuid)
+        .await?
+        .into_iter()
+        .find(|r| {
+            r.kind == relationship.kind && (r.related_id == related_id || r.guest_id == related_id)
+        })
+        .unwrap_or(relationship);
+    Ok((StatusCode::OK, Json(relationship_to_response(&existing))))
+}
+
+/// DELETE /guests/{id}/relationships/{related_id} — Supprimer les relations entre deux guests (dans les deux sens).
+#[utoipa::path(
+    delete,
+    path = "/guests/{id}/relationships/{related_id}",
+    params(
+        ("id" = String, Path, description = "UUID du guest"),
+        ("related_id" = String, Path, description = "UUID de l'autre guest"),
+        crate::server::relationship::dto::RemoveRelationshipQuery
+    ),
+    responses(
+        (status = 204, description = "Relation(s) supprimée(s)"),
+        (status = 400, description = "Id invalide (format UUID) ou type de relation inconnu"),
+        (status = 404, description = "Aucune relation entre les deux guests")
+    ),
+    tag = "relationships"
+)]
+pub async fn remove_guest_relationship(
+    State(state): State<AppState>,
+    Path((id, related_id)): Path<(String, String)>,
+    Query(query): Query<RemoveRelationshipQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let uuid = parse_guest_id(&id)?;
+    let related_id = parse_related_id(&related_id)?;
+    let kind = query.kind.map(relationship_kind_to_domain);
+    let removed = state
+        .store
+        .relationships
+        .remove(&uuid, &related_id, kind)
+        .await?;
+    if removed == 0 {
+        return Err(ApiError::NotFound);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,50 @@
+//! Mappers domaine Relationship ↔ DTOs API.
+
+use chrono::Utc;
+
+use crate::domain::{Relationship, RelationshipKind};
+use crate::server::relationship::dto::{RelationshipKindDto, RelationshipResponse};
+
+fn relationship_kind_to_dto(kind: RelationshipKind) -> RelationshipKindDto {
+    match kind {
+        RelationshipKind::Spouse => RelationshipKindDto::Spouse,
+        RelationshipKind::Child => RelationshipKindDto::Child,
+        RelationshipKind::Colleague => RelationshipKindDto::Colleague,
+        RelationshipKind::BookerFor => RelationshipKindDto::BookerFor,
+        RelationshipKind::HouseholdMember => RelationshipKindDto::HouseholdMember,
+    }
+}
+
+pub fn relationship_kind_to_domain(kind: RelationshipKindDto) -> RelationshipKind {
+    match kind {
+        RelationshipKindDto::Spouse => RelationshipKind::Spouse,
+        RelationshipKindDto::Child => RelationshipKind::Child,
+        RelationshipKindDto::Colleague => RelationshipKind::Colleague,
+        RelationshipKindDto::BookerFor => RelationshipKind::BookerFor,
+        RelationshipKindDto::HouseholdMember => RelationshipKind::HouseholdMember,
+    }
+}
+
+/// Relation entre deux guests (ids déjà validés) créée maintenant.
+pub fn new_relationship(
+    guest_id: uuid::Uuid,
+    related_id: uuid::Uuid,
+    kind: RelationshipKindDto,
+) -> Relationship {
+    Relationship {
+        guest_id,
+        related_id,
+        kind: relationship_kind_to_domain(kind),
+        created_at: Utc::now(),
+    }
+}
+
+/// Domaine → DTO réponse API.
+pub fn relationship_to_response(relationship: &Relationship) -> RelationshipResponse {
+    RelationshipResponse {
+        guest_id: relationship.guest_id.to_string(),
+        related_id: relationship.related_id.to_string(),
+        kind: relationship_kind_to_dto(relationship.kind),
+        created_at: relationship.created_at,
+    }
+}
@@ -0,0 +1,9 @@
+//! Module serveur pour les relations entre guests : DTOs, mappers, validation, handlers.
+
+pub mod dto;
+pub mod handlers;
+mod mapper;
+mod validation;
+
+pub use dto::{AddRelationshipRequest, RelationshipKindDto, RelationshipResponse};
+pub use handlers::{add_guest_relationship, list_guest_relationships, remove_guest_relationship};
@@ -0,0 +1,13 @@
+//! Validation des requêtes relation : uuid de l'autre guest.
+
+use crate::domain::ValidationError;
+
+/// Parse l'uuid de l'autre guest (corps ou chemin).
+pub fn parse_related_id(related_id: &str) -> Result<uuid::Uuid, ValidationError> {
+    uuid::Uuid::parse_str(related_id.trim()).map_err(|_| {
+        ValidationError(format!(
+            "related_id invalide: '{}' n'est pas un UUID valide",
+            related_id
+        ))
+    })
+}
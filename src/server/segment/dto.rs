@@ -0,0 +1,49 @@
+//! DTOs API pour les segments de guests.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::server::guest::GuestResponse;
+
+/// Corps de requête pour créer ou remplacer un segment (le nom est dans le chemin).
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct SegmentRequest {
+    /// Expression de filtre, ex. `mail.preferred_at exists and not consents.email = "opted_out" and updated_at >= now-90d`.
+    pub expression: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Paramètres de pagination des membres d'un segment.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+pub struct SegmentMembersQuery {
+    /// Taille de page (défaut 50, max 200).
+    pub limit: Option<u32>,
+    /// Curseur opaque renvoyé par la page précédente.
+    pub cursor: Option<String>,
+}
+
+/// Réponse API : un segment.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SegmentResponse {
+    pub name: String,
+    pub expression: String,
+    pub description: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Réponse API : nombre de guests du segment.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SegmentCountResponse {
+    pub count: u64,
+}
+
+/// Réponse API : nombre total de membres et une page de guests (par uuid croissant).
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SegmentMembersResponse {
+    pub count: u64,
+    pub items: Vec<GuestResponse>,
+    /// Curseur de la page suivante (absent sur la dernière page).
+    pub next_cursor: Option<String>,
+}
@@ -0,0 +1,241 @@
+//! Handlers HTTP pour les segments de guests : enregistrement des expressions et évaluation.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+
+use crate::domain::{parse_segment, Guest, GuestListQuery, RepositoryError, SegmentExpr};
+use crate::server::cursor::encode_cursor;
+use crate::server::error::ApiError;
+use crate::server::guest::guest_to_response;
+use crate::server::segment::dto::{
+    SegmentCountResponse, SegmentMembersQuery, SegmentMembersResponse, SegmentRequest,
+};
+use crate::server::segment::mapper::{request_to_segment, segment_to_response};
+use crate::server::segment::validation::validate_members_query;
+use crate::server::state::AppState;
+
+/// Taille des pages de guests lues pendant l'évaluation.
+const SCAN_PAGE_SIZE: u32 = 200;
+
+/// Membres d'un segment : nombre total et une page (avec l'uuid du dernier membre s'il en reste d'autres).
+struct Evaluation {
+    count: u64,
+    members: Vec<Guest>,
+    next: Option<uuid::Uuid>,
+}
+
+/// Évalue l'expression sur tous les guests actifs (par uuid croissant) ; la page contient au plus `limit`
+/// membres d'uuid supérieur à `after`.
+async fn evaluate(
+    state: &AppState,
+    expr: &SegmentExpr,
+    after: Option<uuid::Uuid>,
+    limit: usize,
+) -> Result<Evaluation, RepositoryError> {
+    let now = Utc::now();
+    let mut evaluation = Evaluation {
+        count: 0,
+        members: Vec::new(),
+        next: None,
+    };
+    let mut more = false;
+    let mut position = None;
+    loop {
+        let page = state
+            .store
+            .guests
+            .list(GuestListQuery {
+                after: position,
+                limit: SCAN_PAGE_SIZE,
+                ..Default::default()
+            })
+            .await?;
+        for guest in page.guests {
+            if !expr.matches(&guest, now) {
+                continue;
+            }
+            evaluation.count += 1;
+            if after.is_some_and(|a| guest.id <= a) {
+                continue;
+            }
+            if evaluation.members.len() < limit {
+                evaluation.members.push(guest);
+            } else {
+                more = true;
+            }
+        }
+        match page.next {
+            Some(next) => position = Some(next),
+            None => break,
+        }
+    }
+    if more {
+        evaluation.next = evaluation.members.last().map(|g| g.id);
+    }
+    Ok(evaluation)
+}
+
+/// Expression analysée du segment enregistré sous ce nom (404 s'il n'existe pas).
+async fn segment_expr(state: &AppState, name: &str) -> Result<SegmentExpr, ApiError> {
+    let segment = state
+        .store
+        .segments
+        .get(name)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    Ok(parse_segment(&segment.expression)?)
+}
+
+/// GET /segments — Lister les segments (par nom).
+#[utoipa::path(
+    get,
+    path = "/segments",
+    responses(
+        (status = 200, description = "Segments enregistrés", body = Vec<crate::server::segment::dto::SegmentResponse>)
+    ),
+    tag = "segments"
+)]
+pub async fn list_segments(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let segments = state.store.segments.list().await?;
+    let body: Vec<_> = segments.iter().map(segment_to_response).collect();
+    Ok((StatusCode::OK, Json(body)))
+}
+
+/// GET /segments/{name} — Récupérer un segment.
+#[utoipa::path(
+    get,
+    path = "/segments/{name}",
+    params(("name" = String, Path, description = "Nom du segment")),
+    responses(
+        (status = 200, description = "Segment trouvé", body = crate::server::segment::dto::SegmentResponse),
+        (status = 404, description = "Segment non trouvé")
+    ),
+    tag = "segments"
+)]
+pub async fn get_segment(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let segment = state
+        .store
+        .segments
+        .get(&name)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    Ok((StatusCode::OK, Json(segment_to_response(&segment))))
+}
+
+/// PUT /segments/{name} — Créer ou remplacer un segment (l'expression est analysée avant l'enregistrement).
+#[utoipa::path(
+    put,
+    path = "/segments/{name}",
+    params(("name" = String, Path, description = "Nom du segment (minuscules, chiffres et _)")),
+    request_body = crate::server::segment::dto::SegmentRequest,
+    responses(
+        (status = 201, description = "Segment créé", body = crate::server::segment::dto::SegmentResponse),
+        (status = 200, description = "Segment remplacé", body = crate::server::segment::dto::SegmentResponse),
+        (status = 400, description = "Nom ou expression invalide")
+    ),
+    tag = "segments"
+)]
+pub async fn put_segment(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<SegmentRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let segment = request_to_segment(&name, &payload);
+    segment.validate()?;
+    tracing::info!(name = %name, "handler: saving segment");
+    let created = state.store.segments.save(&segment).await?;
+    let status = if created {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    Ok((status, Json(segment_to_response(&segment))))
+}
+
+/// DELETE /segments/{name} — Supprimer un segment.
+#[utoipa::path(
+    delete,
+    path = "/segments/{name}",
+    params(("name" = String, Path, description = "Nom du segment")),
+    responses(
+        (status = 204, description = "Segment supprimé"),
+        (status = 404, description = "Segment non trouvé")
+    ),
+    tag = "segments"
+)]
+pub async fn delete_segment(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    if !state.store.segments.delete(&name).await? {
+        return Err(ApiError::NotFound);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /segments/{name}/count — Nombre de guests actifs du segment.
+#[utoipa::path(
+    get,
+    path = "/segments/{name}/count",
+    params(("name" = String, Path, description = "Nom du segment")),
+    responses(
+        (status = 200, description = "Nombre de membres", body = crate::server::segment::dto::SegmentCountResponse),
+        (status = 404, description = "Segment non trouvé")
+    ),
+    tag = "segments"
+)]
+pub async fn count_segment(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let expr = segment_expr(&state, &name).await?;
+    let evaluation = evaluate(&state, &expr, None, 0).await?;
+    tracing::debug!(name = %name, count = evaluation.count, "handler: count_segment");
+    Ok((
+        StatusCode::OK,
+        Json(SegmentCountResponse {
+            count: evaluation.count,
+        }),
+    ))
+}
+
+/// GET /segments/{name}/members — Membres du segment (nombre total + pagination par curseur).
+#[utoipa::path(
+    get,
+    path = "/segments/{name}/members",
+    params(
+        ("name" = String, Path, description = "Nom du segment"),
+        crate::server::segment::dto::SegmentMembersQuery
+    ),
+    responses(
+        (status = 200, description = "Nombre de membres et page de guests", body = crate::server::segment::dto::SegmentMembersResponse),
+        (status = 400, description = "Paramètres invalides (limit, curseur)"),
+        (status = 404, description = "Segment non trouvé")
+    ),
+    tag = "segments"
+)]
+pub async fn list_segment_members(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(query): Query<SegmentMembersQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (after, limit) = validate_members_query(&query)?;
+    let expr = segment_expr(&state, &name).await?;
+    let evaluation = evaluate(&state, &expr, after, limit).await?;
+    Ok((
+        StatusCode::OK,
+        Json(SegmentMembersResponse {
+            count: evaluation.count,
+            items: evaluation.members.iter().map(guest_to_response).collect(),
+            next_cursor: evaluation.next.map(|id| encode_cursor(&id.to_string())),
+        }),
+    ))
+}
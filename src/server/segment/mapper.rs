@@ -0,0 +1,26 @@
+//! Mappers domaine Segment ↔ DTOs API.
+
+use chrono::Utc;
+
+use crate::domain::Segment;
+use crate::server::segment::dto::{SegmentRequest, SegmentResponse};
+
+/// Requête API + nom (chemin) → segment domaine ; la validation est faite par l'appelant.
+pub fn request_to_segment(name: &str, req: &SegmentRequest) -> Segment {
+    Segment {
+        name: name.to_string(),
+        expression: req.expression.trim().to_string(),
+        description: req.description.clone(),
+        updated_at: Utc::now(),
+    }
+}
+
+/// Domaine → DTO réponse API.
+pub fn segment_to_response(segment: &Segment) -> SegmentResponse {
+    SegmentResponse {
+        name: segment.name.clone(),
+        expression: segment.expression.clone(),
+        description: segment.description.clone(),
+        updated_at: segment.updated_at,
+    }
+}
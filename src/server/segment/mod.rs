@@ -0,0 +1,11 @@
+//! Module serveur pour les segments de guests : DTOs, mappers, validation, handlers (enregistrement et évaluation).
+
+pub mod dto;
+pub mod handlers;
+mod mapper;
+mod validation;
+
+pub use dto::{SegmentCountResponse, SegmentMembersResponse, SegmentRequest, SegmentResponse};
+pub use handlers::{
+    count_segment, delete_segment, get_segment, list_segment_members, list_segments, put_segment,
+};
@@ -0,0 +1,24 @@
+//! Validation des requêtes segment : pagination des membres.
+
+use crate::domain::ValidationError;
+use crate::server::cursor::decode_cursor;
+use crate::server::guest::page_limit;
+use crate::server::segment::dto::SegmentMembersQuery;
+
+/// Valide la pagination des membres : taille de page et curseur (uuid du dernier membre renvoyé).
+pub fn validate_members_query(
+    query: &SegmentMembersQuery,
+) -> Result<(Option<uuid::Uuid>, usize), ValidationError> {
+    let limit = page_limit(query.limit)?;
+    let after = query
+        .cursor
+        .as_deref()
+        .map(|c| {
+            decode_cursor(c).and_then(|position| {
+                uuid::Uuid::parse_str(&position)
+                    .map_err(|_| ValidationError(format!("cursor invalide: '{}'", c)))
+            })
+        })
+        .transpose()?;
+    Ok((after, limit as usize))
+}
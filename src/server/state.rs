@@ -1,17 +1,47 @@
-//! État partagé du serveur (injection du Store et du client NATS).
+//! État partagé du serveur (injection du Store, du client NATS et des réglages).
 
+use crate::domain::{PhoneRegion, SurvivorshipRules};
 use crate::store::Store;
 use async_nats::Client;
 
+/// Réglages du serveur issus de l'environnement.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    /// Délai pendant lequel un guest supprimé peut être restauré.
+    pub guest_restore_grace: chrono::Duration,
+    /// Région des numéros de téléphone saisis sans indicatif international.
+    pub default_phone_region: Option<PhoneRegion>,
+    /// Règles de survie appliquées par PUT /guests/{id}.
+    pub survivorship: SurvivorshipRules,
+    /// Durée pendant laquelle une `Idempotency-Key` rejoue la réponse d'origine.
+    pub idempotency_ttl: chrono::Duration,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            guest_restore_grace: chrono::Duration::days(30),
+            default_phone_region: Some(PhoneRegion::FR),
+            survivorship: SurvivorshipRules::default(),
+            idempotency_ttl: chrono::Duration::hours(24),
+        }
+    }
+}
+
 /// État de l'application : le serveur dépend du Store et du client NATS.
 pub struct AppState {
     pub store: Store,
     pub nats: Client,
+    pub settings: Settings,
 }
 
 impl AppState {
-    pub fn new(store: Store, nats: Client) -> Self {
-        Self { store, nats }
+    pub fn new(store: Store, nats: Client, settings: Settings) -> Self {
+        Self {
+            store,
+            nats,
+            settings,
+        }
     }
 }
 
@@ -20,6 +50,7 @@ impl Clone for AppState {
         Self {
             store: self.store.clone(),
             nats: self.nats.clone(),
+            settings: self.settings.clone(),
         }
     }
 }
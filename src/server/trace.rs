@@ -0,0 +1,15 @@
+//! Trace id de la requête (en-tête x-request-id) : propagé vers NATS et l'historique des guests.
+
+use tower_http::request_id::RequestId;
+
+use crate::domain::ChangeContext;
+
+/// Trace id de la requête HTTP (chaîne vide si l'en-tête n'est pas lisible).
+pub fn trace_id(request_id: &RequestId) -> String {
+    request_id.header_value().to_str().unwrap_or("").to_string()
+}
+
+/// Contexte d'écriture pour le repository, portant le trace id de la requête.
+pub fn change_context(request_id: &RequestId) -> ChangeContext {
+    ChangeContext::with_trace_id(trace_id(request_id))
+}
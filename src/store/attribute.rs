@@ -0,0 +1,149 @@
+//! Store SQLite du registre des attributs personnalisés : implémentation de AttributeRepository.
+
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::{FromRow, SqlitePool};
+
+use crate::domain::{AttributeDefinition, AttributeRepository, AttributeType, RepositoryError};
+
+/// Colonnes lues pour reconstruire une définition.
+const DEFINITION_COLUMNS: &str =
+    "name, attribute_type, enum_values, required, description, updated_at";
+
+/// Définition telle que lue depuis SQLite (valeurs permises en JSON).
+#[derive(Debug, FromRow)]
+struct DefinitionRow {
+    name: String,
+    attribute_type: String,
+    enum_values: String,
+    required: bool,
+    description: Option<String>,
+    updated_at: String,
+}
+
+impl DefinitionRow {
+    fn into_definition(self) -> Result<AttributeDefinition, String> {
+        let attribute_type = AttributeType::parse(&self.attribute_type)
+            .ok_or_else(|| format!("type d'attribut inconnu: {}", self.attribute_type))?;
+        Ok(AttributeDefinition {
+            name: self.name,
+            attribute_type,
+            enum_values: serde_json::from_str(&self.enum_values).map_err(|e| e.to_string())?,
+            required: self.required,
+            description: self.description,
+            updated_at: DateTime::parse_from_rfc3339(&self.updated_at)
+                .map_err(|e| e.to_string())?
+                .with_timezone(&Utc),
+        })
+    }
+}
+
+/// Store SQLite du registre des attributs (même base que les guests).
+pub struct SqliteAttributeStore {
+    pool: SqlitePool,
+}
+
+impl SqliteAttributeStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AttributeRepository for SqliteAttributeStore {
+    async fn list(&self) -> Result<Vec<AttributeDefinition>, RepositoryError> {
+        let rows = sqlx::query_as::<_, DefinitionRow>(&format!(
+            "SELECT {DEFINITION_COLUMNS} FROM guest_attribute_definitions ORDER BY name"
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        rows.into_iter()
+            .map(DefinitionRow::into_definition)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RepositoryError::Other)
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<AttributeDefinition>, RepositoryError> {
+        let row = sqlx::query_as::<_, DefinitionRow>(&format!(
+            "SELECT {DEFINITION_COLUMNS} FROM guest_attribute_definitions WHERE name = ?"
+        ))
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        row.map(DefinitionRow::into_definition)
+            .transpose()
+            .map_err(RepositoryError::Other)
+    }
+
+    async fn save(&self, definition: &AttributeDefinition) -> Result<bool, RepositoryError> {
+        let enum_values = serde_json::to_string(&definition.enum_values)
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        let updated_at = definition
+            .updated_at
+            .to_rfc3339_opts(SecondsFormat::Millis, true);
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        let existed = sqlx::query_scalar::<_, i64>(
+            "SELECT 1 FROM guest_attribute_definitions WHERE name = ?",
+        )
+        .bind(&definition.name)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?
+        .is_some();
+        sqlx::query(
+            r#"
+            INSERT INTO guest_attribute_definitions (name, attribute_type, enum_values, required, description, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT (name) DO UPDATE SET attribute_type = excluded.attribute_type,
+                enum_values = excluded.enum_values, required = excluded.required,
+                description = excluded.description, updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&definition.name)
+        .bind(definition.attribute_type.as_str())
+        .bind(&enum_values)
+        .bind(definition.required)
+        .bind(&definition.description)
+        .bind(&updated_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        tx.commit()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+
+        tracing::info!(name = %definition.name, created = !existed, "store: attribute definition saved");
+        Ok(!existed)
+    }
+
+    async fn delete(&self, name: &str) -> Result<bool, RepositoryError> {
+        let result = sqlx::query("DELETE FROM guest_attribute_definitions WHERE name = ?")
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        let deleted = result.rows_affected() > 0;
+        if deleted {
+            tracing::info!(name = %name, "store: attribute definition deleted");
+        }
+        Ok(deleted)
+    }
+
+    async fn usage(&self, name: &str) -> Result<u64, RepositoryError> {
+        // Nom validé par le registre (minuscules, chiffres, _) : sûr dans un chemin JSON entre guillemets.
+        let count = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM guests WHERE json_type(attributes, '$.\"' || ? || '\"') IS NOT NULL",
+        )
+        .bind(name)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        Ok(count as u64)
+    }
+}
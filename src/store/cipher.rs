@@ -0,0 +1,391 @@
+//! Chiffrement au repos des données personnelles des guests (AES-256-GCM) et index aveugle (HMAC-SHA256).
+//!
+//! Format stocké d'une valeur chiffrée : `enc:<key_id>:<base64(nonce || ciphertext)>`. L'id de clé permet
+//! la rotation : toutes les clés configurées déchiffrent, seule la clé courante chiffre. Une valeur sans
+//! préfixe `enc:` (données antérieures au chiffrement) est lue telle quelle.
+
+use std::collections::HashMap;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+
+/// Préfixe d'une valeur chiffrée.
+const SEALED_PREFIX: &str = "enc:";
+/// Taille du nonce AES-GCM (96 bits).
+const NONCE_LEN: usize = 12;
+/// Taille d'une clé AES-256.
+const KEY_LEN: usize = 32;
+/// Taille minimale de la clé HMAC de l'index aveugle.
+const MIN_INDEX_KEY_LEN: usize = 32;
+
+/// Champs chiffrés dans une valeur structurée (JSON) : la provenance et les dates restent lisibles
+/// pour les filtres SQL (`from`, nombre d'éléments).
+const SEALED_KEYS: [&str; 2] = ["value", "normalized"];
+/// Clé portant une `value` non textuelle (objet comme une adresse postale, nombre ou booléen d'un attribut)
+/// chiffrée sous forme de texte JSON.
+const SEALED_VALUE_KEY: &str = "sealed_value";
+
+/// Clés de chiffrement (par id) et clé de l'index aveugle.
+pub struct PiiCipher {
+    keys: HashMap<String, Aes256Gcm>,
+    current_key_id: String,
+    index_key: Vec<u8>,
+}
+
+impl std::fmt::Debug for PiiCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PiiCipher")
+            .field("key_ids", &self.keys.keys().collect::<Vec<_>>())
+            .field("current_key_id", &self.current_key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PiiCipher {
+    /// Construit le chiffrement depuis la configuration : `keys` au format `id:base64,id:base64`
+    /// (clés de 32 octets), id de la clé courante (vide = dernière de la liste) et clé HMAC en base64.
+    /// Retourne None si aucune clé n'est configurée (données stockées en clair).
+    pub fn parse(
+        keys: &str,
+        current_key_id: &str,
+        index_key: &str,
+    ) -> Result<Option<Self>, String> {
+        let mut parsed = HashMap::new();
+        let mut last_id = None;
+        for entry in keys.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (id, key) = entry
+                .split_once(':')
+                .ok_or_else(|| format!("clé '{}' : format attendu id:base64", entry))?;
+            let id = id.trim();
+            if id.is_empty() || id.contains(':') {
+                return Err(format!("id de clé invalide '{}'", id));
+            }
+            let key = STANDARD
+                .decode(key.trim())
+                .map_err(|e| format!("clé '{}' : base64 invalide ({})", id, e))?;
+            if key.len() != KEY_LEN {
+                return Err(format!("clé '{}' : {} octets attendus", id, KEY_LEN));
+            }
+            let cipher = Aes256Gcm::new_from_slice(&key).map_err(|e| e.to_string())?;
+            if parsed.insert(id.to_string(), cipher).is_some() {
+                return Err(format!("id de clé en double '{}'", id));
+            }
+            last_id = Some(id.to_string());
+        }
+        let Some(last_id) = last_id else {
+            return Ok(None);
+        };
+        let current_key_id = match current_key_id.trim() {
+            "" => last_id,
+            id if parsed.contains_key(id) => id.to_string(),
+            id => {
+                return Err(format!(
+                    "clé courante '{}' absente des clés configurées",
+                    id
+                ))
+            }
+        };
+        let index_key = STANDARD
+            .decode(index_key.trim())
+            .map_err(|e| format!("clé d'index : base64 invalide ({})", e))?;
+        if index_key.len() < MIN_INDEX_KEY_LEN {
+            return Err(format!(
+                "clé d'index : au moins {} octets",
+                MIN_INDEX_KEY_LEN
+            ));
+        }
+        Ok(Some(Self {
+            keys: parsed,
+            current_key_id,
+            index_key,
+        }))
+    }
+
+    /// Id de la clé utilisée pour chiffrer.
+    pub fn current_key_id(&self) -> &str {
+        &self.current_key_id
+    }
+
+    /// Chiffre `plaintext` avec la clé courante ; `aad` lie la valeur à son emplacement (guest, champ).
+    pub fn seal(&self, plaintext: &str, aad: &str) -> Result<String, String> {
+        let cipher = &self.keys[&self.current_key_id];
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext.as_bytes(),
+                    aad: aad.as_bytes(),
+                },
+            )
+            .map_err(|_| "chiffrement impossible".to_string())?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend(ciphertext);
+        Ok(format!(
+            "{SEALED_PREFIX}{}:{}",
+            self.current_key_id,
+            STANDARD.encode(sealed)
+        ))
+    }
+
+    /// Déchiffre une valeur stockée ; une valeur en clair (sans préfixe) est retournée telle quelle.
+    pub fn open(&self, stored: &str, aad: &str) -> Result<String, String> {
+        let Some(rest) = stored.strip_prefix(SEALED_PREFIX) else {
+            return Ok(stored.to_string());
+        };
+        let (key_id, data) = rest.split_once(':').ok_or("valeur chiffrée mal formée")?;
+        let cipher = self
+            .keys
+            .get(key_id)
+            .ok_or_else(|| format!("clé de chiffrement inconnue '{}'", key_id))?;
+        let data = STANDARD.decode(data).map_err(|e| e.to_string())?;
+        if data.len() < NONCE_LEN {
+            return Err("valeur chiffrée tronquée".into());
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        let plaintext = cipher
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: aad.as_bytes(),
+                },
+            )
+            .map_err(|_| format!("déchiffrement impossible (clé '{}')", key_id))?;
+        String::from_utf8(plaintext).map_err(|e| e.to_string())
+    }
+
+    /// Index aveugle d'une valeur normalisée : HMAC-SHA256 (hex) de `kind` et de la valeur.
+    pub fn blind_index(&self, kind: &str, normalized: &str) -> String {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&self.index_key)
+            .expect("HMAC accepte toute taille de clé");
+        mac.update(kind.as_bytes());
+        mac.update(&[0]);
+        mac.update(normalized.as_bytes());
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+}
+
+/// Sens de la transformation appliquée par `map_sealed_keys`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Seal,
+    Open,
+}
+
+/// Applique `f` aux champs chiffrables (`value`, `normalized`) d'une valeur structurée ou d'une liste.
+/// Une `value` non textuelle est chiffrée en texte JSON sous `sealed_value`, puis restaurée à l'ouverture.
+fn map_sealed_keys(
+    json: &mut Value,
+    direction: Direction,
+    f: &impl Fn(&str) -> Result<String, String>,
+) -> Result<(), String> {
+    match json {
+        Value::Array(items) => {
+            for item in items {
+                map_sealed_keys(item, direction, f)?;
+            }
+        }
+        Value::Object(map) => {
+            for key in SEALED_KEYS {
+                if let Some(Value::String(s)) = map.get_mut(key) {
+                    *s = f(s)?;
+                }
+            }
+            match direction {
+                Direction::Seal => {
+                    if let Some(value) = map.get("value").filter(|v| !v.is_string() && !v.is_null())
+                    {
+                        let sealed = f(&value.to_string())?;
+                        map.remove("value");
+                        map.insert(SEALED_VALUE_KEY.to_string(), Value::String(sealed));
+                    }
+                }
+                Direction::Open => {
+                    if let Some(Value::String(sealed)) = map.remove(SEALED_VALUE_KEY) {
+                        let value =
+                            serde_json::from_str(&f(&sealed)?).map_err(|e| e.to_string())?;
+                        map.insert("value".to_string(), value);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Données personnelles au repos : chiffrées si un `PiiCipher` est configuré, en clair sinon.
+#[derive(Debug, Clone, Default)]
+pub struct Pii {
+    cipher: Option<std::sync::Arc<PiiCipher>>,
+}
+
+impl Pii {
+    pub fn new(cipher: Option<PiiCipher>) -> Self {
+        Self {
+            cipher: cipher.map(std::sync::Arc::new),
+        }
+    }
+
+    /// Id de la clé courante (None si non chiffré).
+    pub fn current_key_id(&self) -> Option<&str> {
+        self.cipher.as_deref().map(PiiCipher::current_key_id)
+    }
+
+    /// Chiffre les champs `value` / `normalized` d'une valeur structurée (ou d'une liste) en JSON.
+    pub fn seal_json(&self, mut json: Value, aad: &str) -> Result<Value, String> {
+        if let Some(cipher) = &self.cipher {
+            map_sealed_keys(&mut json, Direction::Seal, &|s| cipher.seal(s, aad))?;
+        }
+        Ok(json)
+    }
+
+    /// Déchiffre les champs `value` / `normalized` (les valeurs en clair sont laissées telles quelles).
+    pub fn open_json(&self, mut json: Value, aad: &str) -> Result<Value, String> {
+        if let Some(cipher) = &self.cipher {
+            map_sealed_keys(&mut json, Direction::Open, &|s| cipher.open(s, aad))?;
+        }
+        Ok(json)
+    }
+
+    /// Chiffre un texte (ex. réponse enregistrée contenant des données personnelles).
+    pub fn seal_text(&self, text: &str, aad: &str) -> Result<String, String> {
+        match &self.cipher {
+            Some(cipher) => cipher.seal(text, aad),
+            None => Ok(text.to_string()),
+        }
+    }
+
+    /// Déchiffre un texte (un texte en clair est renvoyé tel quel).
+    pub fn open_text(&self, stored: &str, aad: &str) -> Result<String, String> {
+        match &self.cipher {
+            Some(cipher) => cipher.open(stored, aad),
+            None => Ok(stored.to_string()),
+        }
+    }
+
+    /// Valeur indexée d'une coordonnée normalisée : index aveugle si chiffré, sinon la valeur elle-même.
+    pub fn index(&self, kind: &str, normalized: &str) -> String {
+        match &self.cipher {
+            Some(cipher) => cipher.blind_index(kind, normalized),
+            None => normalized.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const INDEX_KEY: &str = "aWlpaWlpaWlpaWlpaWlpaWlpaWlpaWlpaWlpaWlpaWk=";
+
+    fn key(byte: u8) -> String {
+        STANDARD.encode([byte; KEY_LEN])
+    }
+
+    fn cipher(keys: &str, current_key_id: &str) -> PiiCipher {
+        PiiCipher::parse(keys, current_key_id, INDEX_KEY)
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn seal_then_open_returns_the_plaintext() {
+        let cipher = cipher(&format!("1:{}", key(1)), "");
+        let sealed = cipher.seal("ana@example.com", "g1:mail").unwrap();
+        assert!(sealed.starts_with("enc:1:"));
+        assert!(!sealed.contains("ana@example.com"));
+        assert_ne!(sealed, cipher.seal("ana@example.com", "g1:mail").unwrap());
+        assert_eq!(cipher.open(&sealed, "g1:mail").unwrap(), "ana@example.com");
+        assert!(cipher.open(&sealed, "g2:mail").is_err());
+        assert_eq!(cipher.open("en clair", "g1:mail").unwrap(), "en clair");
+    }
+
+    #[test]
+    fn seal_json_then_open_json_restores_the_structured_values() {
+        let pii = Pii::new(Some(cipher(&format!("1:{}", key(1)), "")));
+        let original = json!([
+            {"value": "06 12 34 56 78", "normalized": "+33612345678", "from": "crm", "updated_at": "2025-03-01T00:00:00Z"},
+            {"value": {"lines": ["1 rue de la Paix"], "city": "Paris", "country": "FR"}, "from": null},
+            {"value": 42},
+            {"value": null}
+        ]);
+        let sealed = pii.seal_json(original.clone(), "g1:phone").unwrap();
+        assert_eq!(sealed[0]["from"], "crm");
+        assert!(sealed[0]["value"]
+            .as_str()
+            .unwrap()
+            .starts_with(SEALED_PREFIX));
+        assert!(sealed[0]["normalized"]
+            .as_str()
+            .unwrap()
+            .starts_with(SEALED_PREFIX));
+        assert!(sealed[1].get("value").is_none());
+        assert!(sealed[1][SEALED_VALUE_KEY]
+            .as_str()
+            .unwrap()
+            .starts_with(SEALED_PREFIX));
+        assert!(sealed[2][SEALED_VALUE_KEY].is_string());
+        assert_eq!(sealed[3]["value"], Value::Null);
+        assert_eq!(pii.open_json(sealed, "g1:phone").unwrap(), original);
+
+        let clear = Pii::default();
+        assert_eq!(
+            clear.seal_json(original.clone(), "g1:phone").unwrap(),
+            original
+        );
+        assert_eq!(
+            pii.open_json(original.clone(), "g1:phone").unwrap(),
+            original
+        );
+    }
+
+    #[test]
+    fn values_sealed_under_a_retired_key_still_open_after_rotation() {
+        let before = cipher(&format!("1:{}", key(1)), "");
+        let sealed = before.seal("Lopez", "g1:last_name").unwrap();
+
+        let after = cipher(&format!("1:{},2:{}", key(1), key(2)), "2");
+        assert_eq!(after.current_key_id(), "2");
+        assert_eq!(after.open(&sealed, "g1:last_name").unwrap(), "Lopez");
+        let resealed = after.seal("Lopez", "g1:last_name").unwrap();
+        assert!(resealed.starts_with("enc:2:"));
+        assert_eq!(after.open(&resealed, "g1:last_name").unwrap(), "Lopez");
+
+        let dropped = cipher(&format!("2:{}", key(2)), "");
+        assert!(dropped.open(&sealed, "g1:last_name").is_err());
+    }
+
+    #[test]
+    fn blind_index_is_deterministic_and_keyed() {
+        let a = cipher(&format!("1:{}", key(1)), "");
+        let rotated = cipher(&format!("1:{},2:{}", key(1), key(2)), "2");
+        let index = a.blind_index("mail", "ana@example.com");
+        assert_eq!(index, a.blind_index("mail", "ana@example.com"));
+        assert_eq!(index, rotated.blind_index("mail", "ana@example.com"));
+        assert_eq!(index.len(), 64);
+        assert_ne!(index, a.blind_index("phone", "ana@example.com"));
+        assert_ne!(index, a.blind_index("mail", "bob@example.com"));
+
+        let other_key = PiiCipher::parse(&format!("1:{}", key(1)), "", &key(9))
+            .unwrap()
+            .unwrap();
+        assert_ne!(index, other_key.blind_index("mail", "ana@example.com"));
+        assert_eq!(
+            Pii::default().index("mail", "ana@example.com"),
+            "ana@example.com"
+        );
+    }
+}
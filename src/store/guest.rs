@@ -1,9 +1,33 @@
 //! Store SQLite pour les guests : implémentation de GuestRepository.
 
 use async_trait::async_trait;
-use sqlx::{FromRow, SqlitePool};
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures_util::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::{FromRow, QueryBuilder, Sqlite, SqliteConnection, SqliteExecutor, SqlitePool};
 
-use crate::domain::{Guest, GuestRepository, RepositoryError, StructuredValue};
+use super::cipher::Pii;
+
+use crate::domain::{
+    diff_guests, is_pii_field, merge_guests, Address, ChangeContext, ChangeOperation, Consent,
+    ContactKind, DuplicatePair, DuplicatePairPage, DuplicatePairQuery, ErasureCertificate,
+    FieldChange, Guest, GuestAttributes, GuestHistoryEntry, GuestListQuery, GuestPage,
+    GuestRepository, HistoryPage, HistoryQuery, OptOutEvent, OptOutEventRecord, PhoneRegion,
+    RepositoryError, StructuredValue, PII_FIELDS,
+};
+
+/// Colonnes lues pour reconstruire un Guest.
+const GUEST_COLUMNS: &str =
+    "id, first_name, last_name, mail, phone, addresses, consents, attributes, version, erased_at";
+
+/// Nombre de lignes réécrites par transaction lors d'une rotation de clé.
+const REENCRYPT_BATCH_SIZE: i64 = 200;
+/// Guests réindexés par transaction au démarrage (`reindex_contacts`).
+const REINDEX_BATCH_SIZE: i64 = 200;
+
+/// Colonnes JSON contenant une liste (ou un objet, pour les attributs) de StructuredValue.
+const LIST_COLUMNS: [&str; 5] = ["mail", "phone", "addresses", "consents", "attributes"];
 
 /// Row telle que lue depuis SQLite (id + JSON en texte).
 #[derive(Debug, FromRow)]
@@ -13,100 +37,757 @@ struct GuestRow {
     last_name: String,
     mail: String,
     phone: String,
-    opt_outs: String,
+    addresses: String,
+    consents: String,
+    attributes: String,
+    version: i64,
+    erased_at: Option<String>,
 }
 
-#[async_trait]
-impl GuestRepository for SqliteGuestStore {
-    async fn create(&self, guest: Guest) -> Result<Guest, RepositoryError> {
+/// Entrée d'historique telle que lue depuis SQLite.
+#[derive(Debug, FromRow)]
+struct HistoryRow {
+    seq: i64,
+    guest_id: String,
+    operation: String,
+    field: String,
+    old_value: Option<String>,
+    new_value: Option<String>,
+    source: Option<String>,
+    changed_at: String,
+    trace_id: Option<String>,
+}
+
+/// Paire du dernier scan de doublons telle que lue depuis SQLite (raisons en JSON).
+#[derive(Debug, FromRow)]
+struct DuplicatePairRow {
+    seq: i64,
+    guest_id: String,
+    candidate_id: String,
+    score: f64,
+    reasons: String,
+}
+
+/// Événement opt-out conservé tel que lu depuis SQLite (canaux en JSON).
+#[derive(Debug, FromRow)]
+struct OptOutEventRow {
+    seq: i64,
+    guest_id: String,
+    channels: String,
+    reason: String,
+    trace_id: Option<String>,
+    published: bool,
+    occurred_at: String,
+}
+
+/// Certificat d'effacement tel que lu depuis SQLite (champs en JSON).
+#[derive(Debug, FromRow)]
+struct ErasureRow {
+    guest_id: String,
+    erased_at: String,
+    requester: String,
+    trace_id: Option<String>,
+    fields: String,
+}
+
+/// Valeurs des colonnes à écrire pour un guest (JSON en texte + date d'écriture).
+struct GuestColumns {
+    id: String,
+    first_name: String,
+    last_name: String,
+    mail: String,
+    phone: String,
+    addresses: String,
+    consents: String,
+    attributes: String,
+    updated_at: String,
+    last_name_index: Option<String>,
+}
+
+/// Format des dates stockées en colonne : longueur fixe pour que l'ordre texte soit l'ordre chronologique.
+fn timestamp_column(date: DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, RepositoryError> {
+    serde_json::to_string(value).map_err(|e| RepositoryError::Other(e.to_string()))
+}
+
+/// Données associées au chiffrement d'une valeur personnelle : lie le chiffré au guest et au champ.
+fn pii_aad(guest_id: &str, field: &str) -> String {
+    format!("{guest_id}:{field}")
+}
+
+/// JSON d'une valeur personnelle (valeur structurée ou liste), chiffrée si une clé est configurée.
+fn to_sealed_json<T: Serialize>(
+    pii: &Pii,
+    value: &T,
+    guest_id: &str,
+    field: &str,
+) -> Result<String, RepositoryError> {
+    let json = serde_json::to_value(value).map_err(|e| RepositoryError::Other(e.to_string()))?;
+    let sealed = pii
+        .seal_json(json, &pii_aad(guest_id, field))
+        .map_err(RepositoryError::Other)?;
+    to_json(&sealed)
+}
+
+/// JSON des attributs personnalisés : noms en clair (requêtes d'usage, filtre de provenance), valeur de chaque
+/// attribut chiffrée si une clé est configurée (liée au champ `attributes.<nom>`).
+fn to_sealed_attributes(
+    pii: &Pii,
+    attributes: &GuestAttributes,
+    guest_id: &str,
+) -> Result<String, RepositoryError> {
+    let mut sealed = serde_json::Map::new();
+    for (name, value) in attributes {
+        let json =
+            serde_json::to_value(value).map_err(|e| RepositoryError::Other(e.to_string()))?;
+        let value = pii
+            .seal_json(json, &pii_aad(guest_id, &format!("attributes.{name}")))
+            .map_err(RepositoryError::Other)?;
+        sealed.insert(name.clone(), value);
+    }
+    to_json(&sealed)
+}
+
+/// Lit les attributs personnalisés stockés (chiffrés ou en clair).
+fn from_sealed_attributes(
+    pii: &Pii,
+    text: &str,
+    guest_id: &str,
+) -> Result<GuestAttributes, String> {
+    let stored: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(text).map_err(|e| e.to_string())?;
+    stored
+        .into_iter()
+        .map(|(name, json)| {
+            let opened = pii.open_json(json, &pii_aad(guest_id, &format!("attributes.{name}")))?;
+            let value = serde_json::from_value(opened).map_err(|e| e.to_string())?;
+            Ok((name, value))
+        })
+        .collect()
+}
+
+/// Lit une valeur personnelle stockée (chiffrée ou en clair).
+fn from_sealed_json<T: DeserializeOwned>(
+    pii: &Pii,
+    text: &str,
+    guest_id: &str,
+    field: &str,
+) -> Result<T, String> {
+    let json: serde_json::Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let opened = pii.open_json(json, &pii_aad(guest_id, field))?;
+    serde_json::from_value(opened).map_err(|e| e.to_string())
+}
+
+/// Clé de blocage du scan de doublons : initiale du nom en minuscules (index aveugle si chiffré).
+/// Absente pour un nom vide ou effacé.
+fn last_name_index(pii: &Pii, guest: &Guest) -> Option<String> {
+    if guest.is_erased() {
+        return None;
+    }
+    let initial: String = guest
+        .last_name
+        .value
+        .trim()
+        .chars()
+        .next()
+        .map(|c| c.to_lowercase().collect())?;
+    Some(pii.index("last_name_initial", &initial))
+}
+
+impl GuestColumns {
+    fn from_guest(guest: &Guest, pii: &Pii) -> Result<Self, RepositoryError> {
         let id = guest.id.to_string();
-        let first_name_json =
-            serde_json::to_string(&guest.first_name).map_err(|e| RepositoryError::Other(e.to_string()))?;
-        let last_name_json =
-            serde_json::to_string(&guest.last_name).map_err(|e| RepositoryError::Other(e.to_string()))?;
-        let mail_json =
-            serde_json::to_string(&guest.mail).map_err(|e| RepositoryError::Other(e.to_string()))?;
-        let phone_json =
-            serde_json::to_string(&guest.phone).map_err(|e| RepositoryError::Other(e.to_string()))?;
-        let opt_outs_json =
-            serde_json::to_string(&guest.opt_outs).map_err(|e| RepositoryError::Other(e.to_string()))?;
+        Ok(Self {
+            first_name: to_sealed_json(pii, &guest.first_name, &id, "first_name")?,
+            last_name: to_sealed_json(pii, &guest.last_name, &id, "last_name")?,
+            mail: to_sealed_json(pii, &guest.mail, &id, "mail")?,
+            phone: to_sealed_json(pii, &guest.phone, &id, "phone")?,
+            addresses: to_sealed_json(pii, &guest.addresses, &id, "addresses")?,
+            consents: to_json(&guest.consents)?,
+            attributes: to_sealed_attributes(pii, &guest.attributes, &id)?,
+            // Heure serveur de l'écriture, et non les dates fournies par le client (synchronisation antidatée,
+            // retrait d'un élément) : les filtres `updated_after` / `updated_since` voient chaque modification.
+            updated_at: timestamp_column(Utc::now()),
+            last_name_index: last_name_index(pii, guest),
+            id,
+        })
+    }
+}
+
+/// Réécrit la projection normalisée des coordonnées du guest (table guest_contacts) ;
+/// index aveugle des valeurs normalisées si une clé est configurée.
+async fn replace_contacts(
+    conn: &mut SqliteConnection,
+    pii: &Pii,
+    guest: &Guest,
+) -> Result<(), RepositoryError> {
+    let id = guest.id.to_string();
+    sqlx::query("DELETE FROM guest_contacts WHERE guest_id = ?")
+        .bind(&id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+    for (kind, normalized) in guest.normalized_contacts() {
+        sqlx::query("INSERT INTO guest_contacts (guest_id, kind, normalized) VALUES (?, ?, ?)")
+            .bind(&id)
+            .bind(kind.as_str())
+            .bind(pii.index(kind.as_str(), &normalized))
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+    }
+    Ok(())
+}
 
+/// Lit un guest non supprimé (via le pool ou dans une transaction).
+async fn fetch_guest<'e>(
+    executor: impl SqliteExecutor<'e>,
+    pii: &Pii,
+    id: &str,
+) -> Result<Option<Guest>, RepositoryError> {
+    let row = sqlx::query_as::<_, GuestRow>(&format!(
+        "SELECT {GUEST_COLUMNS} FROM guests WHERE id = ? AND deleted_at IS NULL"
+    ))
+    .bind(id)
+    .fetch_optional(executor)
+    .await
+    .map_err(|e| RepositoryError::Other(e.to_string()))?;
+    row.map(|row| row.into_guest(pii))
+        .transpose()
+        .map_err(RepositoryError::Other)
+}
+
+/// Valeur d'historique en colonne : chiffrée pour les champs personnels si une clé est configurée.
+fn history_value_column(
+    pii: &Pii,
+    value: Option<&serde_json::Value>,
+    guest_id: &str,
+    field: &str,
+) -> Result<Option<String>, RepositoryError> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    if !is_pii_field(field) {
+        return to_json(value).map(Some);
+    }
+    let sealed = pii
+        .seal_json(value.clone(), &pii_aad(guest_id, field))
+        .map_err(RepositoryError::Other)?;
+    to_json(&sealed).map(Some)
+}
+
+/// Ajoute les modifications d'une écriture à l'historique du guest (table guest_history).
+async fn append_history(
+    conn: &mut SqliteConnection,
+    pii: &Pii,
+    guest_id: &uuid::Uuid,
+    operation: ChangeOperation,
+    changes: &[FieldChange],
+    ctx: &ChangeContext,
+) -> Result<(), RepositoryError> {
+    let guest_id = guest_id.to_string();
+    let changed_at = timestamp_column(Utc::now());
+    for change in changes {
+        let old_value =
+            history_value_column(pii, change.old_value.as_ref(), &guest_id, &change.field)?;
+        let new_value =
+            history_value_column(pii, change.new_value.as_ref(), &guest_id, &change.field)?;
         sqlx::query(
             r#"
-            INSERT INTO guests (id, first_name, last_name, mail, phone, opt_outs)
-            VALUES (?, ?, ?, ?, ?, ?)
+            INSERT INTO guest_history (guest_id, operation, field, old_value, new_value, source, changed_at, trace_id)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
-        .bind(&id)
-        .bind(&first_name_json)
-        .bind(&last_name_json)
-        .bind(&mail_json)
-        .bind(&phone_json)
-        .bind(&opt_outs_json)
-        .execute(&self.pool)
+        .bind(&guest_id)
+        .bind(operation.as_str())
+        .bind(&change.field)
+        .bind(&old_value)
+        .bind(&new_value)
+        .bind(&change.from)
+        .bind(&changed_at)
+        .bind(&ctx.trace_id)
+        .execute(&mut *conn)
         .await
         .map_err(|e| RepositoryError::Other(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Efface la réponse d'idempotence enregistrée pour la création du guest (elle contient ses données
+/// personnelles) : la clé est conservée jusqu'à expiration mais un rejeu est refusé.
+async fn scrub_idempotency_keys(
+    conn: &mut SqliteConnection,
+    guest_id: &str,
+) -> Result<(), RepositoryError> {
+    sqlx::query(
+        "UPDATE idempotency_keys SET status = NULL, headers = NULL, body = NULL, erased_at = ? \
+         WHERE resource_id = ? AND erased_at IS NULL",
+    )
+    .bind(timestamp_column(Utc::now()))
+    .bind(guest_id)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| RepositoryError::Other(e.to_string()))?;
+    Ok(())
+}
+
+/// Version stockée d'un guest (None si le guest n'existe pas ou est supprimé).
+async fn stored_version(
+    conn: &mut SqliteConnection,
+    id: &str,
+) -> Result<Option<i64>, RepositoryError> {
+    sqlx::query_scalar::<_, i64>("SELECT version FROM guests WHERE id = ? AND deleted_at IS NULL")
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))
+}
+
+/// Insère un guest (version 1) avec sa projection guest_contacts et son historique de création.
+async fn insert_guest(
+    conn: &mut SqliteConnection,
+    pii: &Pii,
+    guest: &mut Guest,
+    ctx: &ChangeContext,
+) -> Result<(), RepositoryError> {
+    if try_insert_guest(conn, pii, guest, ctx).await? {
+        Ok(())
+    } else {
+        Err(RepositoryError::Other(format!(
+            "guest {} déjà existant",
+            guest.id
+        )))
+    }
+}
+
+/// Insère un guest (version 1) sauf si un guest (même supprimé) porte déjà son uuid ; retourne `false`
+/// sans rien écrire dans ce cas.
+async fn try_insert_guest(
+    conn: &mut SqliteConnection,
+    pii: &Pii,
+    guest: &mut Guest,
+    ctx: &ChangeContext,
+) -> Result<bool, RepositoryError> {
+    let mut inserted = guest.clone();
+    inserted.version = 1;
+    let columns = GuestColumns::from_guest(&inserted, pii)?;
+    let result = sqlx::query(
+        r#"
+        INSERT INTO guests (id, first_name, last_name, mail, phone, addresses, consents, attributes, updated_at,
+            last_name_index, version)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 1)
+        ON CONFLICT (id) DO NOTHING
+        "#,
+    )
+    .bind(&columns.id)
+    .bind(&columns.first_name)
+    .bind(&columns.last_name)
+    .bind(&columns.mail)
+    .bind(&columns.phone)
+    .bind(&columns.addresses)
+    .bind(&columns.consents)
+    .bind(&columns.attributes)
+    .bind(&columns.updated_at)
+    .bind(&columns.last_name_index)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| RepositoryError::Other(e.to_string()))?;
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    *guest = inserted;
+    replace_contacts(conn, pii, guest).await?;
+    let changes = diff_guests(None, Some(guest));
+    append_history(conn, pii, &guest.id, ChangeOperation::Create, &changes, ctx).await?;
+    Ok(true)
+}
+
+/// Réécrit les colonnes d'un guest si sa version stockée vaut `expected_version` (incrémente la version).
+/// Retourne le nombre de lignes modifiées (0 en cas de conflit ou de guest absent).
+async fn update_columns(
+    conn: &mut SqliteConnection,
+    columns: &GuestColumns,
+    expected_version: i64,
+) -> Result<u64, RepositoryError> {
+    let result = sqlx::query(
+        r#"
+        UPDATE guests SET first_name = ?, last_name = ?, mail = ?, phone = ?, addresses = ?, consents = ?,
+            attributes = ?, updated_at = ?, last_name_index = ?, version = version + 1
+        WHERE id = ? AND version = ? AND deleted_at IS NULL
+        "#,
+    )
+    .bind(&columns.first_name)
+    .bind(&columns.last_name)
+    .bind(&columns.mail)
+    .bind(&columns.phone)
+    .bind(&columns.addresses)
+    .bind(&columns.consents)
+    .bind(&columns.attributes)
+    .bind(&columns.updated_at)
+    .bind(&columns.last_name_index)
+    .bind(&columns.id)
+    .bind(expected_version)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| RepositoryError::Other(e.to_string()))?;
+    Ok(result.rows_affected())
+}
+
+/// Met à jour un guest si sa version stockée vaut `guest.version` (incrémente `guest.version`), avec sa
+/// projection guest_contacts et son historique de modification.
+async fn update_guest(
+    conn: &mut SqliteConnection,
+    pii: &Pii,
+    guest: &mut Guest,
+    columns: GuestColumns,
+    ctx: &ChangeContext,
+) -> Result<(), RepositoryError> {
+    let previous = fetch_guest(&mut *conn, pii, &columns.id)
+        .await?
+        .ok_or_else(|| RepositoryError::NotFound(columns.id.clone()))?;
+    if previous.version != guest.version {
+        return Err(RepositoryError::VersionConflict {
+            id: columns.id,
+            expected: guest.version,
+            actual: previous.version,
+        });
+    }
+
+    let updated = update_columns(conn, &columns, guest.version).await?;
+    if updated == 0 {
+        return Err(match stored_version(conn, &columns.id).await? {
+            Some(actual) => RepositoryError::VersionConflict {
+                id: columns.id,
+                expected: guest.version,
+                actual,
+            },
+            None => RepositoryError::NotFound(columns.id),
+        });
+    }
+    guest.version += 1;
+    replace_contacts(conn, pii, guest).await?;
+    let changes = diff_guests(Some(&previous), Some(guest));
+    append_history(conn, pii, &guest.id, ChangeOperation::Update, &changes, ctx).await
+}
+
+#[async_trait]
+impl GuestRepository for SqliteGuestStore {
+    async fn create(
+        &self,
+        mut guest: Guest,
+        ctx: &ChangeContext,
+    ) -> Result<Guest, RepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        insert_guest(&mut tx, &self.pii, &mut guest, ctx).await?;
+        tx.commit()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
 
         tracing::info!(guest_id = %guest.id, "store: guest created");
         Ok(guest)
     }
 
+    async fn create_many(
+        &self,
+        mut guests: Vec<Guest>,
+        ctx: &ChangeContext,
+    ) -> Result<Vec<Guest>, RepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        for guest in guests.iter_mut() {
+            insert_guest(&mut tx, &self.pii, guest, ctx).await?;
+        }
+        tx.commit()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+
+        tracing::info!(count = guests.len(), "store: guests created (batch)");
+        Ok(guests)
+    }
+
     async fn get_by_id(&self, id: &uuid::Uuid) -> Result<Option<Guest>, RepositoryError> {
-        let id_str = id.to_string();
-        let row = sqlx::query_as::<_, GuestRow>(
-            "SELECT id, first_name, last_name, mail, phone, opt_outs FROM guests WHERE id = ?",
-        )
-        .bind(&id_str)
+        let guest = fetch_guest(&self.pool, &self.pii, &id.to_string()).await?;
+        tracing::debug!(guest_id = %id, found = guest.is_some(), "store: guest get_by_id");
+        Ok(guest)
+    }
+
+    async fn get_including_deleted(
+        &self,
+        id: &uuid::Uuid,
+    ) -> Result<Option<Guest>, RepositoryError> {
+        let row = sqlx::query_as::<_, GuestRow>(&format!(
+            "SELECT {GUEST_COLUMNS} FROM guests WHERE id = ?"
+        ))
+        .bind(id.to_string())
         .fetch_optional(&self.pool)
         .await
         .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        row.map(|row| row.into_guest(&self.pii))
+            .transpose()
+            .map_err(RepositoryError::Other)
+    }
 
-        let guest = row.map(|r| r.into_guest()).transpose()
-            .map_err(|e| RepositoryError::Other(e))?;
-        tracing::debug!(guest_id = %id, found = guest.is_some(), "store: guest get_by_id");
+    async fn list(&self, query: GuestListQuery) -> Result<GuestPage, RepositoryError> {
+        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(format!(
+            "SELECT {GUEST_COLUMNS} FROM guests WHERE deleted_at IS NULL"
+        ));
+
+        if let Some(from) = &query.from {
+            qb.push(" AND (json_extract(first_name, '$.from') = ")
+                .push_bind(from.clone())
+                .push(" OR json_extract(last_name, '$.from') = ")
+                .push_bind(from.clone());
+            for column in LIST_COLUMNS {
+                qb.push(format!(
+                    " OR EXISTS (SELECT 1 FROM json_each(guests.{column}) WHERE json_extract(json_each.value, '$.from') = "
+                ))
+                .push_bind(from.clone())
+                .push(")");
+            }
+            qb.push(")");
+        }
+        if let Some(after) = query.updated_after {
+            qb.push(" AND updated_at >= ")
+                .push_bind(timestamp_column(after));
+        }
+        if let Some(before) = query.updated_before {
+            qb.push(" AND updated_at < ")
+                .push_bind(timestamp_column(before));
+        }
+        if let Some(has_mail) = query.has_mail {
+            qb.push(if has_mail {
+                " AND json_array_length(mail) > 0"
+            } else {
+                " AND json_array_length(mail) = 0"
+            });
+        }
+        if let Some(has_phone) = query.has_phone {
+            qb.push(if has_phone {
+                " AND json_array_length(phone) > 0"
+            } else {
+                " AND json_array_length(phone) = 0"
+            });
+        }
+        if let Some(after) = query.after {
+            qb.push(" AND id > ").push_bind(after.to_string());
+        }
+        // Une ligne de plus que la page pour savoir s'il existe une page suivante.
+        qb.push(" ORDER BY id LIMIT ")
+            .push_bind(i64::from(query.limit) + 1);
+
+        let rows = qb
+            .build_query_as::<GuestRow>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+
+        let has_more = rows.len() > query.limit as usize;
+        let guests = rows
+            .into_iter()
+            .take(query.limit as usize)
+            .map(|row| row.into_guest(&self.pii))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RepositoryError::Other)?;
+        let next = if has_more {
+            guests.last().map(|g| g.id)
+        } else {
+            None
+        };
+        tracing::debug!(count = guests.len(), has_more, "store: guest list");
+        Ok(GuestPage { guests, next })
+    }
+
+    fn export(&self, query: GuestListQuery) -> BoxStream<'static, Result<Guest, RepositoryError>> {
+        let pool = self.pool.clone();
+        let pii = self.pii.clone();
+        stream::try_unfold(Some(query), move |query| {
+            let store = SqliteGuestStore::new(pool.clone(), pii.clone());
+            async move {
+                let Some(query) = query else {
+                    return Ok(None);
+                };
+                let page = store.list(query.clone()).await?;
+                let next = page.next.map(|after| GuestListQuery {
+                    after: Some(after),
+                    ..query
+                });
+                Ok(Some((stream::iter(page.guests.into_iter().map(Ok)), next)))
+            }
+        })
+        .try_flatten()
+        .boxed()
+    }
+
+    async fn find_by_contact(
+        &self,
+        kind: ContactKind,
+        value: &str,
+    ) -> Result<Vec<Guest>, RepositoryError> {
+        let normalized = kind.normalize(value);
+        let rows = sqlx::query_as::<_, GuestRow>(&format!(
+            "SELECT {GUEST_COLUMNS} FROM guests WHERE deleted_at IS NULL AND id IN \
+             (SELECT guest_id FROM guest_contacts WHERE kind = ? AND normalized = ?) ORDER BY id"
+        ))
+        .bind(kind.as_str())
+        .bind(self.pii.index(kind.as_str(), &normalized))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+
+        let guests = rows
+            .into_iter()
+            .map(|row| row.into_guest(&self.pii))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RepositoryError::Other)?;
+        tracing::debug!(
+            kind = kind.as_str(),
+            count = guests.len(),
+            "store: guest find_by_contact"
+        );
+        Ok(guests)
+    }
+
+    async fn update(
+        &self,
+        mut guest: Guest,
+        ctx: &ChangeContext,
+    ) -> Result<Guest, RepositoryError> {
+        let columns = GuestColumns::from_guest(&guest, &self.pii)?;
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+
+        update_guest(&mut tx, &self.pii, &mut guest, columns, ctx).await?;
+        tx.commit()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        tracing::info!(guest_id = %guest.id, version = guest.version, "store: guest updated");
         Ok(guest)
     }
 
-    async fn update(&self, guest: Guest) -> Result<Guest, RepositoryError> {
+    async fn upsert(
+        &self,
+        mut guest: Guest,
+        ctx: &ChangeContext,
+    ) -> Result<(Guest, bool), RepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        // L'uuid d'un guest fusionné reste une redirection vers la cible : il n'est pas réattribué.
         let id = guest.id.to_string();
-        let first_name_json =
-            serde_json::to_string(&guest.first_name).map_err(|e| RepositoryError::Other(e.to_string()))?;
-        let last_name_json =
-            serde_json::to_string(&guest.last_name).map_err(|e| RepositoryError::Other(e.to_string()))?;
-        let mail_json =
-            serde_json::to_string(&guest.mail).map_err(|e| RepositoryError::Other(e.to_string()))?;
-        let phone_json =
-            serde_json::to_string(&guest.phone).map_err(|e| RepositoryError::Other(e.to_string()))?;
-        let opt_outs_json =
-            serde_json::to_string(&guest.opt_outs).map_err(|e| RepositoryError::Other(e.to_string()))?;
-
-        let result = sqlx::query(
-            r#"
-            UPDATE guests SET first_name = ?, last_name = ?, mail = ?, phone = ?, opt_outs = ? WHERE id = ?
-            "#,
+        let merged_into = sqlx::query_scalar::<_, String>(
+            "SELECT target_id FROM guest_redirects WHERE source_id = ?",
         )
-        .bind(&first_name_json)
-        .bind(&last_name_json)
-        .bind(&mail_json)
-        .bind(&phone_json)
-        .bind(&opt_outs_json)
         .bind(&id)
-        .execute(&self.pool)
+        .fetch_optional(&mut *tx)
         .await
         .map_err(|e| RepositoryError::Other(e.to_string()))?;
-
-        if result.rows_affected() == 0 {
-            return Err(RepositoryError::NotFound(id));
+        if let Some(target) = merged_into {
+            return Err(RepositoryError::Conflict(format!(
+                "guest {} fusionné dans {} : uuid non réutilisable",
+                id, target
+            )));
         }
-        tracing::info!(guest_id = %guest.id, "store: guest updated");
-        Ok(guest)
+        // L'insertion atomique décide : le guest est créé si aucun guest ne porte cet uuid, sinon mis à jour
+        // si sa version stockée vaut `guest.version` (0 : le guest était absent à la lecture, création perdue).
+        let created = try_insert_guest(&mut tx, &self.pii, &mut guest, ctx).await?;
+        if !created {
+            let columns = GuestColumns::from_guest(&guest, &self.pii)?;
+            match update_guest(&mut tx, &self.pii, &mut guest, columns, ctx).await {
+                // L'uuid d'un guest supprimé n'est pas réattribué (restauration).
+                Err(RepositoryError::NotFound(id)) => {
+                    let actual =
+                        sqlx::query_scalar::<_, i64>("SELECT version FROM guests WHERE id = ?")
+                            .bind(&id)
+                            .fetch_optional(&mut *tx)
+                            .await
+                            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+                    return Err(match actual {
+                        Some(actual) => RepositoryError::VersionConflict {
+                            id,
+                            expected: guest.version,
+                            actual,
+                        },
+                        None => RepositoryError::NotFound(id),
+                    });
+                }
+                result => result?,
+            }
+        }
+        tx.commit()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        tracing::info!(guest_id = %guest.id, version = guest.version, created, "store: guest upserted");
+        Ok((guest, created))
     }
 
-    async fn delete(&self, id: &uuid::Uuid) -> Result<Option<uuid::Uuid>, RepositoryError> {
+    async fn delete(
+        &self,
+        id: &uuid::Uuid,
+        expected_version: Option<i64>,
+        ctx: &ChangeContext,
+    ) -> Result<Option<uuid::Uuid>, RepositoryError> {
         let id_str = id.to_string();
-        let result = sqlx::query("DELETE FROM guests WHERE id = ?")
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        let Some(previous) = fetch_guest(&mut *tx, &self.pii, &id_str).await? else {
+            return Ok(None);
+        };
+        if let Some(expected) = expected_version.filter(|v| *v != previous.version) {
+            return Err(RepositoryError::VersionConflict {
+                id: id_str,
+                expected,
+                actual: previous.version,
+            });
+        }
+        // Suppression logique : la projection guest_contacts est conservée pour une éventuelle restauration.
+        let now = timestamp_column(Utc::now());
+        let result = sqlx::query(
+            "UPDATE guests SET deleted_at = ?, updated_at = ?, version = version + 1 WHERE id = ? AND deleted_at IS NULL",
+        )
+        .bind(&now)
+        .bind(&now)
+        .bind(&id_str)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        // Les relations ne sont pas conservées : elles ne seraient plus visibles depuis l'autre guest.
+        sqlx::query("DELETE FROM guest_relationships WHERE guest_id = ? OR related_id = ?")
+            .bind(&id_str)
             .bind(&id_str)
-            .execute(&self.pool)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        scrub_idempotency_keys(&mut tx, &id_str).await?;
+        let changes = diff_guests(Some(&previous), None);
+        append_history(
+            &mut tx,
+            &self.pii,
+            id,
+            ChangeOperation::Delete,
+            &changes,
+            ctx,
+        )
+        .await?;
+        tx.commit()
             .await
             .map_err(|e| RepositoryError::Other(e.to_string()))?;
 
@@ -118,39 +799,1312 @@ impl GuestRepository for SqliteGuestStore {
             Ok(None)
         }
     }
+
+    async fn restore(
+        &self,
+        id: &uuid::Uuid,
+        deleted_since: DateTime<Utc>,
+        ctx: &ChangeContext,
+    ) -> Result<Option<Guest>, RepositoryError> {
+        let id_str = id.to_string();
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        let row = sqlx::query_as::<_, GuestRow>(&format!(
+            "SELECT {GUEST_COLUMNS} FROM guests WHERE id = ? AND deleted_at IS NOT NULL AND deleted_at >= ?"
+        ))
+        .bind(&id_str)
+        .bind(timestamp_column(deleted_since))
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        let Some(mut guest) = row
+            .map(|row| row.into_guest(&self.pii))
+            .transpose()
+            .map_err(RepositoryError::Other)?
+        else {
+            return Ok(None);
+        };
+
+        sqlx::query("UPDATE guests SET deleted_at = NULL, updated_at = ?, version = version + 1 WHERE id = ?")
+            .bind(timestamp_column(Utc::now()))
+            .bind(&id_str)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        guest.version += 1;
+        let changes = diff_guests(None, Some(&guest));
+        append_history(
+            &mut tx,
+            &self.pii,
+            id,
+            ChangeOperation::Restore,
+            &changes,
+            ctx,
+        )
+        .await?;
+        tx.commit()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+
+        tracing::info!(guest_id = %id, version = guest.version, "store: guest restored");
+        Ok(Some(guest))
+    }
+
+    async fn erase(
+        &self,
+        id: &uuid::Uuid,
+        requester: &str,
+        ctx: &ChangeContext,
+    ) -> Result<Option<ErasureCertificate>, RepositoryError> {
+        let id_str = id.to_string();
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        let row = sqlx::query_as::<_, GuestRow>(&format!(
+            "SELECT {GUEST_COLUMNS} FROM guests WHERE id = ?"
+        ))
+        .bind(&id_str)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        let Some(mut guest) = row
+            .map(|row| row.into_guest(&self.pii))
+            .transpose()
+            .map_err(RepositoryError::Other)?
+        else {
+            return Ok(None);
+        };
+        // Effacement idempotent : un guest déjà effacé n'est pas réécrit, son certificat d'origine est retourné.
+        if guest.is_erased() {
+            let row = sqlx::query_as::<_, ErasureRow>(
+                "SELECT guest_id, erased_at, requester, trace_id, fields FROM guest_erasures \
+                 WHERE guest_id = ? ORDER BY seq LIMIT 1",
+            )
+            .bind(&id_str)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?
+            .ok_or_else(|| {
+                RepositoryError::Other(format!("guest {} effacé sans certificat", id_str))
+            })?;
+            return row
+                .into_certificate()
+                .map(Some)
+                .map_err(RepositoryError::Other);
+        }
+
+        // La ligne est conservée (id, consentements, provenance) ; suppression logique éventuelle inchangée.
+        let erased_at = Utc::now();
+        guest.erase(erased_at);
+        let columns = GuestColumns::from_guest(&guest, &self.pii)?;
+        sqlx::query(
+            "UPDATE guests SET first_name = ?, last_name = ?, mail = ?, phone = ?, addresses = ?, attributes = ?, \
+             updated_at = ?, last_name_index = ?, erased_at = ?, version = version + 1 WHERE id = ?",
+        )
+        .bind(&columns.first_name)
+        .bind(&columns.last_name)
+        .bind(&columns.mail)
+        .bind(&columns.phone)
+        .bind(&columns.addresses)
+        .bind(&columns.attributes)
+        .bind(&columns.updated_at)
+        .bind(&columns.last_name_index)
+        .bind(timestamp_column(erased_at))
+        .bind(&id_str)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        replace_contacts(&mut tx, &self.pii, &guest).await?;
+
+        // Valeurs passées dans l'historique du guest et des guests fusionnés dans lui ; opérations et dates conservées.
+        let mut ids = sqlx::query_scalar::<_, String>(
+            "SELECT source_id FROM guest_redirects WHERE target_id = ?",
+        )
+        .bind(&id_str)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        ids.push(id_str.clone());
+        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
+            "UPDATE guest_history SET old_value = NULL, new_value = NULL WHERE guest_id IN (",
+        );
+        let mut separated = qb.separated(", ");
+        for guest_id in &ids {
+            separated.push_bind(guest_id);
+        }
+        qb.push(") AND (field IN (");
+        let mut separated = qb.separated(", ");
+        for field in PII_FIELDS {
+            separated.push_bind(field);
+        }
+        qb.push(") OR field LIKE 'attributes.%')");
+        qb.build()
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        // Les raisons des paires de doublons citent les valeurs du guest.
+        sqlx::query("DELETE FROM guest_duplicates WHERE guest_id = ? OR candidate_id = ?")
+            .bind(&id_str)
+            .bind(&id_str)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        for guest_id in &ids {
+            scrub_idempotency_keys(&mut tx, guest_id).await?;
+        }
+
+        let changes: Vec<FieldChange> = PII_FIELDS
+            .iter()
+            .map(|field| FieldChange {
+                field: field.to_string(),
+                old_value: None,
+                new_value: None,
+                from: None,
+            })
+            .collect();
+        append_history(
+            &mut tx,
+            &self.pii,
+            id,
+            ChangeOperation::Erase,
+            &changes,
+            ctx,
+        )
+        .await?;
+
+        let certificate = ErasureCertificate {
+            guest_id: *id,
+            erased_at,
+            requester: requester.to_string(),
+            trace_id: ctx.trace_id.clone(),
+            fields: PII_FIELDS.iter().map(|f| f.to_string()).collect(),
+        };
+        sqlx::query(
+            "INSERT INTO guest_erasures (guest_id, erased_at, requester, trace_id, fields) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(&id_str)
+        .bind(timestamp_column(erased_at))
+        .bind(&certificate.requester)
+        .bind(&certificate.trace_id)
+        .bind(to_json(&certificate.fields)?)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        tx.commit()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+
+        tracing::info!(guest_id = %id, "store: guest erased");
+        Ok(Some(certificate))
+    }
+
+    async fn erasures(&self, id: &uuid::Uuid) -> Result<Vec<ErasureCertificate>, RepositoryError> {
+        let rows = sqlx::query_as::<_, ErasureRow>(
+            "SELECT guest_id, erased_at, requester, trace_id, fields FROM guest_erasures \
+             WHERE guest_id = ? ORDER BY seq",
+        )
+        .bind(id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        rows.into_iter()
+            .map(ErasureRow::into_certificate)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RepositoryError::Other)
+    }
+
+    async fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<u64, RepositoryError> {
+        let cutoff = timestamp_column(deleted_before);
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        sqlx::query(
+            "DELETE FROM guest_contacts WHERE guest_id IN \
+             (SELECT id FROM guests WHERE deleted_at IS NOT NULL AND deleted_at < ?)",
+        )
+        .bind(&cutoff)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        sqlx::query(
+            "DELETE FROM guest_duplicates WHERE guest_id IN \
+             (SELECT id FROM guests WHERE deleted_at IS NOT NULL AND deleted_at < ?) \
+             OR candidate_id IN (SELECT id FROM guests WHERE deleted_at IS NOT NULL AND deleted_at < ?)",
+        )
+        .bind(&cutoff)
+        .bind(&cutoff)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        sqlx::query(
+            "DELETE FROM guest_redirects WHERE target_id IN \
+             (SELECT id FROM guests WHERE deleted_at IS NOT NULL AND deleted_at < ?)",
+        )
+        .bind(&cutoff)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        sqlx::query(
+            "UPDATE idempotency_keys SET status = NULL, headers = NULL, body = NULL, erased_at = ? \
+             WHERE erased_at IS NULL AND resource_id IN \
+             (SELECT id FROM guests WHERE deleted_at IS NOT NULL AND deleted_at < ?)",
+        )
+        .bind(timestamp_column(Utc::now()))
+        .bind(&cutoff)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        let result =
+            sqlx::query("DELETE FROM guests WHERE deleted_at IS NOT NULL AND deleted_at < ?")
+                .bind(&cutoff)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        tx.commit()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+
+        let purged = result.rows_affected();
+        if purged > 0 {
+            tracing::info!(purged, "store: deleted guests purged");
+        }
+        Ok(purged)
+    }
+
+    async fn reindex_contacts(
+        &self,
+        default_region: Option<PhoneRegion>,
+    ) -> Result<u64, RepositoryError> {
+        let mut guests = 0u64;
+        let mut after = String::new();
+        loop {
+            let mut tx = self
+                .pool
+                .begin()
+                .await
+                .map_err(|e| RepositoryError::Other(e.to_string()))?;
+            // Guests actifs ou supprimés antérieurs à la canonicalisation, traités une seule fois
+            // (une valeur non canonicalisable ou effacée n'est pas réécrite à chaque démarrage).
+            let rows = sqlx::query_as::<_, GuestRow>(&format!(
+                "SELECT {GUEST_COLUMNS} FROM guests WHERE id > ? AND contacts_reindex_pending = 1 ORDER BY id LIMIT ?"
+            ))
+            .bind(&after)
+            .bind(REINDEX_BATCH_SIZE)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+            let Some(last) = rows.last() else {
+                break;
+            };
+            after = last.id.clone();
+            for row in rows {
+                let mut guest = row.into_guest(&self.pii).map_err(RepositoryError::Other)?;
+                guest.canonicalize_contacts(default_region);
+                let columns = GuestColumns::from_guest(&guest, &self.pii)?;
+                // Version et date de modification inchangées : seule la forme canonique est ajoutée.
+                sqlx::query("UPDATE guests SET mail = ?, phone = ?, contacts_reindex_pending = 0 WHERE id = ?")
+                    .bind(&columns.mail)
+                    .bind(&columns.phone)
+                    .bind(&columns.id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| RepositoryError::Other(e.to_string()))?;
+                replace_contacts(&mut tx, &self.pii, &guest).await?;
+                guests += 1;
+            }
+            tx.commit()
+                .await
+                .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        }
+        if guests > 0 {
+            tracing::info!(guests, "store: guest contacts re-indexed");
+        }
+        Ok(guests)
+    }
+
+    async fn reencrypt_all(&self) -> Result<u64, RepositoryError> {
+        let mut guests = 0u64;
+        let mut after = String::new();
+        loop {
+            let mut tx = self
+                .pool
+                .begin()
+                .await
+                .map_err(|e| RepositoryError::Other(e.to_string()))?;
+            let rows = sqlx::query_as::<_, GuestRow>(&format!(
+                "SELECT {GUEST_COLUMNS} FROM guests WHERE id > ? ORDER BY id LIMIT ?"
+            ))
+            .bind(&after)
+            .bind(REENCRYPT_BATCH_SIZE)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+            let Some(last) = rows.last() else {
+                break;
+            };
+            after = last.id.clone();
+            for row in rows {
+                let guest = row.into_guest(&self.pii).map_err(RepositoryError::Other)?;
+                let columns = GuestColumns::from_guest(&guest, &self.pii)?;
+                // Version et date de modification inchangées : les valeurs ne changent pas.
+                sqlx::query(
+                    "UPDATE guests SET first_name = ?, last_name = ?, mail = ?, phone = ?, addresses = ?, \
+                     attributes = ?, last_name_index = ? WHERE id = ?",
+                )
+                .bind(&columns.first_name)
+                .bind(&columns.last_name)
+                .bind(&columns.mail)
+                .bind(&columns.phone)
+                .bind(&columns.addresses)
+                .bind(&columns.attributes)
+                .bind(&columns.last_name_index)
+                .bind(&columns.id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| RepositoryError::Other(e.to_string()))?;
+                replace_contacts(&mut tx, &self.pii, &guest).await?;
+                guests += 1;
+            }
+            tx.commit()
+                .await
+                .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        }
+
+        // Historique (y compris celui des guests fusionnés ou purgés) : champs personnels uniquement.
+        let mut after_seq = 0i64;
+        loop {
+            let mut tx = self
+                .pool
+                .begin()
+                .await
+                .map_err(|e| RepositoryError::Other(e.to_string()))?;
+            let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
+                "SELECT seq, guest_id, operation, field, old_value, new_value, source, changed_at, trace_id \
+                 FROM guest_history WHERE seq > ",
+            );
+            qb.push_bind(after_seq).push(" AND (field IN (");
+            let mut separated = qb.separated(", ");
+            for field in PII_FIELDS {
+                separated.push_bind(field);
+            }
+            qb.push(") OR field LIKE 'attributes.%') ORDER BY seq LIMIT ")
+                .push_bind(REENCRYPT_BATCH_SIZE);
+            let rows = qb
+                .build_query_as::<HistoryRow>()
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(|e| RepositoryError::Other(e.to_string()))?;
+            let Some(last) = rows.last() else {
+                break;
+            };
+            after_seq = last.seq;
+            for row in rows {
+                let seq = row.seq;
+                let guest_id = row.guest_id.clone();
+                let entry = row.into_entry(&self.pii).map_err(RepositoryError::Other)?;
+                let change = &entry.change;
+                sqlx::query("UPDATE guest_history SET old_value = ?, new_value = ? WHERE seq = ?")
+                    .bind(history_value_column(
+                        &self.pii,
+                        change.old_value.as_ref(),
+                        &guest_id,
+                        &change.field,
+                    )?)
+                    .bind(history_value_column(
+                        &self.pii,
+                        change.new_value.as_ref(),
+                        &guest_id,
+                        &change.field,
+                    )?)
+                    .bind(seq)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| RepositoryError::Other(e.to_string()))?;
+            }
+            tx.commit()
+                .await
+                .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        }
+
+        // Raisons des paires de doublons (valeurs des candidats).
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        let rows = sqlx::query_as::<_, DuplicatePairRow>(
+            "SELECT seq, guest_id, candidate_id, score, reasons FROM guest_duplicates ORDER BY seq",
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        for row in rows {
+            let seq = row.seq;
+            let pair = row.into_pair(&self.pii).map_err(RepositoryError::Other)?;
+            sqlx::query("UPDATE guest_duplicates SET reasons = ? WHERE seq = ?")
+                .bind(to_sealed_json(
+                    &self.pii,
+                    &pair.reasons,
+                    &pair.guest_id.to_string(),
+                    "duplicates",
+                )?)
+                .bind(seq)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        }
+        tx.commit()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+
+        tracing::info!(
+            guests,
+            key_id = self.pii.current_key_id().unwrap_or("(aucune)"),
+            "store: guest PII re-encrypted"
+        );
+        Ok(guests)
+    }
+
+    async fn merge(
+        &self,
+        target: &uuid::Uuid,
+        source: &uuid::Uuid,
+        ctx: &ChangeContext,
+    ) -> Result<Option<Guest>, RepositoryError> {
+        let target_id = target.to_string();
+        let source_id = source.to_string();
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        let Some(previous) = fetch_guest(&mut *tx, &self.pii, &target_id).await? else {
+            return Ok(None);
+        };
+        let Some(duplicate) = fetch_guest(&mut *tx, &self.pii, &source_id).await? else {
+            return Ok(None);
+        };
+        // Un guest effacé ne reçoit plus de données personnelles et les siennes ne sont plus reportées.
+        if let Some(erased) = [&previous, &duplicate].into_iter().find(|g| g.is_erased()) {
+            return Err(RepositoryError::Conflict(format!(
+                "guest {} effacé : fusion impossible",
+                erased.id
+            )));
+        }
+
+        let mut merged = merge_guests(previous.clone(), &duplicate);
+        let columns = GuestColumns::from_guest(&merged, &self.pii)?;
+        if update_columns(&mut tx, &columns, merged.version).await? == 0 {
+            return Err(match stored_version(&mut tx, &target_id).await? {
+                Some(actual) => RepositoryError::VersionConflict {
+                    id: target_id,
+                    expected: merged.version,
+                    actual,
+                },
+                None => RepositoryError::NotFound(target_id),
+            });
+        }
+        merged.version += 1;
+        replace_contacts(&mut tx, &self.pii, &merged).await?;
+
+        sqlx::query("DELETE FROM guest_contacts WHERE guest_id = ?")
+            .bind(&source_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        sqlx::query("DELETE FROM guests WHERE id = ?")
+            .bind(&source_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+
+        // Les relations du guest fusionné sont reportées sur la cible (doublons et relation avec soi-même retirés).
+        for column in ["guest_id", "related_id"] {
+            sqlx::query(&format!(
+                "UPDATE OR IGNORE guest_relationships SET {column} = ? WHERE {column} = ?"
+            ))
+            .bind(&target_id)
+            .bind(&source_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        }
+        sqlx::query(
+            "DELETE FROM guest_relationships WHERE guest_id = ? OR related_id = ? OR guest_id = related_id",
+        )
+        .bind(&source_id)
+        .bind(&source_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+
+        // Les redirections vers le guest fusionné suivent la fusion : une seule lecture suffit à résoudre un ancien id.
+        let merged_at = timestamp_column(Utc::now());
+        sqlx::query("UPDATE guest_redirects SET target_id = ? WHERE target_id = ?")
+            .bind(&target_id)
+            .bind(&source_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        sqlx::query(
+            "INSERT OR REPLACE INTO guest_redirects (source_id, target_id, merged_at) VALUES (?, ?, ?)",
+        )
+        .bind(&source_id)
+        .bind(&target_id)
+        .bind(&merged_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+
+        let changes = diff_guests(Some(&previous), Some(&merged));
+        append_history(
+            &mut tx,
+            &self.pii,
+            target,
+            ChangeOperation::Merge,
+            &changes,
+            ctx,
+        )
+        .await?;
+        let removed = diff_guests(Some(&duplicate), None);
+        append_history(
+            &mut tx,
+            &self.pii,
+            source,
+            ChangeOperation::Merge,
+            &removed,
+            ctx,
+        )
+        .await?;
+        tx.commit()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+
+        tracing::info!(guest_id = %target, source_id = %source, version = merged.version, "store: guests merged");
+        Ok(Some(merged))
+    }
+
+    async fn merged_into(&self, id: &uuid::Uuid) -> Result<Option<uuid::Uuid>, RepositoryError> {
+        let target = sqlx::query_scalar::<_, String>(
+            "SELECT target_id FROM guest_redirects WHERE source_id = ?",
+        )
+        .bind(id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        target
+            .map(|t| uuid::Uuid::parse_str(&t))
+            .transpose()
+            .map_err(|e| RepositoryError::Other(e.to_string()))
+    }
+
+    async fn merged_sources(&self, id: &uuid::Uuid) -> Result<Vec<uuid::Uuid>, RepositoryError> {
+        let sources = sqlx::query_scalar::<_, String>(
+            "SELECT source_id FROM guest_redirects WHERE target_id = ? ORDER BY merged_at",
+        )
+        .bind(id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        sources
+            .iter()
+            .map(|s| uuid::Uuid::parse_str(s))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| RepositoryError::Other(e.to_string()))
+    }
+
+    async fn deleted_at(&self, id: &uuid::Uuid) -> Result<Option<DateTime<Utc>>, RepositoryError> {
+        let deleted_at = sqlx::query_scalar::<_, String>(
+            "SELECT deleted_at FROM guests WHERE id = ? AND deleted_at IS NOT NULL",
+        )
+        .bind(id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        deleted_at
+            .map(|d| DateTime::parse_from_rfc3339(&d).map(|d| d.with_timezone(&Utc)))
+            .transpose()
+            .map_err(|e| RepositoryError::Other(e.to_string()))
+    }
+
+    async fn duplicate_candidates(&self, guest: &Guest) -> Result<Vec<Guest>, RepositoryError> {
+        let initial = last_name_index(&self.pii, guest);
+        let rows = sqlx::query_as::<_, GuestRow>(&format!(
+            r#"
+            SELECT {GUEST_COLUMNS} FROM guests
+            WHERE deleted_at IS NULL AND id != ?1 AND (
+                id IN (
+                    SELECT other.guest_id FROM guest_contacts other
+                    JOIN guest_contacts mine ON mine.kind = other.kind AND mine.normalized = other.normalized
+                    WHERE mine.guest_id = ?1
+                )
+                OR (?2 IS NOT NULL AND last_name_index = ?2)
+            )
+            ORDER BY id
+            "#
+        ))
+        .bind(guest.id.to_string())
+        .bind(&initial)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        let guests = rows
+            .into_iter()
+            .map(|row| row.into_guest(&self.pii))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RepositoryError::Other)?;
+        tracing::debug!(guest_id = %guest.id, count = guests.len(), "store: duplicate candidates");
+        Ok(guests)
+    }
+
+    async fn replace_duplicate_pairs(
+        &self,
+        pairs: &[DuplicatePair],
+    ) -> Result<(), RepositoryError> {
+        let scanned_at = timestamp_column(Utc::now());
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        sqlx::query("DELETE FROM guest_duplicates")
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        for pair in pairs {
+            sqlx::query(
+                "INSERT INTO guest_duplicates (guest_id, candidate_id, score, reasons, scanned_at) \
+                 VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(pair.guest_id.to_string())
+            .bind(pair.candidate_id.to_string())
+            .bind(pair.score)
+            .bind(to_sealed_json(
+                &self.pii,
+                &pair.reasons,
+                &pair.guest_id.to_string(),
+                "duplicates",
+            )?)
+            .bind(&scanned_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        }
+        tx.commit()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        tracing::info!(pairs = pairs.len(), "store: duplicate scan saved");
+        Ok(())
+    }
+
+    async fn duplicate_pairs_of(
+        &self,
+        id: &uuid::Uuid,
+    ) -> Result<Vec<DuplicatePair>, RepositoryError> {
+        let rows = sqlx::query_as::<_, DuplicatePairRow>(
+            "SELECT seq, guest_id, candidate_id, score, reasons FROM guest_duplicates \
+             WHERE guest_id = ? OR candidate_id = ? ORDER BY seq",
+        )
+        .bind(id.to_string())
+        .bind(id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        rows.into_iter()
+            .map(|row| row.into_pair(&self.pii))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RepositoryError::Other)
+    }
+
+    async fn list_duplicate_pairs(
+        &self,
+        query: DuplicatePairQuery,
+    ) -> Result<DuplicatePairPage, RepositoryError> {
+        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
+            "SELECT d.seq, d.guest_id, d.candidate_id, d.score, d.reasons FROM guest_duplicates d \
+             JOIN guests g ON g.id = d.guest_id AND g.deleted_at IS NULL \
+             JOIN guests c ON c.id = d.candidate_id AND c.deleted_at IS NULL WHERE 1 = 1",
+        );
+        if let Some(after) = query.after {
+            qb.push(" AND d.seq > ").push_bind(after);
+        }
+        qb.push(" ORDER BY d.seq LIMIT ")
+            .push_bind(i64::from(query.limit) + 1);
+
+        let rows = qb
+            .build_query_as::<DuplicatePairRow>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+
+        let has_more = rows.len() > query.limit as usize;
+        let rows: Vec<DuplicatePairRow> = rows.into_iter().take(query.limit as usize).collect();
+        let next = if has_more {
+            rows.last().map(|r| r.seq)
+        } else {
+            None
+        };
+        let pairs = rows
+            .into_iter()
+            .map(|row| row.into_pair(&self.pii))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RepositoryError::Other)?;
+        Ok(DuplicatePairPage { pairs, next })
+    }
+
+    async fn record_opt_out_event(
+        &self,
+        event: &OptOutEvent,
+        trace_id: Option<&str>,
+        published: bool,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO guest_opt_out_events (guest_id, channels, reason, trace_id, published, occurred_at) \
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(event.guest_id.to_string())
+        .bind(to_json(&event.channels)?)
+        .bind(&event.reason)
+        .bind(trace_id)
+        .bind(published)
+        .bind(timestamp_column(event.occurred_at))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        tracing::debug!(guest_id = %event.guest_id, published, "store: opt-out event recorded");
+        Ok(())
+    }
+
+    async fn opt_out_events(
+        &self,
+        id: &uuid::Uuid,
+    ) -> Result<Vec<OptOutEventRecord>, RepositoryError> {
+        let rows = sqlx::query_as::<_, OptOutEventRow>(
+            "SELECT seq, guest_id, channels, reason, trace_id, published, occurred_at \
+             FROM guest_opt_out_events WHERE guest_id = ? ORDER BY seq",
+        )
+        .bind(id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        rows.into_iter()
+            .map(OptOutEventRow::into_record)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RepositoryError::Other)
+    }
+
+    async fn history(
+        &self,
+        id: &uuid::Uuid,
+        query: HistoryQuery,
+    ) -> Result<HistoryPage, RepositoryError> {
+        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
+            "SELECT seq, guest_id, operation, field, old_value, new_value, source, changed_at, trace_id \
+             FROM guest_history WHERE guest_id = ",
+        );
+        qb.push_bind(id.to_string());
+        if let Some(before) = query.before {
+            qb.push(" AND seq < ").push_bind(before);
+        }
+        qb.push(" ORDER BY seq DESC LIMIT ")
+            .push_bind(i64::from(query.limit) + 1);
+
+        let rows = qb
+            .build_query_as::<HistoryRow>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+
+        let has_more = rows.len() > query.limit as usize;
+        let entries = rows
+            .into_iter()
+            .take(query.limit as usize)
+            .map(|row| row.into_entry(&self.pii))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RepositoryError::Other)?;
+        let next = if has_more {
+            entries.last().map(|e| e.seq)
+        } else {
+            None
+        };
+        tracing::debug!(guest_id = %id, count = entries.len(), "store: guest history");
+        Ok(HistoryPage { entries, next })
+    }
+}
+
+impl DuplicatePairRow {
+    fn into_pair(self, pii: &Pii) -> Result<DuplicatePair, String> {
+        Ok(DuplicatePair {
+            guest_id: uuid::Uuid::parse_str(&self.guest_id).map_err(|e| e.to_string())?,
+            candidate_id: uuid::Uuid::parse_str(&self.candidate_id).map_err(|e| e.to_string())?,
+            score: self.score,
+            reasons: from_sealed_json(pii, &self.reasons, &self.guest_id, "duplicates")?,
+        })
+    }
+}
+
+impl ErasureRow {
+    fn into_certificate(self) -> Result<ErasureCertificate, String> {
+        Ok(ErasureCertificate {
+            guest_id: uuid::Uuid::parse_str(&self.guest_id).map_err(|e| e.to_string())?,
+            erased_at: DateTime::parse_from_rfc3339(&self.erased_at)
+                .map_err(|e| e.to_string())?
+                .with_timezone(&Utc),
+            requester: self.requester,
+            trace_id: self.trace_id,
+            fields: serde_json::from_str(&self.fields).map_err(|e| e.to_string())?,
+        })
+    }
+}
+
+impl OptOutEventRow {
+    fn into_record(self) -> Result<OptOutEventRecord, String> {
+        let occurred_at = DateTime::parse_from_rfc3339(&self.occurred_at)
+            .map_err(|e| e.to_string())?
+            .with_timezone(&Utc);
+        Ok(OptOutEventRecord {
+            seq: self.seq,
+            event: OptOutEvent {
+                guest_id: uuid::Uuid::parse_str(&self.guest_id).map_err(|e| e.to_string())?,
+                channels: serde_json::from_str(&self.channels).map_err(|e| e.to_string())?,
+                reason: self.reason,
+                occurred_at,
+            },
+            trace_id: self.trace_id,
+            published: self.published,
+        })
+    }
+}
+
+impl HistoryRow {
+    fn into_entry(self, pii: &Pii) -> Result<GuestHistoryEntry, String> {
+        let guest_id = uuid::Uuid::parse_str(&self.guest_id).map_err(|e| e.to_string())?;
+        let operation = ChangeOperation::parse(&self.operation)
+            .ok_or_else(|| format!("opération d'historique inconnue: {}", self.operation))?;
+        let old_value = self
+            .old_value
+            .map(|v| from_sealed_json(pii, &v, &self.guest_id, &self.field))
+            .transpose()?;
+        let new_value = self
+            .new_value
+            .map(|v| from_sealed_json(pii, &v, &self.guest_id, &self.field))
+            .transpose()?;
+        let changed_at = DateTime::parse_from_rfc3339(&self.changed_at)
+            .map_err(|e| e.to_string())?
+            .with_timezone(&Utc);
+        Ok(GuestHistoryEntry {
+            seq: self.seq,
+            guest_id,
+            operation,
+            change: FieldChange {
+                field: self.field,
+                old_value,
+                new_value,
+                from: self.source,
+            },
+            changed_at,
+            trace_id: self.trace_id,
+        })
+    }
 }
 
 impl GuestRow {
-    fn into_guest(self) -> Result<Guest, String> {
+    fn into_guest(self, pii: &Pii) -> Result<Guest, String> {
         let id = uuid::Uuid::parse_str(&self.id).map_err(|e| e.to_string())?;
         let first_name: StructuredValue<String> =
-            serde_json::from_str(&self.first_name).map_err(|e| e.to_string())?;
+            from_sealed_json(pii, &self.first_name, &self.id, "first_name")?;
         let last_name: StructuredValue<String> =
-            serde_json::from_str(&self.last_name).map_err(|e| e.to_string())?;
+            from_sealed_json(pii, &self.last_name, &self.id, "last_name")?;
         let mail: Vec<StructuredValue<String>> =
-            serde_json::from_str(&self.mail).map_err(|e| e.to_string())?;
+            from_sealed_json(pii, &self.mail, &self.id, "mail")?;
         let phone: Vec<StructuredValue<String>> =
-            serde_json::from_str(&self.phone).map_err(|e| e.to_string())?;
-        let opt_outs: Vec<StructuredValue<bool>> =
-            serde_json::from_str(&self.opt_outs).map_err(|e| e.to_string())?;
+            from_sealed_json(pii, &self.phone, &self.id, "phone")?;
+        let addresses: Vec<StructuredValue<Address>> =
+            from_sealed_json(pii, &self.addresses, &self.id, "addresses")?;
+        let consents: Vec<StructuredValue<Consent>> =
+            serde_json::from_str(&self.consents).map_err(|e| e.to_string())?;
+        let attributes = from_sealed_attributes(pii, &self.attributes, &self.id)?;
+        let erased_at = self
+            .erased_at
+            .map(|at| DateTime::parse_from_rfc3339(&at).map(|at| at.with_timezone(&Utc)))
+            .transpose()
+            .map_err(|e| e.to_string())?;
         Ok(Guest {
             id,
             first_name,
             last_name,
             mail,
             phone,
-            opt_outs,
+            addresses,
+            consents,
+            attributes,
+            version: self.version,
+            erased_at,
         })
     }
 }
 
-/// Store SQLite pour les guests.
+/// Store SQLite pour les guests ; noms, emails, téléphones, adresses et attributs chiffrés au repos si `pii`
+/// porte une clé.
 pub struct SqliteGuestStore {
     pub(super) pool: SqlitePool,
+    pii: Pii,
 }
 
 impl SqliteGuestStore {
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+    pub fn new(pool: SqlitePool, pii: Pii) -> Self {
+        Self { pool, pii }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    use crate::domain::{HistoryQuery, ERASED_VALUE};
+    use crate::store::PiiCipher;
+
+    use super::*;
+
+    /// Store sur une base SQLite en mémoire (une seule connexion : la base vit avec elle), données en clair.
+    async fn store() -> SqliteGuestStore {
+        store_with(Pii::default()).await
+    }
+
+    async fn store_with(pii: Pii) -> SqliteGuestStore {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        SqliteGuestStore::new(pool, pii)
+    }
+
+    /// Données personnelles chiffrées au repos (clé `1`).
+    fn encrypted() -> Pii {
+        let key = STANDARD.encode([1u8; 32]);
+        let index_key = STANDARD.encode([2u8; 32]);
+        Pii::new(PiiCipher::parse(&format!("1:{key}"), "", &index_key).unwrap())
+    }
+
+    fn guest(first_name: &str, last_name: &str) -> Guest {
+        Guest::new(uuid::Uuid::new_v4(), first_name.into(), last_name.into())
+    }
+
+    #[tokio::test]
+    async fn upsert_does_not_reuse_the_uuid_of_a_merged_guest() {
+        let store = store().await;
+        let ctx = ChangeContext::default();
+        let target = store.create(guest("Ana", "Lopez"), &ctx).await.unwrap();
+        let source = store.create(guest("Anna", "Lopez"), &ctx).await.unwrap();
+        store
+            .merge(&target.id, &source.id, &ctx)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let mut reused = guest("Bob", "Martin");
+        reused.id = source.id;
+        let err = store.upsert(reused, &ctx).await.unwrap_err();
+        assert!(matches!(err, RepositoryError::Conflict(_)), "{err}");
+        assert!(store
+            .get_including_deleted(&source.id)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            store.merged_into(&source.id).await.unwrap(),
+            Some(target.id)
+        );
+    }
+
+    #[tokio::test]
+    async fn merge_records_a_redirect_that_follows_later_merges() {
+        let store = store().await;
+        let ctx = ChangeContext::default();
+        let target = store.create(guest("Ana", "Lopez"), &ctx).await.unwrap();
+        let mut source = guest("Anna", "Lopez");
+        source
+            .mail
+            .push(StructuredValue::new("anna@example.com".into()));
+        let source = store.create(source, &ctx).await.unwrap();
+
+        let merged = store
+            .merge(&target.id, &source.id, &ctx)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(merged.version, target.version + 1);
+        assert_eq!(merged.mail[0].value, "anna@example.com");
+        assert!(store
+            .get_including_deleted(&source.id)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            store.merged_into(&source.id).await.unwrap(),
+            Some(target.id)
+        );
+        assert_eq!(
+            store.merged_sources(&target.id).await.unwrap(),
+            vec![source.id]
+        );
+        let found = store
+            .find_by_contact(ContactKind::Mail, "anna@example.com")
+            .await
+            .unwrap();
+        assert_eq!(
+            found.iter().map(|g| g.id).collect::<Vec<_>>(),
+            vec![target.id]
+        );
+
+        // La cible est à son tour fusionnée : l'ancien id redirige directement vers la nouvelle cible.
+        let last = store
+            .create(guest("Ana", "Lopez-Garcia"), &ctx)
+            .await
+            .unwrap();
+        store
+            .merge(&last.id, &target.id, &ctx)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(store.merged_into(&source.id).await.unwrap(), Some(last.id));
+        assert_eq!(store.merged_sources(&last.id).await.unwrap().len(), 2);
+        assert!(store
+            .merge(&last.id, &source.id, &ctx)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn history_is_encrypted_at_rest_and_read_back_in_clear() {
+        let store = store_with(encrypted()).await;
+        let mut ana = guest("Ana", "Lopez");
+        ana.mail
+            .push(StructuredValue::new("ana@example.com".into()));
+        let ana = store.create(ana, &ChangeContext::default()).await.unwrap();
+        let mut renamed = ana.clone();
+        renamed.first_name = StructuredValue::with_from("Anna".into(), "web".into());
+        store
+            .update(renamed, &ChangeContext::with_trace_id("trace-1".into()))
+            .await
+            .unwrap();
+
+        let raw: Vec<String> =
+            sqlx::query_scalar("SELECT new_value FROM guest_history ORDER BY seq")
+                .fetch_all(&store.pool)
+                .await
+                .unwrap();
+        assert_eq!(raw.len(), 4);
+        assert!(
+            raw.iter()
+                .all(|v| !v.contains("Ana") && !v.contains("ana@example.com")),
+            "{raw:?}"
+        );
+
+        let page = store
+            .history(
+                &ana.id,
+                HistoryQuery {
+                    before: None,
+                    limit: 1,
+                },
+            )
+            .await
+            .unwrap();
+        let latest = &page.entries[0];
+        assert_eq!(
+            (latest.operation, latest.change.field.as_str()),
+            (ChangeOperation::Update, "first_name")
+        );
+        assert_eq!(latest.change.old_value.as_ref().unwrap()["value"], "Ana");
+        assert_eq!(latest.change.new_value.as_ref().unwrap()["value"], "Anna");
+        assert_eq!(
+            (latest.change.from.as_deref(), latest.trace_id.as_deref()),
+            (Some("web"), Some("trace-1"))
+        );
+
+        let rest = store
+            .history(
+                &ana.id,
+                HistoryQuery {
+                    before: page.next,
+                    limit: 10,
+                },
+            )
+            .await
+            .unwrap();
+        let created: Vec<&str> = rest
+            .entries
+            .iter()
+            .map(|e| e.change.field.as_str())
+            .collect();
+        assert_eq!(created, ["mail", "last_name", "first_name"]);
+        assert_eq!(
+            rest.entries[0].change.new_value.as_ref().unwrap()["value"],
+            "ana@example.com"
+        );
+        assert_eq!(rest.next, None);
+    }
+
+    #[tokio::test]
+    async fn purge_removes_the_duplicate_pairs_of_purged_guests() {
+        let store = store().await;
+        let ctx = ChangeContext::default();
+        let ana = store.create(guest("Ana", "Lopez"), &ctx).await.unwrap();
+        let anna = store.create(guest("Anna", "Lopez"), &ctx).await.unwrap();
+        let pair = DuplicatePair {
+            guest_id: ana.id,
+            candidate_id: anna.id,
+            score: 0.9,
+            reasons: Vec::new(),
+        };
+        store.replace_duplicate_pairs(&[pair]).await.unwrap();
+        store.delete(&anna.id, None, &ctx).await.unwrap().unwrap();
+        assert_eq!(store.duplicate_pairs_of(&ana.id).await.unwrap().len(), 1);
+
+        assert_eq!(
+            store
+                .purge_deleted(Utc::now() + chrono::Duration::seconds(1))
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(store.duplicate_pairs_of(&ana.id).await.unwrap().is_empty());
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM guest_duplicates")
+            .fetch_one(&store.pool)
+            .await
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
+
+    #[tokio::test]
+    async fn legacy_contacts_are_reindexed_once() {
+        let store = store().await;
+        let ctx = ChangeContext::default();
+        let mut legacy = guest("Ana", "Lopez");
+        legacy
+            .phone
+            .push(StructuredValue::new("06 12 34 56 78".into()));
+        legacy
+            .phone
+            .push(StructuredValue::new("not a phone".into()));
+        let legacy = store.create(legacy, &ctx).await.unwrap();
+        store.create(guest("Bob", "Martin"), &ctx).await.unwrap();
+        sqlx::query("UPDATE guests SET contacts_reindex_pending = 1 WHERE id = ?")
+            .bind(legacy.id.to_string())
+            .execute(&store.pool)
+            .await
+            .unwrap();
+
+        let region = Some(phonenumber::country::Id::FR);
+        assert_eq!(store.reindex_contacts(region).await.unwrap(), 1);
+        let reindexed = store.get_by_id(&legacy.id).await.unwrap().unwrap();
+        assert_eq!(
+            reindexed.phone[0].normalized.as_deref(),
+            Some("+33612345678")
+        );
+        assert_eq!(reindexed.phone[1].normalized, None);
+        assert_eq!(reindexed.version, legacy.version);
+        let found = store
+            .find_by_contact(ContactKind::Phone, "+33612345678")
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+
+        // L'autre téléphone n'a pas de forme canonique : le guest n'est pas retraité.
+        assert_eq!(store.reindex_contacts(region).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn erasure_is_persisted_and_idempotent() {
+        let store = store().await;
+        let ctx = ChangeContext::default();
+        let lookalike = store
+            .create(guest(ERASED_VALUE, ERASED_VALUE), &ctx)
+            .await
+            .unwrap();
+        assert!(!lookalike.is_erased());
+
+        let mut ana = guest("Ana", "Lopez");
+        ana.mail
+            .push(StructuredValue::new("ana@example.com".into()));
+        let ana = store.create(ana, &ctx).await.unwrap();
+        store.erase(&ana.id, "dpo", &ctx).await.unwrap().unwrap();
+        let erased = store.get_by_id(&ana.id).await.unwrap().unwrap();
+        assert!(erased.is_erased());
+        assert_eq!(erased.mail[0].value, ERASED_VALUE);
+
+        let again = store.erase(&ana.id, "legal", &ctx).await.unwrap().unwrap();
+        let certificates = store.erasures(&ana.id).await.unwrap();
+        assert_eq!(certificates.len(), 1);
+        assert_eq!(
+            (again.erased_at, again.requester.as_str()),
+            (certificates[0].erased_at, "dpo")
+        );
+        assert_eq!(
+            store.get_by_id(&ana.id).await.unwrap().unwrap().version,
+            erased.version
+        );
+
+        let err = store.merge(&lookalike.id, &ana.id, &ctx).await.unwrap_err();
+        assert!(matches!(err, RepositoryError::Conflict(_)), "{err}");
+    }
+
+    /// Ids exportés depuis `since` (export incrémental).
+    async fn exported_since(store: &SqliteGuestStore, since: DateTime<Utc>) -> Vec<uuid::Uuid> {
+        let query = GuestListQuery {
+            updated_after: Some(since),
+            limit: 50,
+            ..Default::default()
+        };
+        let guests: Vec<Guest> = store.export(query).try_collect().await.unwrap();
+        guests.into_iter().map(|g| g.id).collect()
+    }
+
+    #[tokio::test]
+    async fn backdated_writes_and_removals_show_up_in_incremental_exports() {
+        let store = store().await;
+        let ctx = ChangeContext::default();
+        let long_ago = Utc::now() - chrono::Duration::days(365);
+        let mut created = guest("Ana", "Lopez");
+        created.first_name.updated_at = long_ago;
+        created.last_name.updated_at = long_ago;
+        created.mail.push(StructuredValue::with_updated_at(
+            "ana@example.com".into(),
+            long_ago,
+        ));
+        let created = store.create(created, &ctx).await.unwrap();
+
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        let since = Utc::now();
+        assert!(exported_since(&store, since).await.is_empty());
+
+        // Synchronisation antidatée (PMS) : la valeur porte une date antérieure à `since`.
+        let mut synced = created.clone();
+        synced.phone.push(StructuredValue::with_updated_at(
+            "+33612345678".into(),
+            long_ago,
+        ));
+        let synced = store.update(synced, &ctx).await.unwrap();
+        assert_eq!(exported_since(&store, since).await, vec![created.id]);
+
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        let since = Utc::now();
+        let mut removed = synced;
+        removed.mail.clear();
+        store.update(removed, &ctx).await.unwrap();
+        assert_eq!(exported_since(&store, since).await, vec![created.id]);
     }
 }
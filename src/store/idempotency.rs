@@ -0,0 +1,180 @@
+//! Store SQLite des clés d'idempotence : implémentation de IdempotencyRepository.
+//! Les réponses enregistrées (données personnelles des guests créés) sont chiffrées si `pii` porte une clé.
+
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::{FromRow, SqlitePool};
+
+use super::cipher::Pii;
+
+use crate::domain::{
+    IdempotencyRecord, IdempotencyRepository, IdempotencyReservation, RepositoryError,
+    StoredResponse,
+};
+
+/// Clé telle que lue depuis SQLite (statut, en-têtes et corps absents tant que la requête est en cours).
+#[derive(Debug, FromRow)]
+struct IdempotencyRow {
+    fingerprint: String,
+    status: Option<i64>,
+    headers: Option<String>,
+    body: Option<String>,
+    created_at: String,
+    erased_at: Option<String>,
+}
+
+fn timestamp_column(date: DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Données associées au chiffrement d'une réponse : lie le chiffré à sa clé.
+fn response_aad(scope: &str, key: &str) -> String {
+    format!("idempotency:{scope}:{key}")
+}
+
+impl IdempotencyRow {
+    fn into_record(self, pii: &Pii, aad: &str) -> Result<IdempotencyRecord, String> {
+        let response = match (self.status, self.headers, self.body) {
+            (Some(status), Some(headers), Some(body)) => Some(StoredResponse {
+                status: u16::try_from(status).map_err(|e| e.to_string())?,
+                headers: serde_json::from_str(&headers).map_err(|e| e.to_string())?,
+                body: pii.open_text(&body, aad)?,
+            }),
+            _ => None,
+        };
+        Ok(IdempotencyRecord {
+            fingerprint: self.fingerprint,
+            response,
+            created_at: DateTime::parse_from_rfc3339(&self.created_at)
+                .map_err(|e| e.to_string())?
+                .with_timezone(&Utc),
+            erased_at: self
+                .erased_at
+                .map(|at| DateTime::parse_from_rfc3339(&at).map(|at| at.with_timezone(&Utc)))
+                .transpose()
+                .map_err(|e| e.to_string())?,
+        })
+    }
+}
+
+/// Store SQLite des clés d'idempotence (même base que les guests).
+pub struct SqliteIdempotencyStore {
+    pool: SqlitePool,
+    pii: Pii,
+}
+
+impl SqliteIdempotencyStore {
+    pub fn new(pool: SqlitePool, pii: Pii) -> Self {
+        Self { pool, pii }
+    }
+}
+
+#[async_trait]
+impl IdempotencyRepository for SqliteIdempotencyStore {
+    async fn reserve(
+        &self,
+        scope: &str,
+        key: &str,
+        fingerprint: &str,
+        now: DateTime<Utc>,
+        expired_before: DateTime<Utc>,
+    ) -> Result<IdempotencyReservation, RepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        sqlx::query("DELETE FROM idempotency_keys WHERE scope = ? AND key = ? AND created_at < ?")
+            .bind(scope)
+            .bind(key)
+            .bind(timestamp_column(expired_before))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        // Réservation atomique : une seule des requêtes concurrentes portant la clé l'insère.
+        let inserted = sqlx::query(
+            "INSERT INTO idempotency_keys (scope, key, fingerprint, created_at) VALUES (?, ?, ?, ?) \
+             ON CONFLICT (scope, key) DO NOTHING",
+        )
+        .bind(scope)
+        .bind(key)
+        .bind(fingerprint)
+        .bind(timestamp_column(now))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?
+        .rows_affected()
+            > 0;
+        let existing = if inserted {
+            None
+        } else {
+            sqlx::query_as::<_, IdempotencyRow>(
+                "SELECT fingerprint, status, headers, body, created_at, erased_at FROM idempotency_keys \
+                 WHERE scope = ? AND key = ?",
+            )
+            .bind(scope)
+            .bind(key)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?
+        };
+        tx.commit()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+
+        match existing {
+            Some(row) => row
+                .into_record(&self.pii, &response_aad(scope, key))
+                .map(IdempotencyReservation::Existing)
+                .map_err(RepositoryError::Other),
+            None => Ok(IdempotencyReservation::Reserved),
+        }
+    }
+
+    async fn complete(
+        &self,
+        scope: &str,
+        key: &str,
+        resource_id: Option<&str>,
+        response: &StoredResponse,
+    ) -> Result<(), RepositoryError> {
+        let headers = serde_json::to_string(&response.headers)
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        let body = self
+            .pii
+            .seal_text(&response.body, &response_aad(scope, key))
+            .map_err(RepositoryError::Other)?;
+        sqlx::query(
+            "UPDATE idempotency_keys SET status = ?, headers = ?, body = ?, resource_id = ? WHERE scope = ? AND key = ?",
+        )
+        .bind(i64::from(response.status))
+        .bind(&headers)
+        .bind(&body)
+        .bind(resource_id)
+        .bind(scope)
+        .bind(key)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn release(&self, scope: &str, key: &str) -> Result<(), RepositoryError> {
+        sqlx::query("DELETE FROM idempotency_keys WHERE scope = ? AND key = ? AND status IS NULL AND erased_at IS NULL")
+            .bind(scope)
+            .bind(key)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn purge_expired(&self, expired_before: DateTime<Utc>) -> Result<u64, RepositoryError> {
+        let result = sqlx::query("DELETE FROM idempotency_keys WHERE created_at < ?")
+            .bind(timestamp_column(expired_before))
+            .execute(&self.pool)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        Ok(result.rows_affected())
+    }
+}
@@ -1,8 +1,15 @@
 //! Store : structure agrégée + implémentations des interfaces du domaine.
 //! Chaque partie (items, guests, …) vit dans son module ; Store les regroupe.
 
+mod attribute;
+mod cipher;
 mod guest;
+mod idempotency;
 mod item;
+mod relationship;
+mod segment;
+#[allow(clippy::module_inception)]
 mod store;
 
+pub use cipher::{Pii, PiiCipher};
 pub use store::Store;
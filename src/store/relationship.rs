@@ -0,0 +1,149 @@
+//! Store SQLite des relations entre guests : implémentation de RelationshipRepository.
+
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::{FromRow, SqlitePool};
+
+use crate::domain::{Relationship, RelationshipKind, RelationshipRepository, RepositoryError};
+
+/// Relation telle que lue depuis SQLite.
+#[derive(Debug, FromRow)]
+struct RelationshipRow {
+    guest_id: String,
+    related_id: String,
+    kind: String,
+    created_at: String,
+}
+
+impl RelationshipRow {
+    fn into_relationship(self) -> Result<Relationship, String> {
+        Ok(Relationship {
+            guest_id: uuid::Uuid::parse_str(&self.guest_id).map_err(|e| e.to_string())?,
+            related_id: uuid::Uuid::parse_str(&self.related_id).map_err(|e| e.to_string())?,
+            kind: RelationshipKind::parse(&self.kind)
+                .ok_or_else(|| format!("type de relation inconnu '{}'", self.kind))?,
+            created_at: DateTime::parse_from_rfc3339(&self.created_at)
+                .map_err(|e| e.to_string())?
+                .with_timezone(&Utc),
+        })
+    }
+}
+
+/// Store SQLite des relations (même base que les guests).
+pub struct SqliteRelationshipStore {
+    pool: SqlitePool,
+}
+
+impl SqliteRelationshipStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RelationshipRepository for SqliteRelationshipStore {
+    async fn list(&self, guest_id: &uuid::Uuid) -> Result<Vec<Relationship>, RepositoryError> {
+        let id = guest_id.to_string();
+        let rows = sqlx::query_as::<_, RelationshipRow>(
+            "SELECT guest_id, related_id, kind, created_at FROM guest_relationships \
+             WHERE guest_id = ? OR related_id = ? ORDER BY created_at, guest_id, related_id, kind",
+        )
+        .bind(&id)
+        .bind(&id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        rows.into_iter()
+            .map(|row| row.into_relationship().map(|r| r.seen_from(guest_id)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RepositoryError::Other)
+    }
+
+    async fn add(&self, relationship: &Relationship) -> Result<bool, RepositoryError> {
+        let guest_id = relationship.guest_id.to_string();
+        let related_id = relationship.related_id.to_string();
+        let kind = relationship.kind.as_str();
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        for id in [&guest_id, &related_id] {
+            let exists = sqlx::query_scalar::<_, i64>(
+                "SELECT 1 FROM guests WHERE id = ? AND deleted_at IS NULL",
+            )
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?
+            .is_some();
+            if !exists {
+                return Err(RepositoryError::NotFound(id.clone()));
+            }
+        }
+        // Une relation symétrique déjà enregistrée dans l'autre sens n'est pas dupliquée.
+        let reverse = relationship.kind.is_symmetric();
+        let existed = sqlx::query_scalar::<_, i64>(
+            "SELECT 1 FROM guest_relationships WHERE kind = ? \
+             AND ((guest_id = ? AND related_id = ?) OR (? AND guest_id = ? AND related_id = ?))",
+        )
+        .bind(kind)
+        .bind(&guest_id)
+        .bind(&related_id)
+        .bind(reverse)
+        .bind(&related_id)
+        .bind(&guest_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?
+        .is_some();
+        if !existed {
+            sqlx::query(
+                "INSERT INTO guest_relationships (guest_id, related_id, kind, created_at) VALUES (?, ?, ?, ?)",
+            )
+            .bind(&guest_id)
+            .bind(&related_id)
+            .bind(kind)
+            .bind(relationship.created_at.to_rfc3339_opts(SecondsFormat::Millis, true))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        }
+        tx.commit()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+
+        if !existed {
+            tracing::info!(guest_id = %guest_id, related_id = %related_id, kind, "store: relationship added");
+        }
+        Ok(!existed)
+    }
+
+    async fn remove(
+        &self,
+        guest_id: &uuid::Uuid,
+        related_id: &uuid::Uuid,
+        kind: Option<RelationshipKind>,
+    ) -> Result<u64, RepositoryError> {
+        let (guest_id, related_id) = (guest_id.to_string(), related_id.to_string());
+        let result = sqlx::query(
+            "DELETE FROM guest_relationships \
+             WHERE ((guest_id = ? AND related_id = ?) OR (guest_id = ? AND related_id = ?)) \
+             AND (? IS NULL OR kind = ?)",
+        )
+        .bind(&guest_id)
+        .bind(&related_id)
+        .bind(&related_id)
+        .bind(&guest_id)
+        .bind(kind.map(|k| k.as_str()))
+        .bind(kind.map(|k| k.as_str()))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        let removed = result.rows_affected();
+        if removed > 0 {
+            tracing::info!(guest_id = %guest_id, related_id = %related_id, removed, "store: relationships removed");
+        }
+        Ok(removed)
+    }
+}
@@ -0,0 +1,123 @@
+//! Store SQLite des segments de guests : implémentation de SegmentRepository.
+
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::{FromRow, SqlitePool};
+
+use crate::domain::{RepositoryError, Segment, SegmentRepository};
+
+/// Colonnes lues pour reconstruire un segment.
+const SEGMENT_COLUMNS: &str = "name, expression, description, updated_at";
+
+/// Segment tel que lu depuis SQLite.
+#[derive(Debug, FromRow)]
+struct SegmentRow {
+    name: String,
+    expression: String,
+    description: Option<String>,
+    updated_at: String,
+}
+
+impl SegmentRow {
+    fn into_segment(self) -> Result<Segment, String> {
+        Ok(Segment {
+            name: self.name,
+            expression: self.expression,
+            description: self.description,
+            updated_at: DateTime::parse_from_rfc3339(&self.updated_at)
+                .map_err(|e| e.to_string())?
+                .with_timezone(&Utc),
+        })
+    }
+}
+
+/// Store SQLite des segments (même base que les guests).
+pub struct SqliteSegmentStore {
+    pool: SqlitePool,
+}
+
+impl SqliteSegmentStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SegmentRepository for SqliteSegmentStore {
+    async fn list(&self) -> Result<Vec<Segment>, RepositoryError> {
+        let rows = sqlx::query_as::<_, SegmentRow>(&format!(
+            "SELECT {SEGMENT_COLUMNS} FROM guest_segments ORDER BY name"
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        rows.into_iter()
+            .map(SegmentRow::into_segment)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RepositoryError::Other)
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<Segment>, RepositoryError> {
+        let row = sqlx::query_as::<_, SegmentRow>(&format!(
+            "SELECT {SEGMENT_COLUMNS} FROM guest_segments WHERE name = ?"
+        ))
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        row.map(SegmentRow::into_segment)
+            .transpose()
+            .map_err(RepositoryError::Other)
+    }
+
+    async fn save(&self, segment: &Segment) -> Result<bool, RepositoryError> {
+        let updated_at = segment
+            .updated_at
+            .to_rfc3339_opts(SecondsFormat::Millis, true);
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        let existed = sqlx::query_scalar::<_, i64>("SELECT 1 FROM guest_segments WHERE name = ?")
+            .bind(&segment.name)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?
+            .is_some();
+        sqlx::query(
+            r#"
+            INSERT INTO guest_segments (name, expression, description, updated_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT (name) DO UPDATE SET expression = excluded.expression,
+                description = excluded.description, updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&segment.name)
+        .bind(&segment.expression)
+        .bind(&segment.description)
+        .bind(&updated_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        tx.commit()
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+
+        tracing::info!(name = %segment.name, created = !existed, "store: segment saved");
+        Ok(!existed)
+    }
+
+    async fn delete(&self, name: &str) -> Result<bool, RepositoryError> {
+        let result = sqlx::query("DELETE FROM guest_segments WHERE name = ?")
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| RepositoryError::Other(e.to_string()))?;
+        let deleted = result.rows_affected() > 0;
+        if deleted {
+            tracing::info!(name = %name, "store: segment deleted");
+        }
+        Ok(deleted)
+    }
+}
@@ -3,11 +3,19 @@
 
 use std::sync::Arc;
 
-use crate::domain::{GuestRepository, ItemRepository};
+use crate::domain::{
+    AttributeRepository, GuestRepository, IdempotencyRepository, ItemRepository,
+    RelationshipRepository, SegmentRepository,
+};
 use sqlx::SqlitePool;
 
+use super::attribute::SqliteAttributeStore;
+use super::cipher::{Pii, PiiCipher};
 use super::guest::SqliteGuestStore;
+use super::idempotency::SqliteIdempotencyStore;
 use super::item::MemoryItemStore;
+use super::relationship::SqliteRelationshipStore;
+use super::segment::SqliteSegmentStore;
 
 /// Store agrégé : une structure dont chaque champ satisfait une interface du domaine.
 pub struct Store {
@@ -15,13 +23,27 @@ pub struct Store {
     pub items: Arc<dyn ItemRepository>,
     /// Store des guests (SQLite).
     pub guests: Arc<dyn GuestRepository>,
+    /// Registre des attributs personnalisés des guests (SQLite).
+    pub attributes: Arc<dyn AttributeRepository>,
+    /// Segments de guests enregistrés (SQLite).
+    pub segments: Arc<dyn SegmentRepository>,
+    /// Relations entre guests (SQLite).
+    pub relationships: Arc<dyn RelationshipRepository>,
+    /// Clés d'idempotence des requêtes POST (SQLite, réponses chiffrées comme les guests).
+    pub idempotency: Arc<dyn IdempotencyRepository>,
 }
 
 impl Store {
-    pub fn new(pool: SqlitePool) -> Self {
+    /// `cipher` : chiffrement au repos des données personnelles des guests (None = stockage en clair).
+    pub fn new(pool: SqlitePool, cipher: Option<PiiCipher>) -> Self {
+        let pii = Pii::new(cipher);
         Self {
             items: Arc::new(MemoryItemStore::default()),
-            guests: Arc::new(SqliteGuestStore::new(pool)),
+            guests: Arc::new(SqliteGuestStore::new(pool.clone(), pii.clone())),
+            attributes: Arc::new(SqliteAttributeStore::new(pool.clone())),
+            segments: Arc::new(SqliteSegmentStore::new(pool.clone())),
+            relationships: Arc::new(SqliteRelationshipStore::new(pool.clone())),
+            idempotency: Arc::new(SqliteIdempotencyStore::new(pool, pii)),
         }
     }
 }
@@ -31,6 +53,10 @@ impl Clone for Store {
         Self {
             items: Arc::clone(&self.items),
             guests: Arc::clone(&self.guests),
+            attributes: Arc::clone(&self.attributes),
+            segments: Arc::clone(&self.segments),
+            relationships: Arc::clone(&self.relationships),
+            idempotency: Arc::clone(&self.idempotency),
         }
     }
 }